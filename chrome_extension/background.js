@@ -12,6 +12,11 @@ function safeSend(msg) {
   }
 }
 
+// Reconnect with the new token as soon as it is saved in the popup
+chrome.storage.onChanged.addListener((changes) => {
+  if (changes.leoToken && socket) socket.close();
+});
+
 function connect() {
   console.log("Leo Link: Connecting to " + SERVER_URL);
   try {
@@ -22,18 +27,31 @@ function connect() {
     return;
   }
 
-  socket.onopen = () => {
-    console.log("Leo Link: Connected! 🦁");
+  socket.onopen = async () => {
+    console.log("Leo Link: Connected! Pairing...");
     reconnectDelay = 1000;
-    chrome.action.setBadgeText({ text: "ON" });
-    chrome.action.setBadgeBackgroundColor({ color: "#4CAF50" });
-    safeSend({ type: "hello", message: "Extension connected" });
+    // Leo rejects connections whose first message doesn't carry the pairing token
+    const { leoToken } = await chrome.storage.local.get('leoToken');
+    safeSend({ type: "auth", token: leoToken || "" });
   };
 
   socket.onmessage = async (event) => {
     console.log("Leo Link: Command received:", event.data);
     try {
       const command = JSON.parse(event.data);
+      if (command.type === "auth_ok") {
+        console.log("Leo Link: Paired! 🦁");
+        chrome.action.setBadgeText({ text: "ON" });
+        chrome.action.setBadgeBackgroundColor({ color: "#4CAF50" });
+        safeSend({ type: "hello", message: "Extension connected" });
+        return;
+      }
+      if (command.type === "error") {
+        console.warn("Leo Link: " + command.message + " - set the token from 'leo status' in the popup");
+        chrome.action.setBadgeText({ text: "KEY" });
+        chrome.action.setBadgeBackgroundColor({ color: "#F59E0B" });
+        return;
+      }
       handleCommand(command);
     } catch (e) {
       console.error("Leo Link: Failed to parse command", e);
@@ -55,9 +73,11 @@ function connect() {
   };
 }
 
+// Replies carry the command's id (when Leo sent one) so Leo can match them to the request
 async function handleCommand(cmd) {
   const action = cmd.action;
-  safeSend({ type: "ack", action: action, status: "starting" });
+  const id = cmd.id;
+  safeSend({ type: "ack", id, action: action, status: "starting" });
 
   try {
     if (action === "open") {
@@ -66,7 +86,7 @@ async function handleCommand(cmd) {
         url = "https://" + url;
       }
       await chrome.tabs.create({ url: url });
-      safeSend({ type: "result", action: action, status: "success", message: "Opened " + url });
+      safeSend({ type: "result", id, action: action, status: "success", message: "Opened " + url });
     }
     else if (action === "screenshot" || action === "moment") {
       const [tab] = await chrome.tabs.query({ active: true, currentWindow: true });
@@ -87,9 +107,9 @@ async function handleCommand(cmd) {
         });
         data.page = results[0].result;
       }
-      safeSend({ type: "result", action: action, status: "success", data: data });
+      safeSend({ type: "result", id, action: action, status: "success", data: data });
     }
-    else if (action === "click" || action === "type" || action === "read" || action === "scroll" || action === "get_elements" || action === "wait") {
+    else if (action === "click" || action === "type" || action === "read" || action === "scroll" || action === "get_elements" || action === "wait" || action === "fill_form" || action === "read_selection") {
       const [tab] = await chrome.tabs.query({ active: true, currentWindow: true });
       if (!tab) throw new Error("No active tab found");
 
@@ -108,10 +128,10 @@ async function handleCommand(cmd) {
       });
 
       const output = result[0]?.result;
-      safeSend({ type: "result", action: action, status: "success", data: output });
+      safeSend({ type: "result", id, action: action, status: "success", data: output });
     }
   } catch (err) {
-    safeSend({ type: "error", action: action, message: err.message });
+    safeSend({ type: "error", id, action: action, message: err.message });
   }
 }
 
@@ -165,8 +185,31 @@ function executePageAction(cmd) {
     });
   }
 
+  // Returns [{ text, selector, role }]; selector is anything findElement accepts
   if (cmd.action === "get_elements") {
-    const rawItems = Array.from(document.querySelectorAll('button, a, input, select, [role="button"]'))
+    const selectorFor = (el, text) => {
+      const tag = el.tagName.toLowerCase();
+      if (el.id) return "#" + CSS.escape(el.id);
+      const name = el.getAttribute('name');
+      if (name) return `${tag}[name="${CSS.escape(name)}"]`;
+      const label = el.getAttribute('aria-label');
+      if (label) return `${tag}[aria-label="${CSS.escape(label)}"]`;
+      return text;
+    };
+    const roleOf = (el) => {
+      const tag = el.tagName.toLowerCase();
+      if (el.getAttribute('role')) return el.getAttribute('role');
+      if (tag === 'a') return 'link';
+      if (tag === 'select') return 'combobox';
+      if (tag === 'input') {
+        if (['button', 'submit', 'reset'].includes(el.type)) return 'button';
+        if (el.type === 'checkbox' || el.type === 'radio') return el.type;
+        return 'textbox';
+      }
+      return tag;
+    };
+
+    const rawItems = Array.from(document.querySelectorAll('button, a, input, select, textarea, [role="button"]'))
       .filter(el => {
         const rect = el.getBoundingClientRect();
         return rect.width > 0 && rect.height > 0 && window.getComputedStyle(el).visibility !== 'hidden';
@@ -184,19 +227,14 @@ function executePageAction(cmd) {
         if (el.tagName === 'BUTTON') priority += 2;
         if (el.role === 'button') priority += 2;
 
-        return {
-          tag: el.tagName.toLowerCase(),
-          text,
-          type: el.type || "",
-          id: el.id || "",
-          priority
-        };
+        return { text, selector: selectorFor(el, text), role: roleOf(el), priority };
       })
       .filter(item => item.text.length > 0)
       .sort((a, b) => b.priority - a.priority)
-      .slice(0, 30); // Cap at 30 most relevant elements
+      .slice(0, 50) // Cap at the 50 most relevant elements (Leo's MAX_ELEMENTS)
+      .map(({ text, selector, role }) => ({ text, selector, role }));
 
-    return JSON.stringify(items);
+    return items;
   }
 
   if (cmd.action === "click") {
@@ -224,6 +262,39 @@ function executePageAction(cmd) {
       throw new Error("Element not found: " + cmd.selector);
     }
   }
+  else if (cmd.action === "fill_form") {
+    // All fields in one round-trip: { filled: [selector], missing: [selector] }
+    const filled = [];
+    const missing = [];
+    for (const [selector, value] of Object.entries(cmd.fields || {})) {
+      const el = findElement(selector);
+      if (!el) {
+        missing.push(selector);
+        continue;
+      }
+      if (el.type === "checkbox" || el.type === "radio") {
+        el.checked = ["true", "on", "yes", "1"].includes(String(value).toLowerCase());
+      } else if (el.tagName === "SELECT") {
+        const option = Array.from(el.options).find(o => o.value === value || o.text.trim() === value);
+        el.value = option ? option.value : value;
+      } else {
+        el.value = value;
+      }
+      highlightElement(el);
+      el.dispatchEvent(new Event('input', { bubbles: true }));
+      el.dispatchEvent(new Event('change', { bubbles: true }));
+      filled.push(selector);
+    }
+    return { filled, missing };
+  }
+  else if (cmd.action === "read_selection") {
+    // Selections inside inputs and textareas aren't part of window.getSelection()
+    const active = document.activeElement;
+    if (active && typeof active.selectionStart === "number" && active.selectionEnd > active.selectionStart) {
+      return active.value.substring(active.selectionStart, active.selectionEnd);
+    }
+    return window.getSelection().toString();
+  }
   else if (cmd.action === "read") {
     try {
       if (typeof Readability === "undefined") {
@@ -25,6 +25,18 @@ document.addEventListener('DOMContentLoaded', () => {
     // Initial check
     checkStatus();
 
+    // Pairing token: Leo only accepts commands from an extension that knows it
+    const tokenInput = document.getElementById('token-input');
+    chrome.storage.local.get('leoToken', ({ leoToken }) => {
+        if (leoToken) tokenInput.value = leoToken;
+    });
+    document.getElementById('save-token').addEventListener('click', () => {
+        chrome.storage.local.set({ leoToken: tokenInput.value.trim() }, () => {
+            statusText.innerText = "Token saved, reconnecting...";
+            setTimeout(checkStatus, 1000);
+        });
+    });
+
     // Refresh status on click
     document.getElementById('refresh-btn').addEventListener('click', () => {
         statusText.innerText = "Checking...";
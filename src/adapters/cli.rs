@@ -3,16 +3,159 @@
 //! Provides a clean channel abstraction for CLI interaction, extracted
 //! from the main binary for better separation of concerns.
 
-use std::io::{self, BufRead, Write};
+use std::borrow::Cow;
+use std::path::PathBuf;
 
-use crate::agent::{AgentLoop, Context, LlmClient, Message, Response};
+use rustyline::completion::Completer;
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::Validator;
+use rustyline::{Cmd, Editor, Helper, KeyEvent};
+
+use crate::agent::{AgentLoop, Context, LlmClient, Message, Response, Role};
+use crate::error::Error;
 use crate::Result;
 
+/// Prompt shown before user input.
+const PROMPT: &str = "  You: ";
+
+/// Prompt shown while a multiline message is being entered.
+const CONTINUATION_PROMPT: &str = "  ...: ";
+
+/// Fence that opens and closes a multiline block.
+const FENCE: &str = "\"\"\"";
+
+/// Slash commands handled by the REPL (also offered by tab completion).
+const SLASH_COMMANDS: &[&str] = &["/retry", "/undo", "/clear", "/tools", "/memory", "/save", "/verbose", "/help", "/exit", "/quit"];
+
+/// Longest tool result preview shown in verbose mode.
+const TRACE_PREVIEW_CHARS: usize = 120;
+
+/// Path of the persisted REPL history (`~/.leo/history`).
+pub fn history_path() -> PathBuf {
+    crate::config::config_dir().join("history")
+}
+
+/// Assembles multiline messages from individual input lines.
+///
+/// A line ending in `\` continues on the next line, and a `"""` fence
+/// collects everything up to the closing fence.
+#[derive(Default)]
+struct InputBuffer {
+    lines: Vec<String>,
+    fenced: bool,
+}
+
+impl InputBuffer {
+    /// Feed one line; returns the assembled message once it is complete.
+    fn push(&mut self, line: &str) -> Option<String> {
+        if self.fenced {
+            if line.trim() == FENCE {
+                self.fenced = false;
+                return Some(self.take());
+            }
+            self.lines.push(line.to_string());
+            return None;
+        }
+
+        if self.lines.is_empty() {
+            if let Some(rest) = line.trim_start().strip_prefix(FENCE) {
+                // Single-line form: """text"""
+                if let Some(inner) = rest.strip_suffix(FENCE) {
+                    return Some(inner.to_string());
+                }
+                self.fenced = true;
+                if !rest.trim().is_empty() {
+                    self.lines.push(rest.to_string());
+                }
+                return None;
+            }
+        }
+
+        if let Some(continued) = line.strip_suffix('\\') {
+            self.lines.push(continued.to_string());
+            return None;
+        }
+
+        self.lines.push(line.to_string());
+        Some(self.take())
+    }
+
+    /// Whether a multiline message is still being entered.
+    fn is_pending(&self) -> bool {
+        self.fenced || !self.lines.is_empty()
+    }
+
+    fn take(&mut self) -> String {
+        std::mem::take(&mut self.lines).join("\n")
+    }
+}
+
+/// Line-editor helper: slash-command completion and a colored prompt.
+struct ReplHelper;
+
+impl Completer for ReplHelper {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &rustyline::Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        let prefix = &line[..pos];
+        if !prefix.starts_with('/') || prefix.contains(' ') {
+            return Ok((pos, Vec::new()));
+        }
+
+        let matches = SLASH_COMMANDS
+            .iter()
+            .filter(|cmd| cmd.starts_with(prefix))
+            .map(|cmd| cmd.to_string())
+            .collect();
+        Ok((0, matches))
+    }
+}
+
+impl Highlighter for ReplHelper {
+    fn highlight_prompt<'b, 's: 'b, 'p: 'b>(&'s self, prompt: &'p str, default: bool) -> Cow<'b, str> {
+        if default {
+            Cow::Owned(prompt.replace("You", "\x1b[1;34mYou\x1b[0m"))
+        } else {
+            Cow::Borrowed(prompt)
+        }
+    }
+}
+
+impl Hinter for ReplHelper {
+    type Hint = String;
+}
+
+impl Validator for ReplHelper {}
+
+impl Helper for ReplHelper {}
+
+/// Print the tool calls behind a response, one line each.
+fn print_trace(response: &Response) {
+    for call in &response.tool_trace {
+        let result = call.result.as_deref().unwrap_or("").replace('\n', " ");
+        let preview: String = result.chars().take(TRACE_PREVIEW_CHARS).collect();
+        let ellipsis = if result.chars().count() > TRACE_PREVIEW_CHARS { "…" } else { "" };
+        println!("  \x1b[2m⚙ {}({}) → {}{}\x1b[0m", call.name, call.arguments, preview, ellipsis);
+    }
+}
+
 /// CLI channel for interactive agent sessions.
 pub struct CliChannel<C: LlmClient> {
     agent: AgentLoop<C>,
     context: Context,
     history: Vec<Message>,
+    /// The most recent message sent to the agent, for `/retry`.
+    last_input: Option<String>,
+    /// Print the tool-call trace before each reply.
+    verbose: bool,
 }
 
 impl<C: LlmClient> CliChannel<C> {
@@ -22,63 +165,237 @@ impl<C: LlmClient> CliChannel<C> {
             agent,
             context,
             history: Vec::new(),
+            last_input: None,
+            verbose: false,
         }
     }
 
     /// Run a single message and return the response.
     pub async fn run_once(&mut self, message: &str) -> Result<Response> {
+        self.last_input = Some(message.to_string());
         let msg = Message::user(message);
         let response = self.agent.run(&self.history, msg.clone(), &mut self.context).await?;
-        
+
         // Update history
         self.history.push(msg);
         self.history.push(Message::assistant(response.content.clone()));
-        
+
+        if let Err(e) = self.context.log_exchange(message, &response.content) {
+            tracing::warn!("Failed to write daily log: {}", e);
+        }
+
         Ok(response)
     }
 
+    /// Run the last message again, replacing its reply in the history.
+    ///
+    /// Returns `None` when nothing has been sent yet. If the last run failed
+    /// there is no reply to replace, and the message is simply sent again.
+    pub async fn retry(&mut self) -> Option<Result<Response>> {
+        let message = self.last_input.clone()?;
+        if let [.., user, reply] = self.history.as_slice() {
+            if user.role == Role::User && user.content == message && reply.role == Role::Assistant {
+                self.history.truncate(self.history.len() - 2);
+            }
+        }
+        Some(self.run_once(&message).await)
+    }
+
+    /// Remove the last user message and reply from the history, returning the message.
+    pub fn undo(&mut self) -> Option<String> {
+        let [.., user, reply] = self.history.as_slice() else {
+            return None;
+        };
+        if user.role != Role::User || reply.role != Role::Assistant {
+            return None;
+        }
+        let message = user.content.clone();
+        self.history.truncate(self.history.len() - 2);
+
+        // /retry now means the message before the one undone
+        self.last_input = self.history.iter()
+            .rev()
+            .find(|m| m.role == Role::User)
+            .map(|m| m.content.clone());
+
+        if let Err(e) = self.context.log_undo(&message) {
+            tracing::warn!("Failed to write daily log: {}", e);
+        }
+        Some(message)
+    }
+
+    /// Print a reply (with its tool trace in verbose mode) or the error.
+    fn show(&self, result: Result<Response>) {
+        match result {
+            Ok(response) => {
+                if self.verbose {
+                    print_trace(&response);
+                }
+                println!("\n  \x1b[1;32mLeo\x1b[0m: {}\n", response.content);
+                for path in &response.media {
+                    println!("  📎 {}", path);
+                }
+            }
+            Err(e) => {
+                println!("\n  \x1b[1;31mError\x1b[0m: {e}\n");
+            }
+        }
+    }
+
     /// Run interactive REPL loop.
+    ///
+    /// Uses a line editor with arrow-key editing, history persisted to
+    /// `~/.leo/history`, tab completion for slash commands and Ctrl+L to clear.
+    /// Multiline messages are entered with a trailing `\` or a `"""` fence.
     pub async fn run_interactive(&mut self) -> Result<()> {
-        let stdin = io::stdin();
-        let mut stdout = io::stdout();
+        let mut editor: Editor<ReplHelper, DefaultHistory> = Editor::new()
+            .map_err(|e| Error::Other(format!("Failed to initialize line editor: {e}")))?;
+        editor.set_helper(Some(ReplHelper));
+        editor.bind_sequence(KeyEvent::ctrl('L'), Cmd::ClearScreen);
+
+        let history_file = history_path();
+        // Missing history on first run is expected
+        let _ = editor.load_history(&history_file);
+
+        let mut buffer = InputBuffer::default();
 
         loop {
-            // Print prompt
-            print!("\n> ");
-            stdout.flush()?;
-
-            // Read input
-            let mut line = String::new();
-            if stdin.lock().read_line(&mut line)? == 0 {
-                // EOF
-                break;
-            }
+            let prompt = if buffer.is_pending() { CONTINUATION_PROMPT } else { PROMPT };
+            let line = match editor.readline(prompt) {
+                Ok(line) => line,
+                Err(ReadlineError::Interrupted) => {
+                    buffer = InputBuffer::default();
+                    println!("  ! Press Ctrl+D or type 'exit' to quit");
+                    continue;
+                }
+                Err(ReadlineError::Eof) => break,
+                Err(e) => return Err(Error::Other(format!("Failed to read input: {e}"))),
+            };
+
+            let Some(text) = buffer.push(&line) else {
+                continue;
+            };
 
-            let input = line.trim();
+            let input = text.trim();
             if input.is_empty() {
                 continue;
             }
 
+            let _ = editor.add_history_entry(input);
+            if let Some(parent) = history_file.parent() {
+                let _ = std::fs::create_dir_all(parent);
+            }
+            let _ = editor.save_history(&history_file);
+
             // Check for exit commands
             if matches!(input.to_lowercase().as_str(), "exit" | "quit" | "q") {
-                println!("Goodbye! 👋");
                 break;
             }
 
-            // Process message
-            match self.run_once(input).await {
-                Ok(response) => {
-                    println!("\n{}", response.content);
+            // /retry needs the agent, so it is handled here rather than in handle_command
+            if input == "/retry" {
+                match self.retry().await {
+                    Some(result) => self.show(result),
+                    None => println!("  Nothing to retry yet\n"),
                 }
-                Err(e) => {
-                    eprintln!("\nError: {e}");
+                continue;
+            }
+
+            // Slash commands short-circuit before reaching the agent
+            if input.starts_with('/') {
+                if !self.handle_command(input) {
+                    break;
                 }
+                continue;
             }
+
+            // Process message
+            let result = self.run_once(input).await;
+            self.show(result);
         }
 
+        println!("\n  → Bye!");
         Ok(())
     }
 
+    /// Handle a slash command. Returns `false` when the REPL should exit.
+    fn handle_command(&mut self, input: &str) -> bool {
+        let (command, arg) = match input.split_once(char::is_whitespace) {
+            Some((cmd, rest)) => (cmd, rest.trim()),
+            None => (input, ""),
+        };
+
+        match command {
+            "/exit" | "/quit" => return false,
+            "/undo" => match self.undo() {
+                Some(message) => {
+                    let preview: String = message.chars().take(TRACE_PREVIEW_CHARS).collect();
+                    println!("  ✔ Removed your last message and its reply: {}\n", preview);
+                }
+                None => println!("  Nothing to undo\n"),
+            },
+            "/clear" => {
+                self.clear_history();
+                println!("  ✔ Conversation history cleared\n");
+            }
+            "/tools" => {
+                let mut names = self.context.tool_runner.tool_names();
+                names.sort_unstable();
+                println!("  Tools ({}): {}\n", names.len(), names.join(", "));
+            }
+            "/memory" => match self.context.memory.get_context() {
+                Ok(memory) if memory.is_empty() => println!("  Memory is empty.\n"),
+                Ok(memory) => println!("\n{}\n", memory),
+                Err(e) => println!("  \x1b[1;31mError\x1b[0m: {e}\n"),
+            },
+            "/save" => {
+                if arg.is_empty() {
+                    println!("  Usage: /save <file>\n");
+                } else {
+                    match std::fs::write(arg, self.transcript()) {
+                        Ok(()) => println!("  ✔ Transcript saved to {}\n", arg),
+                        Err(e) => println!("  \x1b[1;31mError\x1b[0m: Failed to save {}: {e}\n", arg),
+                    }
+                }
+            }
+            "/verbose" => {
+                self.verbose = !self.verbose;
+                let state = if self.verbose { "on" } else { "off" };
+                println!("  ✔ Tool trace {}\n", state);
+            }
+            "/help" => {
+                println!("  /retry         Regenerate the reply to your last message");
+                println!("  /undo          Remove your last message and its reply");
+                println!("  /clear         Clear conversation history");
+                println!("  /tools         List available tools");
+                println!("  /memory        Show memory context");
+                println!("  /save <file>   Save the transcript to a file");
+                println!("  /verbose       Toggle showing tool calls behind each reply");
+                println!("  /exit          Quit\n");
+            }
+            other => println!("  Unknown command: {} (try /help)\n", other),
+        }
+
+        true
+    }
+
+    /// Render the conversation so far as a Markdown transcript.
+    pub fn transcript(&self) -> String {
+        self.history
+            .iter()
+            .map(|m| {
+                let speaker = match m.role {
+                    Role::User => "You",
+                    Role::Assistant => "Leo",
+                    Role::System => "System",
+                    Role::Tool => "Tool",
+                };
+                format!("**{}**: {}", speaker, m.content)
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+
     /// Clear conversation history.
     pub fn clear_history(&mut self) {
         self.history.clear();
@@ -89,3 +406,101 @@ impl<C: LlmClient> CliChannel<C> {
         self.history.len()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::llm::FakeLlmClient;
+
+    fn channel(responses: Vec<&str>) -> CliChannel<FakeLlmClient> {
+        CliChannel::new(AgentLoop::new(FakeLlmClient::new(responses), 10), Context::test())
+    }
+
+    #[tokio::test]
+    async fn test_clear_command() {
+        let mut cli = channel(vec!["Hi!"]);
+        cli.run_once("Hello").await.unwrap();
+        assert_eq!(cli.history_len(), 2);
+
+        assert!(cli.handle_command("/clear"));
+        assert_eq!(cli.history_len(), 0);
+        assert!(!cli.handle_command("/exit"));
+    }
+
+    #[tokio::test]
+    async fn test_retry_replaces_last_reply() {
+        let mut cli = channel(vec!["First try", "Second try"]);
+        assert!(cli.retry().await.is_none());
+
+        cli.run_once("Hello").await.unwrap();
+        let response = cli.retry().await.unwrap().unwrap();
+        assert_eq!(response.content, "Second try");
+        assert_eq!(cli.transcript(), "**You**: Hello\n\n**Leo**: Second try");
+    }
+
+    #[tokio::test]
+    async fn test_undo_removes_last_exchange() {
+        let mut cli = channel(vec!["One", "Two", "Three"]);
+        assert!(cli.undo().is_none());
+
+        cli.run_once("First").await.unwrap();
+        cli.run_once("Second").await.unwrap();
+        assert_eq!(cli.undo().as_deref(), Some("Second"));
+        assert_eq!(cli.transcript(), "**You**: First\n\n**Leo**: One");
+
+        // /retry now re-runs the message before the undone one
+        cli.retry().await.unwrap().unwrap();
+        assert_eq!(cli.transcript(), "**You**: First\n\n**Leo**: Three");
+
+        assert!(cli.handle_command("/undo"));
+        assert_eq!(cli.history_len(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_save_command_writes_transcript() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = tmp.path().join("chat.md");
+
+        let mut cli = channel(vec!["Hi there!"]);
+        cli.run_once("Hello").await.unwrap();
+        assert!(cli.handle_command(&format!("/save {}", path.display())));
+
+        let saved = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(saved, "**You**: Hello\n\n**Leo**: Hi there!");
+    }
+
+    #[test]
+    fn test_single_line_input() {
+        let mut buffer = InputBuffer::default();
+        assert_eq!(buffer.push("hello").as_deref(), Some("hello"));
+        assert!(!buffer.is_pending());
+    }
+
+    #[test]
+    fn test_backslash_continuation() {
+        let mut buffer = InputBuffer::default();
+        assert_eq!(buffer.push("first \\"), None);
+        assert!(buffer.is_pending());
+        assert_eq!(buffer.push("second").as_deref(), Some("first \nsecond"));
+    }
+
+    #[test]
+    fn test_fenced_block() {
+        let mut buffer = InputBuffer::default();
+        assert_eq!(buffer.push("\"\"\""), None);
+        assert_eq!(buffer.push("fn main() {"), None);
+        assert_eq!(buffer.push("    println!(\"hi\"); \\"), None);
+        assert_eq!(buffer.push("}"), None);
+        assert_eq!(
+            buffer.push("\"\"\"").as_deref(),
+            Some("fn main() {\n    println!(\"hi\"); \\\n}")
+        );
+        assert!(!buffer.is_pending());
+    }
+
+    #[test]
+    fn test_inline_fence() {
+        let mut buffer = InputBuffer::default();
+        assert_eq!(buffer.push("\"\"\"quick\"\"\"").as_deref(), Some("quick"));
+    }
+}
@@ -0,0 +1,132 @@
+//! Gateway health endpoint
+//!
+//! A tiny HTTP server for liveness probes (systemd, containers).
+//! `GET /health` returns uptime and per-channel connection state as JSON,
+//! with status 200 when every channel is connected and 503 otherwise.
+//! `GET /metrics` serves Prometheus metrics (see [`crate::metrics`]).
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use serde_json::{json, Value};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tracing::debug;
+use crate::Result;
+
+/// Shared gateway health state, updated by channels as they connect.
+pub struct HealthState {
+    started: Instant,
+    channels: Mutex<BTreeMap<String, bool>>,
+}
+
+impl HealthState {
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            channels: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Record whether a channel is currently connected.
+    pub fn set_connected(&self, channel: &str, connected: bool) {
+        self.channels.lock().unwrap().insert(channel.to_string(), connected);
+    }
+
+    /// True when at least one channel is registered and all are connected.
+    pub fn is_healthy(&self) -> bool {
+        let channels = self.channels.lock().unwrap();
+        !channels.is_empty() && channels.values().all(|c| *c)
+    }
+
+    /// JSON body served by the endpoint.
+    pub fn report(&self) -> Value {
+        let channels = self.channels.lock().unwrap().clone();
+        json!({
+            "status": if self.is_healthy() { "ok" } else { "degraded" },
+            "uptime_secs": self.started.elapsed().as_secs(),
+            "channels": channels,
+        })
+    }
+}
+
+impl Default for HealthState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Serve health requests on `listener` until the task is dropped.
+pub async fn serve(listener: TcpListener, state: Arc<HealthState>) -> Result<()> {
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let state = state.clone();
+        tokio::spawn(async move {
+            if let Err(e) = respond(stream, &state).await {
+                debug!("Health request failed: {}", e);
+            }
+        });
+    }
+}
+
+async fn respond(mut stream: TcpStream, state: &HealthState) -> std::io::Result<()> {
+    let mut buffer = [0u8; 1024];
+    let n = stream.read(&mut buffer).await?;
+    let request = String::from_utf8_lossy(&buffer[..n]);
+    let path = request.split_whitespace().nth(1).unwrap_or("/");
+
+    let (status, content_type, body) = match path {
+        "/" | "/health" => {
+            let status = if state.is_healthy() { "200 OK" } else { "503 Service Unavailable" };
+            (status, "application/json", state.report().to_string())
+        }
+        "/metrics" => ("200 OK", "text/plain; version=0.0.4", crate::metrics::metrics().render()),
+        _ => ("404 Not Found", "application/json", json!({ "error": "not found" }).to_string()),
+    };
+
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.flush().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_health_endpoint() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let state = Arc::new(HealthState::new());
+        state.set_connected("telegram", false);
+        tokio::spawn(serve(listener, state.clone()));
+
+        let url = format!("http://{}/health", addr);
+        let response = reqwest::get(&url).await.unwrap();
+        assert_eq!(response.status().as_u16(), 503);
+        let body: Value = response.json().await.unwrap();
+        assert_eq!(body["status"], "degraded");
+        assert_eq!(body["channels"]["telegram"], false);
+
+        state.set_connected("telegram", true);
+        let response = reqwest::get(&url).await.unwrap();
+        assert_eq!(response.status().as_u16(), 200);
+        let body: Value = response.json().await.unwrap();
+        assert_eq!(body["status"], "ok");
+        assert!(body["uptime_secs"].is_u64());
+
+        crate::metrics::metrics().requests.inc();
+        let metrics = reqwest::get(format!("http://{}/metrics", addr)).await.unwrap();
+        assert_eq!(metrics.status().as_u16(), 200);
+        assert!(metrics.text().await.unwrap().contains("leo_requests_total"));
+
+        let missing = reqwest::get(format!("http://{}/nope", addr)).await.unwrap();
+        assert_eq!(missing.status().as_u16(), 404);
+    }
+}
@@ -15,7 +15,9 @@
 //! 3. Add to [`ChannelRegistry`]
 
 pub mod cli;
+pub mod health;
 pub mod telegram;
+pub mod transcribe;
 
 use crate::config::Config;
 
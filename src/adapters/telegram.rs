@@ -1,40 +1,133 @@
 //! Telegram adapter using teloxide
 
 use teloxide::prelude::*;
-use teloxide::types::{MediaKind, MessageKind};
+use teloxide::net::Download;
+use teloxide::dispatching::ShutdownToken;
+use teloxide::types::{
+    CallbackQuery, InlineKeyboardButton, InlineKeyboardMarkup, InputFile, MediaKind, MessageId, MessageKind,
+    UpdateKind,
+};
 use crate::Result;
 // use crate::error::Error;
 use crate::config::Config;
 use super::Channel;
-use crate::agent::{AgentLoop, Context, Message, LlmClient};
-use tokio::sync::Mutex;
+use super::health::HealthState;
+use crate::agent::{AgentLoop, Context, Message, LlmClient, OnText, Response};
+use crate::tools::ApprovalHandler;
+use tokio::sync::{oneshot, Mutex};
 use std::sync::Arc;
-use tracing::{info, error, debug};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tracing::{info, error, debug, warn};
 use std::collections::HashMap;
 
+/// How long a confirmation waits for a button press before the call is declined
+const APPROVAL_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Telegram clears a chat action after about 5 seconds, so it is re-sent this often
+const TYPING_REFRESH: Duration = Duration::from_secs(4);
+
+/// How often a streamed reply is edited; Telegram rate-limits edits to about one per second
+const STREAM_EDIT_INTERVAL: Duration = Duration::from_millis(1200);
+
+/// Telegram's limit on the length of a message
+const MAX_MESSAGE_CHARS: usize = 4096;
+
+/// History kept per chat, in multiples of `history_window`; older messages only
+/// reach the model through the history summary
+const HISTORY_KEPT_WINDOWS: usize = 2;
+
+/// Longest argument preview shown in a confirmation message
+const MAX_APPROVAL_PREVIEW: usize = 3000;
+
+/// Conversation history of each chat, locked per chat so one chat's run doesn't block the others
+type ChatHistories = Arc<Mutex<HashMap<ChatId, Arc<Mutex<Vec<Message>>>>>>;
+
+/// Confirmations waiting for a button press, keyed by the id in their callback data
+type PendingApprovals = Arc<std::sync::Mutex<HashMap<u64, PendingApproval>>>;
+
+/// A confirmation that only the user who made the request, in the same chat, may answer
+struct PendingApproval {
+    chat_id: ChatId,
+    user_id: Option<UserId>,
+    sender: oneshot::Sender<bool>,
+}
+
+impl PendingApproval {
+    fn answerable_by(&self, chat_id: Option<ChatId>, user_id: UserId) -> bool {
+        chat_id == Some(self.chat_id) && self.user_id.is_none_or(|id| id == user_id)
+    }
+}
+
 /// Telegram channel adapter
 pub struct TelegramChannel<C: LlmClient + 'static> {
     bot: Bot,
     config: Config,
     agent_loop: Arc<AgentLoop<C>>,
-    // Persistent context to keep tools (and history) alive
-    context: Arc<Mutex<Context>>,
+    // Per-chat contexts, kept to keep tools (and history summaries) alive
+    chat_contexts: Arc<Mutex<HashMap<ChatId, Arc<Mutex<Context>>>>>,
     // Simple in-memory session lock to prevent concurrent processing for same chat
     locks: Arc<Mutex<HashMap<ChatId, Arc<Mutex<()>>>>>,
     // Conversation history
-    history: Arc<Mutex<HashMap<ChatId, Vec<Message>>>>,
+    history: ChatHistories,
+    // Reports connection state to the gateway health endpoint
+    health: Option<Arc<HealthState>>,
+    // Set once the dispatcher is running, used by `stop`
+    shutdown: Arc<std::sync::Mutex<Option<ShutdownToken>>>,
+    // Confirmations sent with `telegram.confirm_actions`, awaiting a button press
+    approvals: PendingApprovals,
 }
 
 impl<C: LlmClient + Clone> TelegramChannel<C> {
-    pub fn new(config: Config, agent_loop: AgentLoop<C>, context: Context) -> Self {
+    pub fn new(config: Config, agent_loop: AgentLoop<C>) -> Self {
         let bot = Bot::new(&config.telegram.token);
         Self {
             bot,
             config,
             agent_loop: Arc::new(agent_loop),
-            context: Arc::new(Mutex::new(context)),
+            chat_contexts: Arc::new(Mutex::new(HashMap::new())),
             locks: Arc::new(Mutex::new(HashMap::new())),
             history: Arc::new(Mutex::new(HashMap::new())),
+            health: None,
+            shutdown: Arc::new(std::sync::Mutex::new(None)),
+            approvals: Arc::new(std::sync::Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Report connection state to a gateway health endpoint.
+    pub fn with_health(mut self, health: Arc<HealthState>) -> Self {
+        self.health = Some(health);
+        self
+    }
+
+    /// Context for `chat_id`, rooted at the chat's own workspace when
+    /// `per_chat_workspace` is enabled and at the shared one otherwise.
+    ///
+    /// Every chat gets its own context, so a run waiting on a confirmation
+    /// never holds up the other chats.
+    async fn context_for(&self, chat_id: ChatId) -> Result<Arc<Mutex<Context>>> {
+        let mut contexts = self.chat_contexts.lock().await;
+        if let Some(ctx) = contexts.get(&chat_id) {
+            return Ok(ctx.clone());
+        }
+        
+        let workspace = if self.config.telegram.per_chat_workspace {
+            let workspace = session_workspace(&self.config, chat_id)?;
+            info!("Chat {} uses workspace {}", chat_id, workspace.display());
+            workspace
+        } else {
+            self.config.workspace.clone()
+        };
+        let mut ctx = Context::new_with_workspace(&self.config, &workspace)?;
+        ctx.tool_runner.register(crate::tools::SummarizeTool::new(self.agent_loop.limited_client()));
+        let ctx = Arc::new(Mutex::new(ctx));
+        contexts.insert(chat_id, ctx.clone());
+        Ok(ctx)
+    }
+
+    fn set_connected(&self, connected: bool) {
+        if let Some(health) = &self.health {
+            health.set_connected("telegram", connected);
         }
     }
 
@@ -48,11 +141,21 @@ impl<C: LlmClient + Clone> TelegramChannel<C> {
             return Ok(());
         }
 
-        // Get text content
+        // Get text content, transcribing voice messages when enabled
         let text = match message.kind {
             MessageKind::Common(ref common) => match &common.media_kind {
-                MediaKind::Text(media) => &media.text,
-                _ => return Ok(()), // Ignore non-text messages for now
+                MediaKind::Text(media) => media.text.clone(),
+                MediaKind::Voice(media) if self.config.telegram.transcription_enabled => {
+                    match self.transcribe_voice(&media.voice).await {
+                        Ok(text) => text,
+                        Err(e) => {
+                            error!("Voice transcription failed: {}", e);
+                            self.bot.send_message(chat_id, format!("Couldn't transcribe that voice message: {}", e)).await?;
+                            return Ok(());
+                        }
+                    }
+                }
+                _ => return Ok(()), // Ignore other media for now
             },
             _ => return Ok(()),
         };
@@ -61,40 +164,58 @@ impl<C: LlmClient + Clone> TelegramChannel<C> {
         let username = user.and_then(|u| u.username.as_deref()).unwrap_or("user");
         let status = crate::ui::ChannelStatus::start("telegram", username);
 
-        // Send "typing" action
-        let _ = self.bot.send_chat_action(chat_id, teloxide::types::ChatAction::Typing).await;
+        // Show "typing" for as long as the request takes
+        let typing = TypingIndicator::start(self.bot.clone(), chat_id);
 
-        // Session locking
-        let lock = {
-            let mut locks = self.locks.lock().await;
-            locks.entry(chat_id).or_insert_with(|| Arc::new(Mutex::new(()))).clone()
+        // Ask whoever sent this message to confirm mutating calls
+        let approval: Option<Arc<dyn ApprovalHandler>> = if self.config.telegram.confirm_actions {
+            Some(Arc::new(TelegramApprovalHandler {
+                bot: self.bot.clone(),
+                chat_id,
+                user_id: user.map(|u| u.id),
+                pending: self.approvals.clone(),
+            }))
+        } else {
+            None
         };
-        let _guard = lock.lock().await;
-
-        // Use persistent context
-        let mut ctx = self.context.lock().await;
-        
-        // Get history
-        let mut history_map = self.history.lock().await;
-        let user_history = history_map.entry(chat_id).or_insert_with(Vec::new);
         
         // Convert to Agent Message
-        let msg = Message::user(text);
+        let msg = Message::user(&text);
         
-        // Run Agent Loop with history
-        match self.agent_loop.run(user_history, msg.clone(), &mut ctx).await {
+        // Run Agent Loop with history, showing the answer as it streams in if enabled
+        let (result, live_reply) = if self.config.telegram.stream_replies {
+            let live = LiveReply::start(self.bot.clone(), chat_id);
+            let on_text = live.on_text();
+            let result = self.run_turn(chat_id, msg, approval, Some(&on_text)).await;
+            (result, live.finish().await)
+        } else {
+            (self.run_turn(chat_id, msg, approval, None).await, None)
+        };
+        drop(typing);
+        match result {
             Ok(response) => {
-                // Update history on success
-                user_history.push(msg);
-                user_history.push(Message::assistant(response.content.clone()));
-                
-                // Limit history size (optional, prevent infinite growth)
-                if user_history.len() > 50 {
-                    let remove_count = user_history.len() - 50;
-                    user_history.drain(0..remove_count);
+                if !response.content.trim().is_empty() {
+                    // Replace the streamed text with the answer's first part, or send it fresh
+                    let mut parts = split_message(&response.content).into_iter();
+                    let first = parts.next().unwrap_or_default();
+                    let edited = match live_reply {
+                        Some(id) => {
+                            let edited = self.bot.edit_message_text(chat_id, id, &first).await.is_ok();
+                            if !edited {
+                                let _ = self.bot.delete_message(chat_id, id).await;
+                            }
+                            edited
+                        }
+                        None => false,
+                    };
+                    if !edited {
+                        self.bot.send_message(chat_id, first).await?;
+                    }
+                    for part in parts {
+                        self.bot.send_message(chat_id, part).await?;
+                    }
                 }
-                
-                self.bot.send_message(chat_id, response.content).await?;
+                self.send_documents(chat_id, &response.media).await;
                 status.done();
             }
             Err(e) => {
@@ -107,6 +228,125 @@ impl<C: LlmClient + Clone> TelegramChannel<C> {
         Ok(())
     }
 
+    /// Run `msg` through the agent with this chat's context and history, and
+    /// add the exchange to the history on success.
+    ///
+    /// Only this chat's locks are held while the agent runs.
+    async fn run_turn(
+        &self,
+        chat_id: ChatId,
+        msg: Message,
+        approval: Option<Arc<dyn ApprovalHandler>>,
+        on_text: Option<&OnText<'_>>,
+    ) -> Result<Response> {
+        // Session locking
+        let lock = {
+            let mut locks = self.locks.lock().await;
+            locks.entry(chat_id).or_insert_with(|| Arc::new(Mutex::new(()))).clone()
+        };
+        let _guard = lock.lock().await;
+
+        let context = self.context_for(chat_id).await?;
+        let mut ctx = context.lock().await;
+        ctx.session = format!("telegram:{}", chat_id);
+        if approval.is_some() {
+            ctx.tool_runner.set_approval(approval);
+        }
+        
+        let history = {
+            let mut history_map = self.history.lock().await;
+            history_map.entry(chat_id).or_default().clone()
+        };
+        let mut history = history.lock().await;
+        
+        let response = match on_text {
+            Some(on_text) => self.agent_loop.run_streaming(&history, msg.clone(), &mut ctx, on_text).await?,
+            None => self.agent_loop.run(&history, msg.clone(), &mut ctx).await?,
+        };
+        
+        history.push(msg);
+        history.push(Message::assistant(response.content.clone()));
+        
+        // Limit history size; what falls out of the window is summarized first
+        let kept = self.config.history_window * HISTORY_KEPT_WINDOWS;
+        if history.len() > kept {
+            let remove_count = history.len() - kept;
+            history.drain(0..remove_count);
+        }
+        
+        Ok(response)
+    }
+
+    /// Download a voice message and transcribe it
+    async fn transcribe_voice(&self, voice: &teloxide::types::Voice) -> Result<String> {
+        let file = self.bot.get_file(&voice.file.id).await?;
+        // Removed when `audio` is dropped
+        let audio = tempfile::Builder::new().prefix("leo-voice-").suffix(".ogg").tempfile()?;
+
+        let mut destination = tokio::fs::File::from_std(audio.reopen()?);
+        self.bot.download_file(&file.path, &mut destination).await
+            .map_err(|e| crate::error::Error::Other(format!("Failed to download voice message: {}", e)))?;
+        tokio::io::AsyncWriteExt::flush(&mut destination).await?;
+        let text = super::transcribe::transcribe(&self.config.transcription, audio.path()).await?;
+        debug!("Transcribed {}s voice message: {} chars", voice.duration, text.len());
+        Ok(text)
+    }
+
+    /// Send each attached file as a document, telling the chat about any that fail
+    async fn send_documents(&self, chat_id: ChatId, media: &[String]) {
+        for path in media {
+            if let Err(e) = self.bot.send_document(chat_id, InputFile::file(path)).await {
+                error!("Failed to send {}: {}", path, e);
+                let name = std::path::Path::new(path)
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_else(|| path.clone());
+                let _ = self.bot.send_message(chat_id, format!("Couldn't send {}: {}", name, e)).await;
+            }
+        }
+    }
+
+    /// Resolve a confirmation from its Yes/No button
+    async fn handle_callback(&self, query: CallbackQuery) -> Result<()> {
+        let Some((id, approved)) = query.data.as_deref().and_then(parse_approval_data) else {
+            return Ok(());
+        };
+        if !self.is_allowed(Some(&query.from)) {
+            self.bot.answer_callback_query(query.id).text("You can't answer this").await?;
+            return Ok(());
+        }
+
+        // Only the requester may answer, and only in the chat that asked
+        let chat_id = query.message.as_ref().map(|m| m.chat.id);
+        let pending = {
+            let mut approvals = self.approvals.lock().unwrap();
+            match approvals.get(&id) {
+                Some(pending) if !pending.answerable_by(chat_id, query.from.id) => None,
+                _ => Some(approvals.remove(&id)),
+            }
+        };
+        let Some(pending) = pending else {
+            self.bot.answer_callback_query(query.id).text("Only the person who asked can answer this").await?;
+            return Ok(());
+        };
+        let delivered = pending.is_some_and(|pending| pending.sender.send(approved).is_ok());
+        let outcome = match (delivered, approved) {
+            (true, true) => "✅ Approved",
+            (true, false) => "❌ Declined",
+            (false, _) => "This request has expired",
+        };
+        self.bot.answer_callback_query(query.id).text(outcome).await?;
+
+        // Replace the buttons with the answer
+        if let Some(message) = query.message {
+            let question = message.text().unwrap_or_default();
+            self.bot
+                .edit_message_text(message.chat.id, message.id, format!("{}\n\n{}", question, outcome))
+                .await?;
+        }
+        Ok(())
+    }
+
     fn is_allowed(&self, user: Option<&teloxide::types::User>) -> bool {
         // If allow list is empty, allow all (for personal bots / dev mode)
         if self.config.telegram.allow_from.is_empty() {
@@ -137,22 +377,228 @@ impl<C: LlmClient + Clone> TelegramChannel<C> {
     }
 }
 
-// Helper to wrap the event loop
-async fn run_telegram_loop<C: LlmClient + Clone + 'static>(channel: Arc<TelegramChannel<C>>) {
-    let handler = Update::filter_message()
-        .endpoint(move |_bot: Bot, msg: teloxide::types::Message, channel: Arc<TelegramChannel<C>>| async move {
-            if let Err(e) = channel.handle_message(msg).await {
-                error!("Error handling telegram message: {}", e);
+/// Keeps a chat's "typing…" indicator alive until dropped
+struct TypingIndicator(tokio::task::JoinHandle<()>);
+
+impl TypingIndicator {
+    fn start(bot: Bot, chat_id: ChatId) -> Self {
+        Self(tokio::spawn(async move {
+            loop {
+                let _ = bot.send_chat_action(chat_id, teloxide::types::ChatAction::Typing).await;
+                tokio::time::sleep(TYPING_REFRESH).await;
+            }
+        }))
+    }
+}
+
+impl Drop for TypingIndicator {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// A reply sent as soon as text streams in, then edited as more arrives
+struct LiveReply {
+    text: Arc<std::sync::Mutex<String>>,
+    stop: oneshot::Sender<()>,
+    task: tokio::task::JoinHandle<Option<MessageId>>,
+}
+
+impl LiveReply {
+    fn start(bot: Bot, chat_id: ChatId) -> Self {
+        let text = Arc::new(std::sync::Mutex::new(String::new()));
+        let (stop, mut stopped) = oneshot::channel();
+        let streamed = text.clone();
+
+        let task = tokio::spawn(async move {
+            let mut sent: Option<MessageId> = None;
+            let mut shown = String::new();
+            loop {
+                // Checked only between requests, so a send is never cut off halfway
+                tokio::select! {
+                    _ = &mut stopped => return sent,
+                    _ = tokio::time::sleep(STREAM_EDIT_INTERVAL) => {}
+                }
+
+                let current = streamed.lock().unwrap().clone();
+                if current.trim().is_empty() || current == shown {
+                    continue;
+                }
+                let preview = live_preview(&current);
+                match sent {
+                    None => match bot.send_message(chat_id, preview).await {
+                        Ok(message) => sent = Some(message.id),
+                        Err(e) => debug!("Failed to send streamed reply: {}", e),
+                    },
+                    Some(id) => {
+                        if let Err(e) = bot.edit_message_text(chat_id, id, preview).await {
+                            debug!("Failed to update streamed reply: {}", e);
+                        }
+                    }
+                }
+                shown = current;
             }
-            respond(())
         });
 
-    Dispatcher::builder(channel.bot.clone(), handler)
-        .dependencies(dptree::deps![channel])
-        .enable_ctrlc_handler()
-        .build()
-        .dispatch()
-        .await;
+        Self { text, stop, task }
+    }
+
+    /// Callback for `AgentLoop::run_streaming`
+    fn on_text(&self) -> impl Fn(&str) + Send + Sync {
+        let text = self.text.clone();
+        move |chunk: &str| text.lock().unwrap().push_str(chunk)
+    }
+
+    /// Stop updating; returns the reply's message, if one was sent
+    async fn finish(self) -> Option<MessageId> {
+        let _ = self.stop.send(());
+        self.task.await.ok().flatten()
+    }
+}
+
+/// The end of `text` that fits in one message, marked as still being written
+fn live_preview(text: &str) -> String {
+    let keep = MAX_MESSAGE_CHARS - 2;
+    let chars = text.chars().count();
+    let tail: String = text.chars().skip(chars.saturating_sub(keep)).collect();
+    format!("{} …", tail.trim_end())
+}
+
+/// Split `text` into messages that fit Telegram's limit, preferring line breaks
+fn split_message(text: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut rest = text;
+    while rest.chars().count() > MAX_MESSAGE_CHARS {
+        let limit = rest.char_indices().nth(MAX_MESSAGE_CHARS).map_or(rest.len(), |(i, _)| i);
+        let end = match rest[..limit].rfind('\n') {
+            Some(newline) if newline > 0 => newline + 1,
+            _ => limit,
+        };
+        parts.push(rest[..end].to_string());
+        rest = &rest[end..];
+    }
+    if !rest.is_empty() {
+        parts.push(rest.to_string());
+    }
+    parts
+}
+
+/// Asks a chat to confirm mutating tool calls with inline Yes/No buttons
+struct TelegramApprovalHandler {
+    bot: Bot,
+    chat_id: ChatId,
+    // Who sent the message that needs confirming
+    user_id: Option<UserId>,
+    pending: PendingApprovals,
+}
+
+#[async_trait::async_trait]
+impl ApprovalHandler for TelegramApprovalHandler {
+    async fn approve(&self, tool: &str, preview: &str) -> bool {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+
+        let (sender, receiver) = oneshot::channel();
+        self.pending.lock().unwrap().insert(id, PendingApproval {
+            chat_id: self.chat_id,
+            user_id: self.user_id,
+            sender,
+        });
+
+        let preview: String = preview.chars().take(MAX_APPROVAL_PREVIEW).collect();
+        let buttons = InlineKeyboardMarkup::new([[
+            InlineKeyboardButton::callback("✅ Yes", format!("approve:{}:yes", id)),
+            InlineKeyboardButton::callback("❌ No", format!("approve:{}:no", id)),
+        ]]);
+        let sent = self.bot
+            .send_message(self.chat_id, format!("Leo wants to run {}:\n{}\n\nAllow it?", tool, preview))
+            .reply_markup(buttons)
+            .await;
+        let question = match sent {
+            Ok(message) => message,
+            Err(e) => {
+                error!("Failed to ask for confirmation: {}", e);
+                self.pending.lock().unwrap().remove(&id);
+                return false;
+            }
+        };
+
+        match tokio::time::timeout(APPROVAL_TIMEOUT, receiver).await {
+            Ok(Ok(approved)) => approved,
+            _ => {
+                warn!("No answer to the {} confirmation, declining", tool);
+                self.pending.lock().unwrap().remove(&id);
+                let text = format!("{}\n\n⌛ No answer, skipped", question.text().unwrap_or_default());
+                let _ = self.bot.edit_message_text(self.chat_id, question.id, text).await;
+                false
+            }
+        }
+    }
+}
+
+/// Parse "approve:<id>:yes|no" callback data
+fn parse_approval_data(data: &str) -> Option<(u64, bool)> {
+    let mut parts = data.strip_prefix("approve:")?.split(':');
+    let id = parts.next()?.parse().ok()?;
+    let approved = match parts.next()? {
+        "yes" => true,
+        "no" => false,
+        _ => return None,
+    };
+    Some((id, approved))
+}
+
+/// Handle each chat's messages in order, but button presses on their own worker:
+/// a run waiting for confirmation would otherwise block the press it waits for.
+fn distribute_update(update: &Update) -> Option<ChatId> {
+    match update.kind {
+        UpdateKind::CallbackQuery(_) => None,
+        _ => update.chat().map(|chat| chat.id),
+    }
+}
+
+// Helper to wrap the event loop
+async fn run_telegram_loop<C: LlmClient + Clone + 'static>(channel: Arc<TelegramChannel<C>>) {
+    match channel.bot.get_me().await {
+        Ok(me) => {
+            info!("Connected to Telegram as @{}", me.username());
+            channel.set_connected(true);
+        }
+        Err(e) => error!("Telegram connection check failed: {}", e),
+    }
+
+    let handler = dptree::entry()
+        .branch(Update::filter_message()
+            .endpoint(move |_bot: Bot, msg: teloxide::types::Message, channel: Arc<TelegramChannel<C>>| async move {
+                if let Err(e) = channel.handle_message(msg).await {
+                    error!("Error handling telegram message: {}", e);
+                }
+                respond(())
+            }))
+        .branch(Update::filter_callback_query()
+            .endpoint(move |query: CallbackQuery, channel: Arc<TelegramChannel<C>>| async move {
+                if let Err(e) = channel.handle_callback(query).await {
+                    error!("Error handling telegram callback: {}", e);
+                }
+                respond(())
+            }));
+
+    let mut dispatcher = Dispatcher::builder(channel.bot.clone(), handler)
+        .dependencies(dptree::deps![channel.clone()])
+        .distribution_function(distribute_update)
+        .build();
+    *channel.shutdown.lock().unwrap() = Some(dispatcher.shutdown_token());
+    dispatcher.dispatch().await;
+    channel.set_connected(false);
+}
+
+/// `<workspace>/sessions/<chat_id>`, created and seeded with the default
+/// bootstrap files on first use.
+fn session_workspace(config: &Config, chat_id: ChatId) -> Result<std::path::PathBuf> {
+    let workspace = config.workspace.join("sessions").join(chat_id.0.to_string());
+    std::fs::create_dir_all(&workspace)?;
+    crate::templates::bootstrap_workspace(&workspace)?;
+    Ok(workspace)
 }
 
 impl<C: LlmClient + Clone + 'static> Channel for TelegramChannel<C> {
@@ -165,9 +611,12 @@ impl<C: LlmClient + Clone + 'static> Channel for TelegramChannel<C> {
             bot: self.bot.clone(),
             config: self.config.clone(),
             agent_loop: self.agent_loop.clone(),
-            context: self.context.clone(),
+            chat_contexts: self.chat_contexts.clone(),
             locks: self.locks.clone(),
             history: self.history.clone(),
+            health: self.health.clone(),
+            shutdown: self.shutdown.clone(),
+            approvals: self.approvals.clone(),
         });
         
         async move {
@@ -177,10 +626,124 @@ impl<C: LlmClient + Clone + 'static> Channel for TelegramChannel<C> {
         }
     }
 
-    fn stop(&self) -> impl std::future::Future<Output = std::result::Result<(), crate::error::Error>> + Send {
-        async {
-            // Teloxide dispatcher handles Ctrl+C, so manual stop isn't strictly needed for simple use cases
-            Ok(())
+    async fn stop(&self) -> std::result::Result<(), crate::error::Error> {
+        let token = self.shutdown.lock().unwrap().clone();
+        let Some(token) = token else {
+            return Ok(());
+        };
+        
+        // Waits for in-flight messages to finish before the dispatcher exits
+        match token.shutdown() {
+            Ok(done) => done.await,
+            Err(e) => debug!("Telegram dispatcher not running: {}", e),
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_live_preview() {
+        assert_eq!(live_preview("Hello "), "Hello …");
+        let long = "x".repeat(5000);
+        assert_eq!(live_preview(&long).chars().count(), MAX_MESSAGE_CHARS);
+    }
+
+    #[test]
+    fn test_split_message() {
+        assert_eq!(split_message("short"), vec!["short"]);
+
+        let long = "x".repeat(MAX_MESSAGE_CHARS + 10);
+        let parts = split_message(&long);
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts[0].chars().count(), MAX_MESSAGE_CHARS);
+
+        // Breaks after the last newline that fits
+        let lines = format!("{}\n{}", "a".repeat(3000), "b".repeat(3000));
+        let parts = split_message(&lines);
+        assert_eq!(parts, vec![format!("{}\n", "a".repeat(3000)), "b".repeat(3000)]);
+    }
+
+    #[test]
+    fn test_approval_answerable_only_by_requester() {
+        let (sender, _receiver) = oneshot::channel();
+        let pending = PendingApproval { chat_id: ChatId(-100), user_id: Some(UserId(7)), sender };
+
+        assert!(pending.answerable_by(Some(ChatId(-100)), UserId(7)));
+        assert!(!pending.answerable_by(Some(ChatId(-100)), UserId(8)));
+        assert!(!pending.answerable_by(Some(ChatId(42)), UserId(7)));
+        assert!(!pending.answerable_by(None, UserId(7)));
+    }
+
+    /// Holds its call until `answer` is notified, like a confirmation nobody has pressed yet
+    struct WaitingApproval {
+        waiting: tokio::sync::Notify,
+        answer: tokio::sync::Notify,
+    }
+
+    #[async_trait::async_trait]
+    impl ApprovalHandler for WaitingApproval {
+        async fn approve(&self, _tool: &str, _preview: &str) -> bool {
+            self.waiting.notify_one();
+            self.answer.notified().await;
+            false
         }
     }
+
+    #[tokio::test]
+    async fn test_chats_run_while_another_waits_for_approval() {
+        use crate::agent::{LlmResponse, MockClient, ToolCallRequest, Usage};
+
+        let tmp = tempfile::TempDir::new().unwrap();
+        let config = Config { workspace: tmp.path().to_path_buf(), ..Config::default() };
+        let client = MockClient::new(vec![
+            LlmResponse {
+                content: None,
+                tool_calls: vec![ToolCallRequest {
+                    id: "tc_1".to_string(),
+                    name: "write_file".to_string(),
+                    arguments: serde_json::json!({"path": "a.txt", "content": "a"}),
+                }],
+                finish_reason: "tool_calls".to_string(),
+                usage: Usage::default(),
+            },
+            LlmResponse::text("Chat two answer"),
+            LlmResponse::text("Chat one answer"),
+        ]);
+        let channel = Arc::new(TelegramChannel::new(config, AgentLoop::new(client, 5)));
+
+        let approval = Arc::new(WaitingApproval {
+            waiting: tokio::sync::Notify::new(),
+            answer: tokio::sync::Notify::new(),
+        });
+        let first = tokio::spawn({
+            let channel = channel.clone();
+            let approval: Arc<dyn ApprovalHandler> = approval.clone();
+            async move { channel.run_turn(ChatId(1), Message::user("Write a.txt"), Some(approval), None).await }
+        });
+        approval.waiting.notified().await;
+
+        let second = tokio::time::timeout(
+            Duration::from_secs(5),
+            channel.run_turn(ChatId(2), Message::user("Hello"), None, None),
+        )
+        .await
+        .expect("chat two waited for chat one's confirmation");
+        assert_eq!(second.unwrap().content, "Chat two answer");
+
+        approval.answer.notify_one();
+        assert_eq!(first.await.unwrap().unwrap().content, "Chat one answer");
+        assert_eq!(channel.history.lock().await.len(), 2);
+    }
+
+    #[test]
+    fn test_parse_approval_data() {
+        assert_eq!(parse_approval_data("approve:7:yes"), Some((7, true)));
+        assert_eq!(parse_approval_data("approve:7:no"), Some((7, false)));
+        assert_eq!(parse_approval_data("approve:x:yes"), None);
+        assert_eq!(parse_approval_data("other:7:yes"), None);
+    }
 }
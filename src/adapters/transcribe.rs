@@ -0,0 +1,130 @@
+//! Speech-to-text for voice messages.
+//!
+//! Audio is transcribed either by a local command (e.g. whisper.cpp) or by an
+//! OpenAI-compatible `/audio/transcriptions` endpoint, per [`TranscriptionConfig`].
+
+use std::path::Path;
+use std::time::Duration;
+
+use reqwest::multipart::{Form, Part};
+use serde::Deserialize;
+use tokio::process::Command;
+
+use crate::config::TranscriptionConfig;
+use crate::error::{Error, with_timeout};
+use crate::Result;
+
+/// Placeholder for the audio file in `TranscriptionConfig::command`.
+const FILE_PLACEHOLDER: &str = "{file}";
+
+/// Time allowed for the local transcriber before it is killed.
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Transcribe the audio file at `audio` into text.
+pub async fn transcribe(config: &TranscriptionConfig, audio: &Path) -> Result<String> {
+    let text = match &config.command {
+        Some(command) => transcribe_with_command(command, audio).await?,
+        None => transcribe_with_endpoint(config, audio).await?,
+    };
+
+    let text = text.trim();
+    if text.is_empty() {
+        return Err(Error::Other("No speech recognized".to_string()));
+    }
+    Ok(text.to_string())
+}
+
+/// Arguments of `command`, with `{file}` replaced by the audio path.
+fn command_args(command: &str, audio: &Path) -> Vec<String> {
+    let audio = audio.display().to_string();
+    command
+        .split_whitespace()
+        .map(|arg| arg.replace(FILE_PLACEHOLDER, &audio))
+        .collect()
+}
+
+/// Run a local transcriber and read the transcript from its stdout.
+async fn transcribe_with_command(command: &str, audio: &Path) -> Result<String> {
+    let args = command_args(command, audio);
+    let (program, args) = args
+        .split_first()
+        .ok_or_else(|| Error::Config("transcription.command is empty".to_string()))?;
+
+    let run = async {
+        Command::new(program)
+            .args(args)
+            .kill_on_drop(true)
+            .output()
+            .await
+            .map_err(|e| Error::Other(format!("Failed to run {}: {}", program, e)))
+    };
+    let output = with_timeout(COMMAND_TIMEOUT, &format!("transcribing with {}", program), run).await?;
+
+    if !output.status.success() {
+        return Err(Error::Other(format!(
+            "{} failed: {}",
+            program,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+#[derive(Deserialize)]
+struct TranscriptionResponse {
+    text: String,
+}
+
+/// Upload the audio to an OpenAI-compatible transcription endpoint.
+async fn transcribe_with_endpoint(config: &TranscriptionConfig, audio: &Path) -> Result<String> {
+    let bytes = tokio::fs::read(audio).await?;
+    let file_name = audio
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "audio.ogg".to_string());
+    let form = Form::new()
+        .text("model", config.model.clone())
+        .part("file", Part::bytes(bytes).file_name(file_name));
+
+    let mut request = reqwest::Client::new().post(&config.endpoint).multipart(form);
+    if !config.api_key.is_empty() {
+        request = request.bearer_auth(&config.api_key);
+    }
+    let response = request.send().await?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(Error::Other(format!("Transcription failed ({}): {}", status, body)));
+    }
+    let transcript: TranscriptionResponse = response.json().await?;
+    Ok(transcript.text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_args() {
+        let args = command_args("whisper-cli -m ggml-base.bin -nt -f {file}", Path::new("/tmp/voice.ogg"));
+        assert_eq!(args, vec!["whisper-cli", "-m", "ggml-base.bin", "-nt", "-f", "/tmp/voice.ogg"]);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_transcribe_with_command() {
+        let config = TranscriptionConfig {
+            command: Some("echo hello from {file}".to_string()),
+            ..TranscriptionConfig::default()
+        };
+        let text = transcribe(&config, Path::new("voice.ogg")).await.unwrap();
+        assert_eq!(text, "hello from voice.ogg");
+
+        let silent = TranscriptionConfig {
+            command: Some("true {file}".to_string()),
+            ..TranscriptionConfig::default()
+        };
+        assert!(transcribe(&silent, Path::new("voice.ogg")).await.is_err());
+    }
+}
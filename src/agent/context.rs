@@ -2,52 +2,100 @@
 //!
 //! Optimizations implemented:
 //! - Bootstrap files cached at construction time
-//! - History windowing (max N messages)
+//! - History windowing (max N messages), with older messages summarized
 //! - Pre-allocated string buffers
 
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
 use crate::config::Config;
 use crate::memory::MemoryStore;
 use crate::skills::SkillRegistry;
-use crate::tools::ToolRunner;
+use crate::tools::{AttachTool, Attachments, Scratchpad, ScratchpadTool, SkillTool, StopTool, ToolRunner};
 use crate::Result;
 
+use super::llm::LlmClient;
 use super::message::Message;
 
-/// Maximum history messages to include in prompt (prevents unbounded growth).
-const MAX_HISTORY_MESSAGES: usize = 40;
+/// Maximum characters of `task.md` included in the prompt.
+const MAX_TASK_CHARS: usize = 2000;
+
+/// Above this many skills, only the most relevant ones go into the prompt.
+const MAX_PROMPT_SKILLS: usize = 8;
 
 /// Context holds all state for an agent interaction.
 pub struct Context {
     pub memory: Box<dyn MemoryStore>,
     pub skills: SkillRegistry,
     pub tool_runner: ToolRunner,
+    /// Working notes for the current run (see `ScratchpadTool`).
+    pub scratchpad: Scratchpad,
+    /// Files queued to be sent with the current reply (see `AttachTool`).
+    pub attachments: Attachments,
+    /// Session the current run belongs to, e.g. "telegram:12345" (for tracing).
+    pub session: String,
     pub workspace: PathBuf,
     pub config: Config,
     /// Cached bootstrap file content (loaded once at construction).
     cached_bootstrap: String,
+    /// Skills chosen for the current message (all skills when `None`).
+    relevant_skills: Option<Vec<String>>,
+    /// Summaries of history that fell out of the window, keyed by `session`
+    /// (see `compact_history`).
+    history_summaries: HashMap<String, HistorySummary>,
+}
+
+/// Cached summary of messages older than the history window.
+struct HistorySummary {
+    /// Fingerprint of the newest message the summary covers.
+    last_covered: u64,
+    text: String,
 }
 
 impl Context {
     /// Create a new context from configuration.
     pub fn new(config: &Config) -> Result<Self> {
+        Self::new_with_workspace(config, &config.workspace)
+    }
+
+    /// Create a context rooted at `workspace` instead of `config.workspace`.
+    ///
+    /// Memory, tasks, skills, bootstrap files and every tool use the given
+    /// directory, which lets a gateway give each chat its own sandbox.
+    pub fn new_with_workspace(config: &Config, workspace: &Path) -> Result<Self> {
         use crate::memory::FileMemoryStore;
 
-        let memory = Box::new(FileMemoryStore::new(&config.workspace));
-        let skills = SkillRegistry::new(&config.workspace);
-        let tool_runner = ToolRunner::new_with_defaults(&config.workspace);
+        let config = Config {
+            workspace: workspace.to_path_buf(),
+            ..config.clone()
+        };
+        let memory = Box::new(FileMemoryStore::new(workspace));
+        let skills = SkillRegistry::new(workspace);
+        let mut tool_runner = ToolRunner::from_config(&config);
+        let scratchpad = Scratchpad::new();
+        tool_runner.register(ScratchpadTool::new(scratchpad.clone()));
+        let attachments = Attachments::new();
+        tool_runner.register(AttachTool::new(workspace.to_path_buf(), attachments.clone()));
+        tool_runner.register(StopTool);
+        if !skills.is_empty() {
+            tool_runner.register(SkillTool::new(skills.skills()));
+        }
 
         // Cache bootstrap files at construction time
-        let cached_bootstrap = Self::load_bootstrap_files_static(&config.workspace);
+        let cached_bootstrap = Self::load_bootstrap_files_static(workspace, &config.bootstrap_files);
 
         Ok(Self {
             memory,
             skills,
             tool_runner,
-            workspace: config.workspace.clone(),
-            config: config.clone(),
+            scratchpad,
+            attachments,
+            session: "cli:default".to_string(),
+            workspace: workspace.to_path_buf(),
+            config,
             cached_bootstrap,
+            relevant_skills: None,
+            history_summaries: HashMap::new(),
         })
     }
 
@@ -60,9 +108,14 @@ impl Context {
             memory: Box::new(InMemoryStore::new()),
             skills: SkillRegistry::empty(),
             tool_runner: ToolRunner::new(),
+            scratchpad: Scratchpad::new(),
+            attachments: Attachments::new(),
+            session: "test".to_string(),
             workspace: PathBuf::from("/tmp/test"),
             config: Config::default(),
             cached_bootstrap: String::new(),
+            relevant_skills: None,
+            history_summaries: HashMap::new(),
         }
     }
 
@@ -70,13 +123,29 @@ impl Context {
     pub fn build_system_prompt(&self) -> String {
         // Estimate capacity to reduce allocations
         let mut parts = Vec::with_capacity(4);
-        parts.push(self.get_identity());
+        if let Some(prefix) = self.config.system_prompt_prefix.as_deref().filter(|p| !p.trim().is_empty()) {
+            parts.push(prefix.trim().to_string());
+        }
+        parts.push(self.identity());
 
         // Use cached bootstrap files (loaded at construction)
         if !self.cached_bootstrap.is_empty() {
             parts.push(self.cached_bootstrap.clone());
         }
 
+        if self.config.dry_run {
+            parts.push(
+                "# Dry Run\n\nThis is a dry run: tools that change things only report what they would do. \
+                 Plan and describe the changes; don't retry them."
+                    .to_string(),
+            );
+        }
+
+        // Open tasks (read fresh, since the task tool edits them mid-session)
+        if let Some(tasks) = self.current_tasks() {
+            parts.push(format!("# Current Tasks\n\n{}", tasks));
+        }
+
         // Memory context
         if let Ok(memory) = self.memory.get_context() {
             if !memory.is_empty() {
@@ -85,7 +154,12 @@ impl Context {
         }
 
         // Skills summary
-        let skills_summary = self.skills.build_summary();
+        let tools = self.tool_runner.tool_names();
+        let hide = self.config.hide_unavailable_skills;
+        let skills_summary = match &self.relevant_skills {
+            Some(names) => self.skills.build_summary_for(names, &tools, hide),
+            None => self.skills.build_summary(&tools, hide),
+        };
         if !skills_summary.is_empty() {
             parts.push(format!(
                 "# Skills\n\nThe following skills extend your capabilities:\n\n{}",
@@ -96,11 +170,82 @@ impl Context {
         parts.join("\n\n---\n\n")
     }
 
+    /// Contents of the workspace `task.md`, truncated, if it exists and isn't empty.
+    fn current_tasks(&self) -> Option<String> {
+        let content = std::fs::read_to_string(self.workspace.join("task.md")).ok()?;
+        let content = content.trim();
+        if content.is_empty() {
+            return None;
+        }
+
+        if content.chars().count() > MAX_TASK_CHARS {
+            let truncated: String = content.chars().take(MAX_TASK_CHARS).collect();
+            Some(format!("{}\n\n[Truncated - use the task tool to see all tasks]", truncated))
+        } else {
+            Some(content.to_string())
+        }
+    }
+
+    /// Narrow the prompt's skills to those relevant to `query` when many are loaded.
+    pub async fn select_skills<C: LlmClient + ?Sized>(&mut self, client: &C, query: &str) {
+        self.relevant_skills = if self.skills.len() > MAX_PROMPT_SKILLS {
+            let selected = self.skills.select_relevant(client, query, MAX_PROMPT_SKILLS).await;
+            Some(selected.into_iter().map(|s| s.name.clone()).collect())
+        } else {
+            None
+        };
+    }
+
+    /// Summarize history that no longer fits the window, so early facts survive.
+    ///
+    /// The summary is cached and only extended with messages dropped since the
+    /// last call, so it costs one LLM request per batch of dropped messages
+    /// rather than one per turn. On failure the previous summary is kept.
+    pub async fn compact_history<C: LlmClient + ?Sized>(&mut self, client: &C, history: &[Message]) {
+        let window = self.config.history_window;
+        if history.len() <= window {
+            self.history_summaries.remove(&self.session);
+            return;
+        }
+        let dropped = &history[..history.len() - window];
+        let newest = fingerprint(&dropped[dropped.len() - 1]);
+
+        // Only the messages after the last summarized one need summarizing
+        let (previous, pending) = match self.history_summaries.get(&self.session) {
+            Some(summary) if summary.last_covered == newest => return,
+            Some(summary) => match dropped.iter().rposition(|m| fingerprint(m) == summary.last_covered) {
+                Some(pos) => (Some(summary.text.as_str()), &dropped[pos + 1..]),
+                None => (None, dropped),
+            },
+            None => (None, dropped),
+        };
+
+        let mut request = String::from("Summarize this earlier part of our conversation:\n\n");
+        if let Some(previous) = previous {
+            request.push_str(&format!("Summary so far:\n{}\n\nLater messages:\n", previous));
+        }
+        for message in pending {
+            request.push_str(&format!("{:?}: {}\n", message.role, message.content));
+        }
+
+        let messages = [Message::system(crate::tools::SUMMARY_PROMPT), Message::user(request)];
+        match client.chat(&messages, &[]).await {
+            Ok(response) => match response.content.filter(|c| !c.trim().is_empty()) {
+                Some(text) => {
+                    self.history_summaries.insert(self.session.clone(), HistorySummary { last_covered: newest, text });
+                }
+                None => tracing::warn!("History summary came back empty"),
+            },
+            Err(e) => tracing::warn!("Failed to summarize older history: {}", e),
+        }
+    }
+
     /// Build messages list for LLM call with history windowing.
     pub fn build_messages(&self, history: &[Message], current: &str) -> Vec<Message> {
-        // Apply history windowing to prevent unbounded growth
-        let windowed_history = if history.len() > MAX_HISTORY_MESSAGES {
-            &history[history.len() - MAX_HISTORY_MESSAGES..]
+        // Apply history windowing (`history_window`) to prevent unbounded growth
+        let window = self.config.history_window;
+        let windowed_history = if history.len() > window {
+            &history[history.len() - window..]
         } else {
             history
         };
@@ -108,8 +253,19 @@ impl Context {
         // Pre-allocate with estimated capacity
         let mut messages = Vec::with_capacity(windowed_history.len() + 2);
 
-        // System prompt
-        messages.push(Message::system(self.build_system_prompt()));
+        // System prompt, followed by a summary of what fell out of the window if it was
+        // made from this history. Providers only take one system prompt, so it goes in there.
+        let mut system_prompt = self.build_system_prompt();
+        if history.len() > window {
+            let dropped = &history[..history.len() - window];
+            let summary = self.history_summaries.get(&self.session)
+                .filter(|s| dropped.iter().any(|m| fingerprint(m) == s.last_covered));
+            if let Some(summary) = summary {
+                system_prompt.push_str("\n\n---\n\n# Summary of the earlier conversation\n\n");
+                system_prompt.push_str(&summary.text);
+            }
+        }
+        messages.push(Message::system(system_prompt));
 
         // History (windowed)
         messages.extend(windowed_history.iter().cloned());
@@ -120,9 +276,66 @@ impl Context {
         messages
     }
 
+    /// `system_prompt` followed by the run's scratchpad, when it has anything in it.
+    pub fn with_scratchpad(&self, system_prompt: &str) -> String {
+        let pad = self.scratchpad.read();
+        if pad.trim().is_empty() {
+            system_prompt.to_string()
+        } else {
+            format!("{}\n\n---\n\n# Scratchpad (this request only)\n\n{}", system_prompt, pad)
+        }
+    }
+
+    /// Append a terse record of an exchange to today's notes when `auto_log` is enabled.
+    pub fn log_exchange(&self, user: &str, reply: &str) -> Result<()> {
+        if !self.config.auto_log {
+            return Ok(());
+        }
+
+        let time = chrono::Local::now().format("%H:%M");
+        self.memory.append_today(&format!(
+            "- {} user: {}\n- {} leo: {}",
+            time,
+            terse(user),
+            time,
+            terse(reply)
+        ))
+    }
+
+    /// Note in today's notes that the exchange starting with `user` was undone,
+    /// when `auto_log` is enabled.
+    pub fn log_undo(&self, user: &str) -> Result<()> {
+        if !self.config.auto_log {
+            return Ok(());
+        }
+
+        let time = chrono::Local::now().format("%H:%M");
+        self.memory.append_today(&format!("- {} undo: dropped the exchange \"{}\"", time, terse(user)))
+    }
+
     /// Reload bootstrap files (call if files changed during session).
     pub fn reload_bootstrap(&mut self) {
-        self.cached_bootstrap = Self::load_bootstrap_files_static(&self.workspace);
+        self.cached_bootstrap = Self::load_bootstrap_files_static(&self.workspace, &self.config.bootstrap_files);
+    }
+
+    /// The identity section: the `system_prompt_path` file when configured and
+    /// readable, else the built-in one.
+    fn identity(&self) -> String {
+        let Some(path) = &self.config.system_prompt_path else {
+            return self.get_identity();
+        };
+        let path = self.workspace.join(path);
+        match std::fs::read_to_string(&path) {
+            Ok(identity) if !identity.trim().is_empty() => identity.trim().to_string(),
+            Ok(_) => {
+                tracing::warn!("System prompt file {:?} is empty, using the built-in identity", path);
+                self.get_identity()
+            }
+            Err(e) => {
+                tracing::warn!("Failed to read system prompt file {:?} ({}), using the built-in identity", path, e);
+                self.get_identity()
+            }
+        }
     }
 
     fn get_identity(&self) -> String {
@@ -144,17 +357,30 @@ Your workspace is: `{}`
 - Assume they mean files inside the workspace above
 - Use relative paths from the workspace (e.g., "src/main.rs" not "/full/path/src/main.rs")
 - NEVER ask for the full path - just try the relative path first
-- Common locations: "documents" = ~/Documents, "desktop" = ~/Desktop
+- Common locations: "documents" = ~/Documents, "desktop" = ~/Desktop, "downloads" = ~/Downloads
 
 ## Tools
 You have access to these tools:
 - `read_file`, `write_file`, `edit`, `list_dir` - File operations
 - `find_files` - Find files by pattern (*.rs, config*, etc.)
 - `search` - Search text in files (supports regex or literal)
+- `count` - Count lines, words, chars or tokens in files (instead of `wc`)
+- `template` - Render a {{{{variable}}}} template, optionally into a file
+- `chmod` - Set file permissions (e.g. make a script executable)
 - `exec` - Run shell commands
-- `git` - Git operations
+- `git` - Git operations (including blame and per-file history)
 - `web_search`, `web_fetch` - Web access
+- `download` - Save a URL (PDF, image, archive) to a file
+- `moments` - List and recall pages/screenshots saved from the browser
+- `calendar` - Google Calendar events (list/create), when enabled
 - `memory` - Long-term memory (read/add)
+- `scratchpad` - Your plan for the current request (discarded afterwards)
+- `attach_file` - Send a file you created along with your reply
+- `skill` - Load a skill's instructions (with its parameters filled in) or run a script it lists
+- `more_output` - Next part of a truncated tool result (use the token from its note)
+- `done` - Finish with your final answer once the task is complete
+
+Tool descriptions ending in [Caution: ...] or [Danger: ...] change things outside this conversation. Prefer the safest tool that does the job, and only delete or overwrite what the user asked for.
 
 ## Memory Instructions
 **CRITICAL**: When the user tells you to remember ANYTHING - names, preferences, identity, aim, purpose:
@@ -177,19 +403,10 @@ Always be helpful, accurate, and concise. When using tools, just do it—don't e
     }
 
     /// Load bootstrap files from workspace (static helper for caching).
-    fn load_bootstrap_files_static(workspace: &std::path::Path) -> String {
-        const BOOTSTRAP_FILES: [&str; 6] = [
-            "AGENTS.md",
-            "SOUL.md",
-            "USER.md",
-            "IDENTITY.md",
-            "TOOLS.md",
-            "MEMORY.md",
-        ];
-
-        let mut parts = Vec::with_capacity(BOOTSTRAP_FILES.len());
-
-        for filename in BOOTSTRAP_FILES {
+    fn load_bootstrap_files_static(workspace: &std::path::Path, files: &[String]) -> String {
+        let mut parts = Vec::with_capacity(files.len());
+
+        for filename in files {
             let path = workspace.join(filename);
             if path.exists() {
                 if let Ok(content) = std::fs::read_to_string(&path) {
@@ -202,6 +419,28 @@ Always be helpful, accurate, and concise. When using tools, just do it—don't e
     }
 }
 
+/// Identify a message by role and content, to find it again in a later history.
+fn fingerprint(message: &Message) -> u64 {
+    use std::hash::{Hash, Hasher};
+
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    format!("{:?}", message.role).hash(&mut hasher);
+    message.content.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Collapse a message to a single short line for daily logs.
+fn terse(text: &str) -> String {
+    const MAX_CHARS: usize = 200;
+
+    let line = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if line.chars().count() > MAX_CHARS {
+        format!("{}…", line.chars().take(MAX_CHARS).collect::<String>())
+    } else {
+        line
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -224,6 +463,170 @@ mod tests {
         assert_eq!(messages[1].content, "Hello");
     }
 
+    #[test]
+    fn test_system_prompt_prefix_and_override() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let mut ctx = Context::test();
+        ctx.workspace = tmp.path().to_path_buf();
+        assert!(ctx.build_system_prompt().starts_with("# Leo"));
+
+        ctx.config.system_prompt_prefix = Some("Always answer in French.".to_string());
+        ctx.config.system_prompt_path = Some(PathBuf::from("identity.md"));
+        let prompt = ctx.build_system_prompt();
+        assert!(prompt.starts_with("Always answer in French.\n\n---\n\n# Leo"));
+
+        std::fs::write(tmp.path().join("identity.md"), "# Max\n\nYou are Max, a support bot.\n").unwrap();
+        let prompt = ctx.build_system_prompt();
+        assert!(prompt.starts_with("Always answer in French.\n\n---\n\n# Max\n\nYou are Max"));
+        assert!(!prompt.contains("# Leo"));
+    }
+
+    #[test]
+    fn test_log_exchange_respects_auto_log() {
+        let mut ctx = Context::test();
+        ctx.log_exchange("Hello", "Hi!").unwrap();
+        assert!(ctx.memory.read_today().unwrap().is_empty());
+
+        ctx.config.auto_log = true;
+        ctx.log_exchange("Hello\nthere", "Hi!").unwrap();
+        let today = ctx.memory.read_today().unwrap();
+        assert!(today.contains("user: Hello there"));
+        assert!(today.contains("leo: Hi!"));
+
+        ctx.log_undo("Hello\nthere").unwrap();
+        assert!(ctx.memory.read_today().unwrap().contains("undo: dropped the exchange \"Hello there\""));
+    }
+
+    #[test]
+    fn test_system_prompt_includes_tasks() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let mut ctx = Context::test();
+        ctx.workspace = tmp.path().to_path_buf();
+        assert!(!ctx.build_system_prompt().contains("# Current Tasks"));
+
+        std::fs::write(tmp.path().join("task.md"), "  \n").unwrap();
+        assert!(!ctx.build_system_prompt().contains("# Current Tasks"));
+
+        std::fs::write(tmp.path().join("task.md"), "- [ ] Renew passport\n").unwrap();
+        let prompt = ctx.build_system_prompt();
+        assert!(prompt.contains("# Current Tasks\n\n- [ ] Renew passport"));
+
+        std::fs::write(tmp.path().join("task.md"), "x".repeat(MAX_TASK_CHARS + 10)).unwrap();
+        assert!(ctx.build_system_prompt().contains("[Truncated"));
+    }
+
+    #[tokio::test]
+    async fn test_new_with_workspace() {
+        let shared = tempfile::TempDir::new().unwrap();
+        let chat = tempfile::TempDir::new().unwrap();
+        std::fs::write(shared.path().join("task.md"), "- [ ] Shared task\n").unwrap();
+        std::fs::write(chat.path().join("task.md"), "- [ ] Chat task\n").unwrap();
+        let config = Config {
+            workspace: shared.path().to_path_buf(),
+            ..Config::default()
+        };
+
+        let ctx = Context::new_with_workspace(&config, chat.path()).unwrap();
+        assert_eq!(ctx.workspace, chat.path());
+        assert_eq!(ctx.config.workspace, chat.path());
+        let prompt = ctx.build_system_prompt();
+        assert!(prompt.contains("Chat task"));
+        assert!(!prompt.contains("Shared task"));
+
+        ctx.tool_runner.execute("write_file", serde_json::json!({
+            "path": "notes.txt", "content": "hi"
+        })).await.unwrap();
+        assert!(chat.path().join("notes.txt").exists());
+        assert!(!shared.path().join("notes.txt").exists());
+    }
+
+    #[test]
+    fn test_bootstrap_files_from_config() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        std::fs::write(tmp.path().join("STYLE.md"), "Use tabs").unwrap();
+        std::fs::write(tmp.path().join("CONVENTIONS.md"), "Snake case").unwrap();
+
+        let files = vec!["CONVENTIONS.md".to_string(), "MISSING.md".to_string(), "STYLE.md".to_string()];
+        let bootstrap = Context::load_bootstrap_files_static(tmp.path(), &files);
+
+        assert_eq!(bootstrap, "## CONVENTIONS.md\n\nSnake case\n\n## STYLE.md\n\nUse tabs");
+    }
+
+    #[tokio::test]
+    async fn test_compact_history_caches_summary() {
+        use crate::agent::llm::FakeLlmClient;
+
+        let mut ctx = Context::test();
+        let window = ctx.config.history_window;
+        let mut history: Vec<Message> = (0..window + 2)
+            .map(|i| Message::user(format!("Message {}", i)))
+            .collect();
+
+        // One summary for the first two dropped messages, then reused
+        let client = FakeLlmClient::new(vec!["- User is called Sam", "- Sam likes tea"]);
+        ctx.compact_history(&client, &history).await;
+        ctx.compact_history(&client, &history).await;
+        let messages = ctx.build_messages(&history, "Current");
+        assert_eq!(messages.len(), window + 2);
+        assert!(messages[0].content.contains("- User is called Sam"));
+
+        // A newly dropped message extends the summary
+        history.push(Message::user("Message new"));
+        ctx.compact_history(&client, &history).await;
+        let messages = ctx.build_messages(&history, "Current");
+        assert!(messages[0].content.contains("- Sam likes tea"));
+
+        // Short histories need no summary
+        ctx.compact_history(&client, &history[..3]).await;
+        assert_eq!(ctx.build_messages(&history[..3], "Current").len(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_history_summary_stays_with_its_session() {
+        use crate::agent::llm::FakeLlmClient;
+
+        let mut ctx = Context::test();
+        let window = ctx.config.history_window;
+        let history = |chat: &str| -> Vec<Message> {
+            (0..window + 2).map(|i| Message::user(format!("{} message {}", chat, i))).collect()
+        };
+
+        ctx.session = "telegram:1".to_string();
+        let client = FakeLlmClient::new(vec!["- Chat one is about oolong"]);
+        ctx.compact_history(&client, &history("one")).await;
+
+        // Another chat's history never shows chat one's summary
+        ctx.session = "telegram:2".to_string();
+        let messages = ctx.build_messages(&history("two"), "Current");
+        assert!(messages.iter().all(|m| !m.content.contains("oolong")));
+
+        // Nor does a different history under the same session
+        ctx.session = "telegram:1".to_string();
+        let messages = ctx.build_messages(&history("other"), "Current");
+        assert!(messages.iter().all(|m| !m.content.contains("oolong")));
+        let messages = ctx.build_messages(&history("one"), "Current");
+        assert!(messages[0].content.contains("oolong"));
+    }
+
+    #[tokio::test]
+    async fn test_history_summary_reaches_gemini() {
+        use crate::agent::llm::FakeLlmClient;
+        use crate::agent::llm::gemini::build_request;
+
+        let mut ctx = Context::test();
+        let window = ctx.config.history_window;
+        let history: Vec<Message> = (0..window + 2)
+            .map(|i| Message::user(format!("Message {}", i)))
+            .collect();
+
+        let client = FakeLlmClient::new(vec!["- User is called Sam"]);
+        ctx.compact_history(&client, &history).await;
+        let request = build_request(&ctx.build_messages(&history, "Current"), &[], 8192);
+
+        let system = request["systemInstruction"]["parts"][0]["text"].as_str().unwrap();
+        assert!(system.contains("- User is called Sam"));
+    }
+
     #[test]
     fn test_history_windowing() {
         let ctx = Context::test();
@@ -236,11 +639,22 @@ mod tests {
 
         let messages = ctx.build_messages(&history, "Current");
 
-        // Should have: system + MAX_HISTORY_MESSAGES + current
-        assert_eq!(messages.len(), MAX_HISTORY_MESSAGES + 2);
+        // Should have: system + history_window + current
+        assert_eq!(messages.len(), 40 + 2);
 
         // Last history message should be the most recent
         let last_history_msg = &messages[messages.len() - 2];
         assert!(last_history_msg.content.contains("99"));
     }
+
+    #[test]
+    fn test_history_window_from_config() {
+        let mut ctx = Context::test();
+        ctx.config.history_window = 6;
+        let history: Vec<Message> = (0..10).map(|i| Message::user(format!("Message {}", i))).collect();
+
+        let messages = ctx.build_messages(&history, "Current");
+        assert_eq!(messages.len(), 6 + 2);
+        assert!(messages[1].content.contains("Message 4"));
+    }
 }
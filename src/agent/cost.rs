@@ -0,0 +1,146 @@
+//! Usage cost estimation and the daily spend log.
+//!
+//! Every LLM response's [`Usage`] is priced with a small per-model table and
+//! appended to `~/.leo/usage.jsonl`; `leo status` sums today's entries.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+use crate::Result;
+
+use super::llm::Usage;
+
+/// Model price in USD per 1k tokens: (model prefix, input, output).
+///
+/// Matched by longest prefix so `gemini-2.0-flash-001` uses the `gemini-2.0-flash` row.
+const PRICING: &[(&str, f64, f64)] = &[
+    ("gemini-2.5-pro", 0.00125, 0.01),
+    ("gemini-2.5-flash-lite", 0.0001, 0.0004),
+    ("gemini-2.5-flash", 0.0003, 0.0025),
+    ("gemini-2.0-flash-lite", 0.000075, 0.0003),
+    ("gemini-2.0-flash", 0.0001, 0.0004),
+    ("gemini-1.5-pro", 0.00125, 0.005),
+    ("gemini-1.5-flash", 0.000075, 0.0003),
+];
+
+/// Path of the spend log (`~/.leo/usage.jsonl`).
+pub fn usage_log_path() -> PathBuf {
+    crate::config::config_dir().join("usage.jsonl")
+}
+
+/// Estimated cost in USD of one response (0 for models without pricing).
+pub fn estimate_cost(model: &str, usage: &Usage) -> f64 {
+    let Some(&(_, input, output)) = PRICING
+        .iter()
+        .filter(|(prefix, _, _)| model.starts_with(prefix))
+        .max_by_key(|(prefix, _, _)| prefix.len())
+    else {
+        return 0.0;
+    };
+
+    // Some responses only report a total; count the remainder as input
+    let completion = usage.completion_tokens;
+    let prompt = usage.prompt_tokens.max(usage.total_tokens.saturating_sub(completion));
+    (prompt as f64 * input + completion as f64 * output) / 1000.0
+}
+
+/// One line of the spend log.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageRecord {
+    pub timestamp: DateTime<Local>,
+    pub model: String,
+    pub prompt_tokens: usize,
+    pub completion_tokens: usize,
+    pub cost_usd: f64,
+}
+
+/// Price a response and append it to the spend log at `path`.
+pub fn record_usage(path: &Path, model: &str, usage: &Usage) -> Result<()> {
+    let record = UsageRecord {
+        timestamp: Local::now(),
+        model: model.to_string(),
+        prompt_tokens: usage.prompt_tokens,
+        completion_tokens: usage.completion_tokens,
+        cost_usd: estimate_cost(model, usage),
+    };
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut file = std::fs::OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", serde_json::to_string(&record)?)?;
+    Ok(())
+}
+
+/// Totals over a set of usage records.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UsageTotals {
+    pub requests: usize,
+    pub prompt_tokens: usize,
+    pub completion_tokens: usize,
+    pub cost_usd: f64,
+}
+
+/// Sum today's entries in the spend log (empty totals when there is no log).
+pub fn today_totals(path: &Path) -> Result<UsageTotals> {
+    let mut totals = UsageTotals::default();
+    if !path.exists() {
+        return Ok(totals);
+    }
+
+    let today = Local::now().date_naive();
+    let content = std::fs::read_to_string(path)?;
+    for record in content
+        .lines()
+        .filter_map(|line| serde_json::from_str::<UsageRecord>(line).ok())
+        .filter(|r| r.timestamp.date_naive() == today)
+    {
+        totals.requests += 1;
+        totals.prompt_tokens += record.prompt_tokens;
+        totals.completion_tokens += record.completion_tokens;
+        totals.cost_usd += record.cost_usd;
+    }
+    Ok(totals)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usage(prompt: usize, completion: usize) -> Usage {
+        Usage {
+            prompt_tokens: prompt,
+            completion_tokens: completion,
+            total_tokens: prompt + completion,
+        }
+    }
+
+    #[test]
+    fn test_estimate_cost_uses_longest_prefix() {
+        let flash = estimate_cost("gemini-2.0-flash-001", &usage(10_000, 1_000));
+        assert!((flash - 0.0014).abs() < 1e-9);
+
+        let lite = estimate_cost("gemini-2.0-flash-lite", &usage(10_000, 1_000));
+        assert!((lite - 0.00105).abs() < 1e-9);
+
+        assert_eq!(estimate_cost("unknown-model", &usage(10_000, 1_000)), 0.0);
+    }
+
+    #[test]
+    fn test_record_and_total_today() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = tmp.path().join("usage.jsonl");
+
+        record_usage(&path, "gemini-2.0-flash", &usage(1_000, 100)).unwrap();
+        record_usage(&path, "gemini-2.0-flash", &usage(2_000, 200)).unwrap();
+
+        let totals = today_totals(&path).unwrap();
+        assert_eq!(totals.requests, 2);
+        assert_eq!(totals.prompt_tokens, 3_000);
+        assert_eq!(totals.completion_tokens, 300);
+        assert!(totals.cost_usd > 0.0);
+    }
+}
@@ -0,0 +1,162 @@
+//! Request limiter - caps concurrent and per-minute LLM calls.
+//!
+//! One limiter is shared by everything that goes through an [`AgentLoop`](super::AgentLoop),
+//! so several busy chats smooth out into a steady request rate instead of bursting into 429s.
+//! Tools that call the LLM themselves get a [`LimitedClient`] on the same limiter.
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use tokio::sync::{Semaphore, SemaphorePermit};
+use tokio::time::Instant;
+
+use crate::config::Config;
+use crate::tools::ToolDefinition;
+use crate::Result;
+
+use super::llm::{LlmClient, LlmResponse, OnText};
+use super::message::Message;
+
+/// Limits how many LLM requests run at once and how often they start.
+pub struct RequestLimiter {
+    /// `None` means unlimited concurrency.
+    semaphore: Option<Semaphore>,
+    /// Minimum spacing between request starts (`None` = no rate limit).
+    interval: Option<Duration>,
+    /// Earliest time the next request may start.
+    next_slot: Mutex<Instant>,
+}
+
+impl RequestLimiter {
+    /// Create a limiter. Zero disables the corresponding limit.
+    pub fn new(max_concurrent: usize, requests_per_minute: u32) -> Self {
+        Self {
+            semaphore: (max_concurrent > 0).then(|| Semaphore::new(max_concurrent)),
+            interval: (requests_per_minute > 0)
+                .then(|| Duration::from_secs(60) / requests_per_minute),
+            next_slot: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Create a limiter from `max_concurrent_requests` and `requests_per_minute`.
+    pub fn from_config(config: &Config) -> Self {
+        Self::new(config.max_concurrent_requests, config.requests_per_minute)
+    }
+
+    /// A limiter that never waits.
+    pub fn unlimited() -> Self {
+        Self::new(0, 0)
+    }
+
+    /// Wait for a request slot. Hold the returned permit for the duration of the request.
+    pub async fn acquire(&self) -> Option<SemaphorePermit<'_>> {
+        let permit = match &self.semaphore {
+            // The semaphore is never closed, so acquire cannot fail
+            Some(semaphore) => semaphore.acquire().await.ok(),
+            None => None,
+        };
+
+        if let Some(interval) = self.interval {
+            let start = {
+                let mut next_slot = self.next_slot.lock().unwrap();
+                let start = (*next_slot).max(Instant::now());
+                *next_slot = start + interval;
+                start
+            };
+            tokio::time::sleep_until(start).await;
+        }
+
+        permit
+    }
+}
+
+/// An LLM client whose requests wait for a slot on a shared [`RequestLimiter`].
+#[derive(Clone)]
+pub struct LimitedClient<C> {
+    inner: C,
+    limiter: Arc<RequestLimiter>,
+}
+
+impl<C> LimitedClient<C> {
+    pub fn new(inner: C, limiter: Arc<RequestLimiter>) -> Self {
+        Self { inner, limiter }
+    }
+}
+
+#[async_trait]
+impl<C: LlmClient> LlmClient for LimitedClient<C> {
+    async fn chat(&self, messages: &[Message], tools: &[ToolDefinition]) -> Result<LlmResponse> {
+        let _permit = self.limiter.acquire().await;
+        self.inner.chat(messages, tools).await
+    }
+
+    async fn chat_stream(
+        &self,
+        messages: &[Message],
+        tools: &[ToolDefinition],
+        on_text: &OnText<'_>,
+    ) -> Result<LlmResponse> {
+        let _permit = self.limiter.acquire().await;
+        self.inner.chat_stream(messages, tools, on_text).await
+    }
+
+    fn default_model(&self) -> &str {
+        self.inner.default_model()
+    }
+
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        let _permit = self.limiter.acquire().await;
+        self.inner.embed(texts).await
+    }
+
+    async fn list_models(&self) -> Result<Vec<String>> {
+        let _permit = self.limiter.acquire().await;
+        self.inner.list_models().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_concurrency_limit() {
+        let limiter = RequestLimiter::new(1, 0);
+
+        let first = limiter.acquire().await;
+        let blocked = tokio::time::timeout(Duration::from_millis(50), limiter.acquire()).await;
+        assert!(blocked.is_err());
+
+        drop(first);
+        let second = tokio::time::timeout(Duration::from_millis(50), limiter.acquire()).await;
+        assert!(second.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_spaces_requests() {
+        // 600/min = one every 100ms
+        let limiter = RequestLimiter::new(0, 600);
+        let start = Instant::now();
+        for _ in 0..3 {
+            limiter.acquire().await;
+        }
+        assert!(start.elapsed() >= Duration::from_millis(200));
+    }
+
+    #[tokio::test]
+    async fn test_limited_client_waits_for_a_slot() {
+        use crate::agent::llm::FakeLlmClient;
+
+        let limiter = Arc::new(RequestLimiter::new(1, 0));
+        let client = LimitedClient::new(FakeLlmClient::new(vec!["hi"]), limiter.clone());
+
+        let held = limiter.acquire().await;
+        let blocked = tokio::time::timeout(Duration::from_millis(50), client.chat(&[], &[])).await;
+        assert!(blocked.is_err());
+
+        drop(held);
+        let response = client.chat(&[], &[]).await.unwrap();
+        assert_eq!(response.content.as_deref(), Some("hi"));
+    }
+}
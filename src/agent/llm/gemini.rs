@@ -9,15 +9,35 @@ use crate::tools::ToolDefinition;
 use crate::Result;
 
 use super::super::message::{Message, Role, ToolCallRequest};
-use super::{GeminiResponse, LlmClient, LlmResponse, Usage};
+use super::{BatchEmbedResponse, GeminiResponse, LlmClient, LlmResponse, ModelList, OnText, Usage};
 
 const GEMINI_API_URL: &str = "https://generativelanguage.googleapis.com/v1beta/models";
 
+/// Model used for `batchEmbedContents`.
+const EMBEDDING_MODEL: &str = "text-embedding-004";
+
+/// Output token cap for models not matched by `default_max_output_tokens`.
+const DEFAULT_MAX_OUTPUT_TOKENS: u32 = 8192;
+
+/// A sensible `maxOutputTokens` for `model`, by model family.
+///
+/// Gemini 2.5 and later can write up to 64k tokens; everything else, including
+/// the older lite, 8B and Gemma models, keeps the long-standing 8192.
+pub fn default_max_output_tokens(model: &str) -> u32 {
+    let model = model.to_lowercase();
+    if model.contains("gemini-2.5") || model.contains("gemini-3") {
+        65_536
+    } else {
+        DEFAULT_MAX_OUTPUT_TOKENS
+    }
+}
+
 /// Gemini API client using API key authentication.
 #[derive(Clone)]
 pub struct GeminiClient {
     api_key: String,
     model: String,
+    max_output_tokens: u32,
     client: Client,
 }
 
@@ -27,154 +47,342 @@ impl GeminiClient {
         Self {
             api_key: api_key.to_string(),
             model: model.to_string(),
+            max_output_tokens: default_max_output_tokens(model),
             client: Client::new(),
         }
     }
 
-    fn build_url(&self) -> String {
+    /// Cap replies at `max_output_tokens` instead of the model's default (`None` keeps it).
+    pub fn with_max_output_tokens(mut self, max_output_tokens: Option<u32>) -> Self {
+        if let Some(max) = max_output_tokens {
+            self.max_output_tokens = max;
+        }
+        self
+    }
+
+    fn build_url(&self, method: &str) -> String {
         format!(
-            "{}/{}:generateContent?key={}",
-            GEMINI_API_URL, self.model, self.api_key
+            "{}/{}:{}?key={}",
+            GEMINI_API_URL, self.model, method, self.api_key
         )
     }
 
-    fn convert_messages(&self, messages: &[Message]) -> Vec<Value> {
-        let mut result: Vec<Value> = Vec::new();
-        let mut tool_responses: Vec<Value> = Vec::new();
-        
-        for m in messages.iter().filter(|m| m.role != Role::System) {
-            // If we have accumulated tool responses and hit a non-tool message, flush them
-            if m.role != Role::Tool && !tool_responses.is_empty() {
-                result.push(json!({
-                    "role": "function",
-                    "parts": tool_responses.clone()
-                }));
-                tool_responses.clear();
-            }
-            
-            match m.role {
-                Role::Tool => {
-                    // Accumulate tool responses into a single message
-                    tool_responses.push(json!({
-                        "functionResponse": {
-                            "name": m.tool_call_id.as_deref().unwrap_or("unknown"),
-                            "response": {"result": m.content}
-                        }
-                    }));
-                },
-                Role::Assistant if m.tool_calls.is_some() => {
-                    let calls: Vec<Value> = m.tool_calls.as_ref().unwrap()
-                        .iter()
-                        .map(|tc| {
-                            json!({
-                                "functionCall": {
-                                    "name": tc.name,
-                                    "args": tc.arguments
-                                }
-                            })
-                        })
-                        .collect();
-                    result.push(json!({
-                        "role": "model",
-                        "parts": calls
-                    }));
-                },
-                _ => {
-                    let role = match m.role {
-                        Role::User => "user",
-                        Role::Assistant => "model",
-                        _ => "user",
-                    };
-                    result.push(json!({
-                        "role": role,
-                        "parts": [{"text": m.content}]
-                    }));
-                }
-            }
+    /// Check that the API key is accepted by listing models (no tokens are spent).
+    pub async fn validate_key(&self) -> Result<()> {
+        let response = self
+            .client
+            .get(GEMINI_API_URL)
+            .query(&[("key", self.api_key.as_str()), ("pageSize", "1")])
+            .send()
+            .await?;
+
+        if response.status().is_success() {
+            return Ok(());
         }
-        
-        // Flush any remaining tool responses
-        if !tool_responses.is_empty() {
+
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        let message = serde_json::from_str::<Value>(&error_text)
+            .ok()
+            .and_then(|v| v["error"]["message"].as_str().map(str::to_string))
+            .unwrap_or(error_text);
+        Err(Error::Auth(format!("Gemini rejected the API key ({status}): {message}")))
+    }
+
+    fn build_embed_url(&self) -> String {
+        format!(
+            "{}/{}:batchEmbedContents?key={}",
+            GEMINI_API_URL, EMBEDDING_MODEL, self.api_key
+        )
+    }
+}
+
+/// Names of the models in `list` that can chat, without the `models/` prefix.
+fn chat_model_names(list: &ModelList) -> Vec<String> {
+    list.models
+        .iter()
+        .filter(|m| m.supported_generation_methods.iter().any(|g| g == "generateContent"))
+        .map(|m| m.name.strip_prefix("models/").unwrap_or(&m.name).to_string())
+        .collect()
+}
+
+/// Build a `batchEmbedContents` request body, one request per text.
+fn build_embed_request(texts: &[String]) -> Value {
+    let requests: Vec<Value> = texts
+        .iter()
+        .map(|text| {
+            json!({
+                "model": format!("models/{EMBEDDING_MODEL}"),
+                "content": {"parts": [{"text": text}]}
+            })
+        })
+        .collect();
+
+    json!({ "requests": requests })
+}
+
+/// Build a `generateContent` request body.
+///
+/// Shared by the API key and OAuth clients so request format fixes apply to both.
+pub(crate) fn build_request(messages: &[Message], tools: &[ToolDefinition], max_output_tokens: u32) -> Value {
+    let mut request = json!({
+        "contents": convert_messages(messages),
+        "generationConfig": {
+            "temperature": 0.7,
+            "maxOutputTokens": max_output_tokens
+        }
+    });
+
+    if let Some(system) = system_instruction(messages) {
+        request["systemInstruction"] = json!({
+            "parts": [{"text": system}]
+        });
+    }
+
+    if let Some(tool_config) = convert_tools(tools) {
+        request["tools"] = tool_config;
+    }
+
+    request
+}
+
+/// Convert conversation messages to Gemini `contents`.
+///
+/// Consecutive tool results are grouped into a single `function` turn.
+fn convert_messages(messages: &[Message]) -> Vec<Value> {
+    let mut result: Vec<Value> = Vec::new();
+    let mut tool_responses: Vec<Value> = Vec::new();
+    
+    for m in messages.iter().filter(|m| m.role != Role::System) {
+        // If we have accumulated tool responses and hit a non-tool message, flush them
+        if m.role != Role::Tool && !tool_responses.is_empty() {
             result.push(json!({
                 "role": "function",
-                "parts": tool_responses
+                "parts": tool_responses.clone()
             }));
+            tool_responses.clear();
         }
         
-        result
+        match m.role {
+            Role::Tool => {
+                // Accumulate tool responses into a single message
+                tool_responses.push(json!({
+                    "functionResponse": {
+                        "name": m.tool_call_id.as_deref().unwrap_or("unknown"),
+                        "response": {"result": m.content}
+                    }
+                }));
+            },
+            Role::Assistant if m.tool_calls.is_some() => {
+                let calls: Vec<Value> = m.tool_calls.as_ref().unwrap()
+                    .iter()
+                    .map(|tc| {
+                        json!({
+                            "functionCall": {
+                                "name": tc.name,
+                                "args": tc.arguments
+                            }
+                        })
+                    })
+                    .collect();
+                result.push(json!({
+                    "role": "model",
+                    "parts": calls
+                }));
+            },
+            _ => {
+                let role = match m.role {
+                    Role::User => "user",
+                    Role::Assistant => "model",
+                    _ => "user",
+                };
+                result.push(json!({
+                    "role": role,
+                    "parts": [{"text": m.content}]
+                }));
+            }
+        }
     }
-
-    fn get_system_instruction(&self, messages: &[Message]) -> Option<String> {
-        messages
-            .iter()
-            .find(|m| m.role == Role::System)
-            .map(|m| m.content.clone())
+    
+    // Flush any remaining tool responses
+    if !tool_responses.is_empty() {
+        result.push(json!({
+            "role": "function",
+            "parts": tool_responses
+        }));
     }
+    
+    result
+}
 
-    fn convert_tools(&self, tools: &[ToolDefinition]) -> Option<Value> {
-        if tools.is_empty() {
-            return None;
-        }
+/// Extract the system prompt, which Gemini takes separately.
+fn system_instruction(messages: &[Message]) -> Option<String> {
+    messages
+        .iter()
+        .find(|m| m.role == Role::System)
+        .map(|m| m.content.clone())
+}
 
-        let function_declarations: Vec<Value> = tools
-            .iter()
-            .map(|t| {
-                json!({
-                    "name": t.name,
-                    "description": t.description,
-                    "parameters": t.parameters
-                })
+/// Convert tool definitions to Gemini function declarations.
+fn convert_tools(tools: &[ToolDefinition]) -> Option<Value> {
+    if tools.is_empty() {
+        return None;
+    }
+
+    let function_declarations: Vec<Value> = tools
+        .iter()
+        .map(|t| {
+            json!({
+                "name": t.name,
+                "description": t.description,
+                "parameters": t.parameters
             })
-            .collect();
+        })
+        .collect();
+
+    Some(json!([{
+        "functionDeclarations": function_declarations
+    }]))
+}
+
+/// Parse a Gemini response into an [`LlmResponse`].
+pub(super) fn parse_response(response: &GeminiResponse) -> Result<LlmResponse> {
+    let candidate = response
+        .candidates
+        .first()
+        .ok_or_else(|| Error::Llm("No candidates in response".to_string()))?;
 
-        Some(json!([{
-            "functionDeclarations": function_declarations
-        }]))
+    let mut content = None;
+    let mut tool_calls = Vec::new();
+
+    for part in &candidate.content.parts {
+        if let Some(ref text) = part.text {
+            content = Some(text.clone());
+        }
+
+        if let Some(ref fc) = part.function_call {
+            tool_calls.push(ToolCallRequest {
+                id: format!("tc_{}", tool_calls.len()),
+                name: fc.name.clone(),
+                arguments: fc.args.clone(),
+            });
+        }
     }
 
-    fn parse_response(&self, response: &GeminiResponse) -> Result<LlmResponse> {
-        let candidate = response
-            .candidates
-            .first()
-            .ok_or_else(|| Error::Llm("No candidates in response".to_string()))?;
+    let usage = response
+        .usage_metadata
+        .as_ref()
+        .map(|u| Usage {
+            prompt_tokens: u.prompt_token_count.unwrap_or(0),
+            completion_tokens: u.candidates_token_count.unwrap_or(0),
+            total_tokens: u.total_token_count.unwrap_or(0),
+        })
+        .unwrap_or_default();
 
-        let mut content = None;
-        let mut tool_calls = Vec::new();
+    Ok(LlmResponse {
+        content,
+        tool_calls,
+        finish_reason: candidate
+            .finish_reason
+            .clone()
+            .unwrap_or_else(|| "stop".to_string()),
+        usage,
+    })
+}
 
-        for part in &candidate.content.parts {
-            if let Some(ref text) = part.text {
-                content = Some(text.clone());
-            }
+/// Merges the events of a `streamGenerateContent?alt=sse` response.
+#[derive(Default)]
+pub(super) struct StreamAccumulator {
+    /// Bytes of an event line that hasn't fully arrived yet
+    pending: Vec<u8>,
+    text: String,
+    tool_calls: Vec<ToolCallRequest>,
+    finish_reason: Option<String>,
+    usage: Usage,
+}
+
+impl StreamAccumulator {
+    /// Feed body bytes; returns the text of the events they completed.
+    ///
+    /// `unwrap` extracts the Gemini response from an event (Code Assist nests it).
+    pub(super) fn push(&mut self, bytes: &[u8], unwrap: fn(Value) -> Option<Value>) -> Result<String> {
+        self.pending.extend_from_slice(bytes);
+        let mut new_text = String::new();
+
+        while let Some(end) = self.pending.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.pending.drain(..=end).collect();
+            let line = String::from_utf8_lossy(&line);
+            let Some(data) = line.trim().strip_prefix("data:") else {
+                continue;
+            };
+
+            let event: Value = serde_json::from_str(data.trim())
+                .map_err(|e| Error::Llm(format!("Invalid stream event: {e}")))?;
+            let event = unwrap(event)
+                .ok_or_else(|| Error::Llm("Missing 'response' field in stream event".to_string()))?;
+            let chunk: GeminiResponse = serde_json::from_value(event)
+                .map_err(|e| Error::Llm(format!("Failed to parse stream event: {e}")))?;
 
-            if let Some(ref fc) = part.function_call {
-                tool_calls.push(ToolCallRequest {
-                    id: format!("tc_{}", tool_calls.len()),
-                    name: fc.name.clone(),
-                    arguments: fc.args.clone(),
-                });
+            if let Some(candidate) = chunk.candidates.first() {
+                for part in &candidate.content.parts {
+                    if let Some(text) = &part.text {
+                        new_text.push_str(text);
+                    }
+                    if let Some(fc) = &part.function_call {
+                        self.tool_calls.push(ToolCallRequest {
+                            id: format!("tc_{}", self.tool_calls.len()),
+                            name: fc.name.clone(),
+                            arguments: fc.args.clone(),
+                        });
+                    }
+                }
+                if candidate.finish_reason.is_some() {
+                    self.finish_reason = candidate.finish_reason.clone();
+                }
+            }
+            if let Some(u) = &chunk.usage_metadata {
+                self.usage = Usage {
+                    prompt_tokens: u.prompt_token_count.unwrap_or(0),
+                    completion_tokens: u.candidates_token_count.unwrap_or(0),
+                    total_tokens: u.total_token_count.unwrap_or(0),
+                };
             }
         }
 
-        let usage = response
-            .usage_metadata
-            .as_ref()
-            .map(|u| Usage {
-                prompt_tokens: u.prompt_token_count.unwrap_or(0),
-                completion_tokens: u.candidates_token_count.unwrap_or(0),
-                total_tokens: u.total_token_count.unwrap_or(0),
-            })
-            .unwrap_or_default();
-
-        Ok(LlmResponse {
-            content,
-            tool_calls,
-            finish_reason: candidate
-                .finish_reason
-                .clone()
-                .unwrap_or_else(|| "stop".to_string()),
-            usage,
-        })
+        self.text.push_str(&new_text);
+        Ok(new_text)
+    }
+
+    pub(super) fn finish(self) -> LlmResponse {
+        LlmResponse {
+            content: Some(self.text).filter(|t| !t.is_empty()),
+            tool_calls: self.tool_calls,
+            finish_reason: self.finish_reason.unwrap_or_else(|| "stop".to_string()),
+            usage: self.usage,
+        }
+    }
+}
+
+/// Read a streamed response body to the end, passing text to `on_text` as it arrives.
+pub(super) async fn read_stream(
+    mut response: reqwest::Response,
+    unwrap: fn(Value) -> Option<Value>,
+    on_text: &OnText<'_>,
+) -> Result<LlmResponse> {
+    let mut stream = StreamAccumulator::default();
+    while let Some(bytes) = response.chunk().await? {
+        let text = stream.push(&bytes, unwrap)?;
+        if !text.is_empty() {
+            on_text(&text);
+        }
+    }
+
+    // The last event may not end with a newline
+    let text = stream.push(b"\n", unwrap)?;
+    if !text.is_empty() {
+        on_text(&text);
     }
+    Ok(stream.finish())
 }
 
 #[async_trait]
@@ -184,40 +392,183 @@ impl LlmClient for GeminiClient {
         messages: &[Message],
         tools: &[ToolDefinition],
     ) -> Result<LlmResponse> {
-        let contents = self.convert_messages(messages);
-        let system_instruction = self.get_system_instruction(messages);
-
-        let mut request = json!({
-            "contents": contents,
-            "generationConfig": {
-                "temperature": 0.7,
-                "maxOutputTokens": 8192
-            }
-        });
+        let request = build_request(messages, tools, self.max_output_tokens);
 
-        if let Some(system) = system_instruction {
-            request["systemInstruction"] = json!({
-                "parts": [{"text": system}]
-            });
-        }
+        // Clippy fix: removed unnecessary borrow
+        let response = self.client.post(self.build_url("generateContent")).json(&request).send().await?;
 
-        if let Some(tool_config) = self.convert_tools(tools) {
-            request["tools"] = tool_config;
+        if !response.status().is_success() {
+            let error_text = response.text().await?;
+            return Err(Error::Llm(format!("Gemini API error: {error_text}")));
         }
 
-        // Clippy fix: removed unnecessary borrow
-        let response = self.client.post(self.build_url()).json(&request).send().await?;
+        let gemini_response: GeminiResponse = response.json().await?;
+        parse_response(&gemini_response)
+    }
+
+    async fn chat_stream(
+        &self,
+        messages: &[Message],
+        tools: &[ToolDefinition],
+        on_text: &OnText<'_>,
+    ) -> Result<LlmResponse> {
+        let request = build_request(messages, tools, self.max_output_tokens);
+        let url = format!("{}&alt=sse", self.build_url("streamGenerateContent"));
+        let response = self.client.post(url).json(&request).send().await?;
 
         if !response.status().is_success() {
             let error_text = response.text().await?;
             return Err(Error::Llm(format!("Gemini API error: {error_text}")));
         }
 
-        let gemini_response: GeminiResponse = response.json().await?;
-        self.parse_response(&gemini_response)
+        read_stream(response, Some, on_text).await
     }
 
     fn default_model(&self) -> &str {
         &self.model
     }
+
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        if texts.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let request = build_embed_request(texts);
+        let response = self.client.post(self.build_embed_url()).json(&request).send().await?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await?;
+            return Err(Error::Llm(format!("Gemini embeddings error: {error_text}")));
+        }
+
+        let embed_response: BatchEmbedResponse = response.json().await?;
+        if embed_response.embeddings.len() != texts.len() {
+            return Err(Error::Llm(format!(
+                "Expected {} embeddings, got {}",
+                texts.len(),
+                embed_response.embeddings.len()
+            )));
+        }
+
+        Ok(embed_response.embeddings.into_iter().map(|e| e.values).collect())
+    }
+
+    async fn list_models(&self) -> Result<Vec<String>> {
+        let mut names = Vec::new();
+        let mut page_token: Option<String> = None;
+
+        loop {
+            let mut query = vec![("key", self.api_key.as_str()), ("pageSize", "100")];
+            if let Some(token) = &page_token {
+                query.push(("pageToken", token.as_str()));
+            }
+
+            let response = self.client.get(GEMINI_API_URL).query(&query).send().await?;
+            if !response.status().is_success() {
+                let error_text = response.text().await?;
+                return Err(Error::Llm(format!("Gemini models error: {error_text}")));
+            }
+
+            let list: ModelList = response.json().await?;
+            names.extend(chat_model_names(&list));
+            match list.next_page_token.filter(|t| !t.is_empty()) {
+                Some(token) => page_token = Some(token),
+                None => break,
+            }
+        }
+
+        names.sort();
+        Ok(names)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_max_output_tokens_per_model() {
+        assert_eq!(default_max_output_tokens("gemini-2.0-flash"), 8192);
+        assert_eq!(default_max_output_tokens("gemini-2.5-pro"), 65_536);
+        assert_eq!(default_max_output_tokens("gemini-2.5-flash-lite"), 65_536);
+        assert_eq!(default_max_output_tokens("gemini-2.0-flash-lite"), 8192);
+        assert_eq!(default_max_output_tokens("gemma-3-27b-it"), 8192);
+
+        let client = GeminiClient::new("key", "gemini-2.5-pro");
+        assert_eq!(client.max_output_tokens, 65_536);
+        assert_eq!(client.clone().with_max_output_tokens(None).max_output_tokens, 65_536);
+        assert_eq!(client.with_max_output_tokens(Some(2048)).max_output_tokens, 2048);
+        assert_eq!(build_request(&[], &[], 2048)["generationConfig"]["maxOutputTokens"], 2048);
+    }
+
+    #[test]
+    fn test_chat_model_names() {
+        let list: ModelList = serde_json::from_value(json!({
+            "models": [
+                {"name": "models/gemini-2.0-flash", "supportedGenerationMethods": ["generateContent", "countTokens"]},
+                {"name": "models/text-embedding-004", "supportedGenerationMethods": ["embedContent"]}
+            ],
+            "nextPageToken": "abc"
+        }))
+        .unwrap();
+
+        assert_eq!(chat_model_names(&list), vec!["gemini-2.0-flash"]);
+        assert_eq!(list.next_page_token.as_deref(), Some("abc"));
+    }
+
+    #[test]
+    fn test_build_request_groups_tool_results() {
+        let messages = vec![
+            Message::system("You are Leo"),
+            Message::user("Read a.txt and b.txt"),
+            Message::assistant_with_tools("", vec![
+                ToolCallRequest { id: "tc_0".to_string(), name: "read_file".to_string(), arguments: json!({"path": "a.txt"}) },
+                ToolCallRequest { id: "tc_1".to_string(), name: "read_file".to_string(), arguments: json!({"path": "b.txt"}) },
+            ]),
+            Message::tool_result("tc_0", "A"),
+            Message::tool_result("tc_1", "B"),
+        ];
+
+        let request = build_request(&messages, &[], 8192);
+
+        assert_eq!(request["systemInstruction"]["parts"][0]["text"], "You are Leo");
+        let contents = request["contents"].as_array().unwrap();
+        assert_eq!(contents.len(), 3);
+        assert_eq!(contents[1]["role"], "model");
+        assert_eq!(contents[2]["role"], "function");
+        assert_eq!(contents[2]["parts"].as_array().unwrap().len(), 2);
+        assert!(request.get("tools").is_none());
+    }
+
+    #[test]
+    fn test_stream_accumulator() {
+        let mut stream = StreamAccumulator::default();
+        let first = "data: {\"candidates\": [{\"content\": {\"parts\": [{\"text\": \"Hel\"}]}}]}\r\n\r\n";
+        assert_eq!(stream.push(first.as_bytes(), Some).unwrap(), "Hel");
+
+        // An event split across reads is only handled once complete
+        let second = "data: {\"candidates\": [{\"content\": {\"parts\": [{\"text\": \"lo\"}, \
+                      {\"functionCall\": {\"name\": \"read_file\", \"args\": {\"path\": \"a\"}}}]}, \
+                      \"finishReason\": \"STOP\"}], \"usageMetadata\": {\"totalTokenCount\": 12}}\n";
+        let (head, tail) = second.split_at(30);
+        assert_eq!(stream.push(head.as_bytes(), Some).unwrap(), "");
+        assert_eq!(stream.push(tail.as_bytes(), Some).unwrap(), "lo");
+
+        let response = stream.finish();
+        assert_eq!(response.content.as_deref(), Some("Hello"));
+        assert_eq!(response.tool_calls.len(), 1);
+        assert_eq!(response.tool_calls[0].name, "read_file");
+        assert_eq!(response.finish_reason, "STOP");
+        assert_eq!(response.usage.total_tokens, 12);
+    }
+
+    #[test]
+    fn test_build_embed_request() {
+        let request = build_embed_request(&["hello".to_string(), "world".to_string()]);
+
+        let requests = request["requests"].as_array().unwrap();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0]["model"], "models/text-embedding-004");
+        assert_eq!(requests[1]["content"]["parts"][0]["text"], "world");
+    }
 }
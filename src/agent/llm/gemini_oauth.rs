@@ -13,8 +13,9 @@ use crate::error::Error;
 use crate::tools::ToolDefinition;
 use crate::Result;
 
-use super::super::message::{Message, Role, ToolCallRequest};
-use super::{GeminiResponse, LlmClient, LlmResponse, Usage};
+use super::super::message::Message;
+use super::gemini::{build_request, default_max_output_tokens, parse_response, read_stream};
+use super::{GeminiResponse, LlmClient, LlmResponse, OnText};
 
 /// Code Assist API endpoint (same as Gemini CLI uses).
 const CODE_ASSIST_ENDPOINT: &str = "https://cloudcode-pa.googleapis.com";
@@ -25,6 +26,7 @@ const CODE_ASSIST_API_VERSION: &str = "v1internal";
 pub struct GeminiOAuthClient {
     auth_provider: GeminiAuthProvider,
     model: String,
+    max_output_tokens: u32,
     client: Client,
     project_id: Option<String>,
     session_id: String,
@@ -39,6 +41,7 @@ impl GeminiOAuthClient {
         Ok(Self {
             auth_provider,
             model: model.to_string(),
+            max_output_tokens: default_max_output_tokens(model),
             client: Client::new(),
             project_id: None, // Will be fetched via Code Assist API
             session_id: Uuid::new_v4().to_string(),
@@ -50,12 +53,28 @@ impl GeminiOAuthClient {
         Self {
             auth_provider: GeminiAuthProvider::new(client_id, client_secret),
             model: model.to_string(),
+            max_output_tokens: default_max_output_tokens(model),
             client: Client::new(),
             project_id: None,
             session_id: Uuid::new_v4().to_string(),
         }
     }
 
+    /// Ask for the Google Calendar scope when signing in, so the calendar tool
+    /// can share this sign-in.
+    pub fn with_calendar(mut self, enabled: bool) -> Self {
+        self.auth_provider = self.auth_provider.with_calendar(enabled);
+        self
+    }
+
+    /// Cap replies at `max_output_tokens` instead of the model's default (`None` keeps it).
+    pub fn with_max_output_tokens(mut self, max_output_tokens: Option<u32>) -> Self {
+        if let Some(max) = max_output_tokens {
+            self.max_output_tokens = max;
+        }
+        self
+    }
+
     /// Build Code Assist API URL for a method.
     fn build_code_assist_url(&self, method: &str) -> String {
         format!(
@@ -216,179 +235,28 @@ impl GeminiOAuthClient {
             }
         }
 
-        Err(Error::Auth("Operation polling timeout".to_string()))
-    }
-
-    fn convert_messages(&self, messages: &[Message]) -> Vec<Value> {
-        let mut result: Vec<Value> = Vec::new();
-        let mut tool_responses: Vec<Value> = Vec::new();
-        
-        for m in messages.iter().filter(|m| m.role != Role::System) {
-            // If we have accumulated tool responses and hit a non-tool message, flush them
-            if m.role != Role::Tool && !tool_responses.is_empty() {
-                result.push(json!({
-                    "role": "function",
-                    "parts": tool_responses.clone()
-                }));
-                tool_responses.clear();
-            }
-            
-            match m.role {
-                Role::Tool => {
-                    // Accumulate tool responses into a single message
-                    tool_responses.push(json!({
-                        "functionResponse": {
-                            "name": m.tool_call_id.as_deref().unwrap_or("unknown"),
-                            "response": {"result": m.content}
-                        }
-                    }));
-                },
-                Role::Assistant if m.tool_calls.is_some() => {
-                    let calls: Vec<Value> = m.tool_calls.as_ref().unwrap()
-                        .iter()
-                        .map(|tc| {
-                            json!({
-                                "functionCall": {
-                                    "name": tc.name,
-                                    "args": tc.arguments
-                                }
-                            })
-                        })
-                        .collect();
-                    result.push(json!({
-                        "role": "model",
-                        "parts": calls
-                    }));
-                },
-                _ => {
-                    let role = match m.role {
-                        Role::User => "user",
-                        Role::Assistant => "model",
-                        _ => "user",
-                    };
-                    result.push(json!({
-                        "role": role,
-                        "parts": [{"text": m.content}]
-                    }));
-                }
-            }
-        }
-        
-        // Flush any remaining tool responses
-        if !tool_responses.is_empty() {
-            result.push(json!({
-                "role": "function",
-                "parts": tool_responses
-            }));
-        }
-        
-        result
-    }
-
-    fn get_system_instruction(&self, messages: &[Message]) -> Option<String> {
-        messages
-            .iter()
-            .find(|m| m.role == Role::System)
-            .map(|m| m.content.clone())
+        Err(Error::Timeout("waiting for Code Assist project onboarding".to_string()))
     }
 
-    fn convert_tools(&self, tools: &[ToolDefinition]) -> Option<Value> {
-        if tools.is_empty() {
-            return None;
-        }
-
-        let function_declarations: Vec<Value> = tools
-            .iter()
-            .map(|t| {
-                json!({
-                    "name": t.name,
-                    "description": t.description,
-                    "parameters": t.parameters
-                })
-            })
-            .collect();
-
-        Some(json!([{
-            "functionDeclarations": function_declarations
-        }]))
-    }
-
-    fn parse_response(&self, response: &GeminiResponse) -> Result<LlmResponse> {
-        let candidate = response
-            .candidates
-            .first()
-            .ok_or_else(|| Error::Llm("No candidates in response".to_string()))?;
-
-        let mut content = None;
-        let mut tool_calls = Vec::new();
-
-        for part in &candidate.content.parts {
-            if let Some(ref text) = part.text {
-                content = Some(text.clone());
-            }
-
-            if let Some(ref fc) = part.function_call {
-                tool_calls.push(ToolCallRequest {
-                    id: format!("tc_{}", tool_calls.len()),
-                    name: fc.name.clone(),
-                    arguments: fc.args.clone(),
-                });
-            }
-        }
-
-        let usage = response
-            .usage_metadata
-            .as_ref()
-            .map(|u| Usage {
-                prompt_tokens: u.prompt_token_count.unwrap_or(0),
-                completion_tokens: u.candidates_token_count.unwrap_or(0),
-                total_tokens: u.total_token_count.unwrap_or(0),
-            })
-            .unwrap_or_default();
-
-        Ok(LlmResponse {
-            content,
-            tool_calls,
-            finish_reason: candidate
-                .finish_reason
-                .clone()
-                .unwrap_or_else(|| "stop".to_string()),
-            usage,
-        })
-    }
-}
-
-#[async_trait]
-impl LlmClient for GeminiOAuthClient {
-    async fn chat(&self, messages: &[Message], tools: &[ToolDefinition]) -> Result<LlmResponse> {
+    /// POST a chat request to Code Assist `method`, retrying on rate limits and
+    /// retryable transport errors (see `Error::is_retryable`).
+    ///
+    /// Returns the successful response, unread.
+    async fn send_chat(
+        &self,
+        method: &str,
+        messages: &[Message],
+        tools: &[ToolDefinition],
+    ) -> Result<reqwest::Response> {
         // Get valid access token (may trigger OAuth flow)
         let access_token = self.auth_provider.get_valid_token().await?;
 
         // Get project ID via Code Assist API
         let project_id = self.get_or_fetch_project_id(&access_token).await?;
 
-        let contents = self.convert_messages(messages);
-        let system_instruction = self.get_system_instruction(messages);
-
         // Build the inner request (Vertex format)
-        let mut inner_request = json!({
-            "contents": contents,
-            "generationConfig": {
-                "temperature": 0.7,
-                "maxOutputTokens": 8192
-            },
-            "session_id": self.session_id
-        });
-
-        if let Some(system) = system_instruction {
-            inner_request["systemInstruction"] = json!({
-                "parts": [{"text": system}]
-            });
-        }
-
-        if let Some(tool_config) = self.convert_tools(tools) {
-            inner_request["tools"] = tool_config;
-        }
+        let mut inner_request = build_request(messages, tools, self.max_output_tokens);
+        inner_request["session_id"] = json!(self.session_id);
 
         // Generate a user prompt ID for tracking
         let user_prompt_id = Uuid::new_v4().to_string();
@@ -402,14 +270,14 @@ impl LlmClient for GeminiOAuthClient {
         });
 
         // Use Code Assist API endpoint (same as Gemini CLI)
-        let url = self.build_code_assist_url("generateContent");
+        let url = self.build_code_assist_url(method);
 
         let mut retry_count = 0;
         let max_retries = 5;
         let mut backoff = std::time::Duration::from_secs(1);
 
         loop {
-            let response = self
+            let sent = self
                 .client
                 .post(&url)
                 .header("User-Agent", "google-api-nodejs-client/leo")
@@ -417,22 +285,23 @@ impl LlmClient for GeminiOAuthClient {
                 .bearer_auth(&access_token)
                 .json(&code_assist_request)
                 .send()
-                .await?;
+                .await;
+            let response = match sent {
+                Ok(response) => response,
+                Err(e) => {
+                    let err = Error::from(e);
+                    if !err.is_retryable() || retry_count >= max_retries {
+                        return Err(err);
+                    }
+                    retry_count += 1;
+                    tracing::debug!("Request failed ({}), retry {} in {:?}", err, retry_count, backoff);
+                    backoff = back_off(backoff).await;
+                    continue;
+                }
+            };
 
             if response.status().is_success() {
-                // Code Assist API returns { response: { ... standard response ... } }
-                let code_assist_response: Value = response.json().await?;
-
-                // Extract the nested response
-                let inner_response = code_assist_response.get("response").ok_or_else(|| {
-                    Error::Llm("Missing 'response' field in Code Assist response".to_string())
-                })?;
-
-                // Parse as standard Gemini response
-                let gemini_response: GeminiResponse = serde_json::from_value(inner_response.clone())
-                    .map_err(|e| Error::Llm(format!("Failed to parse response: {e}")))?;
-
-                return self.parse_response(&gemini_response);
+                return Ok(response);
             }
 
             let status = response.status();
@@ -444,13 +313,7 @@ impl LlmClient for GeminiOAuthClient {
             if is_rate_limited && retry_count < max_retries {
                 retry_count += 1;
                 tracing::debug!("Rate limit (429), retry {} in {:?}", retry_count, backoff);
-
-                tokio::time::sleep(backoff).await;
-
-                // Exponential backoff with jitter
-                let jitter = rand::random::<f64>() * 0.5 + 0.5; // 0.5x to 1.0x
-                let next_backoff = backoff.mul_f64(2.0 * jitter);
-                backoff = next_backoff.min(std::time::Duration::from_secs(60));
+                backoff = back_off(backoff).await;
                 continue;
             }
 
@@ -466,6 +329,48 @@ impl LlmClient for GeminiOAuthClient {
             )));
         }
     }
+}
+
+/// Wait `backoff`, then return the next one (exponential with jitter, capped at 60s)
+async fn back_off(backoff: std::time::Duration) -> std::time::Duration {
+    tokio::time::sleep(backoff).await;
+
+    let jitter = rand::random::<f64>() * 0.5 + 0.5; // 0.5x to 1.0x
+    backoff.mul_f64(2.0 * jitter).min(std::time::Duration::from_secs(60))
+}
+
+/// Code Assist wraps each Gemini response as `{ response: { ... } }`
+fn unwrap_code_assist(mut body: Value) -> Option<Value> {
+    body.get_mut("response").map(Value::take)
+}
+
+#[async_trait]
+impl LlmClient for GeminiOAuthClient {
+    async fn chat(&self, messages: &[Message], tools: &[ToolDefinition]) -> Result<LlmResponse> {
+        let response = self.send_chat("generateContent", messages, tools).await?;
+        let code_assist_response: Value = response.json().await?;
+
+        // Extract the nested response
+        let inner_response = unwrap_code_assist(code_assist_response).ok_or_else(|| {
+            Error::Llm("Missing 'response' field in Code Assist response".to_string())
+        })?;
+
+        // Parse as standard Gemini response
+        let gemini_response: GeminiResponse = serde_json::from_value(inner_response)
+            .map_err(|e| Error::Llm(format!("Failed to parse response: {e}")))?;
+
+        parse_response(&gemini_response)
+    }
+
+    async fn chat_stream(
+        &self,
+        messages: &[Message],
+        tools: &[ToolDefinition],
+        on_text: &OnText<'_>,
+    ) -> Result<LlmResponse> {
+        let response = self.send_chat("streamGenerateContent?alt=sse", messages, tools).await?;
+        read_stream(response, unwrap_code_assist, on_text).await
+    }
 
     fn default_model(&self) -> &str {
         &self.model
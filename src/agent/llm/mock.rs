@@ -0,0 +1,154 @@
+//! Scripted LLM client for end-to-end tests.
+//!
+//! Selected with `"provider": "mock"` and `"mock_script": "<file>"`. The script
+//! is a JSON array of replies, returned in order, one per LLM call:
+//!
+//! ```json
+//! [
+//!   { "tool_calls": [{ "name": "read_file", "arguments": { "path": "notes.md" } }] },
+//!   "Your notes say hello."
+//! ]
+//! ```
+//!
+//! A string is a plain text reply; an object may have `text` and/or `tool_calls`.
+
+use std::collections::VecDeque;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use super::{LlmClient, LlmResponse, Message, ToolCallRequest, Usage};
+use crate::config::Config;
+use crate::error::Error;
+use crate::tools::ToolDefinition;
+use crate::Result;
+
+/// One scripted reply.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ScriptEntry {
+    Text(String),
+    Reply {
+        #[serde(default)]
+        text: Option<String>,
+        #[serde(default)]
+        tool_calls: Vec<ScriptToolCall>,
+    },
+}
+
+#[derive(Deserialize)]
+struct ScriptToolCall {
+    name: String,
+    #[serde(default)]
+    arguments: serde_json::Value,
+}
+
+/// LLM client that replays scripted responses instead of calling an API.
+///
+/// Clones share one script, so subagents consume the same queue.
+#[derive(Clone)]
+pub struct MockClient {
+    responses: Arc<Mutex<VecDeque<LlmResponse>>>,
+}
+
+impl MockClient {
+    /// Replay `responses` in order.
+    pub fn new(responses: Vec<LlmResponse>) -> Self {
+        Self { responses: Arc::new(Mutex::new(responses.into())) }
+    }
+
+    /// Load the script named by `config.mock_script`.
+    pub fn from_config(config: &Config) -> Result<Self> {
+        let path = config.mock_script.as_deref().ok_or_else(|| {
+            Error::Config("The mock provider needs \"mock_script\" set in the config".to_string())
+        })?;
+        Self::from_script(path)
+    }
+
+    /// Load a script (see the module docs for the format).
+    pub fn from_script(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| Error::Config(format!("Failed to read mock script {:?}: {}", path, e)))?;
+        Self::parse(&text).map_err(|e| Error::Config(format!("Invalid mock script {:?}: {}", path, e)))
+    }
+
+    fn parse(script: &str) -> std::result::Result<Self, serde_json::Error> {
+        let entries: Vec<ScriptEntry> = serde_json::from_str(script)?;
+        let responses = entries
+            .into_iter()
+            .enumerate()
+            .map(|(i, entry)| match entry {
+                ScriptEntry::Text(text) => LlmResponse::text(text),
+                ScriptEntry::Reply { text, tool_calls } => {
+                    let tool_calls: Vec<ToolCallRequest> = tool_calls
+                        .into_iter()
+                        .enumerate()
+                        .map(|(j, call)| ToolCallRequest {
+                            id: format!("mock_{}_{}", i + 1, j + 1),
+                            name: call.name,
+                            arguments: call.arguments,
+                        })
+                        .collect();
+                    let finish_reason = if tool_calls.is_empty() { "stop" } else { "tool_calls" };
+                    LlmResponse {
+                        content: text,
+                        tool_calls,
+                        finish_reason: finish_reason.to_string(),
+                        usage: Usage::default(),
+                    }
+                }
+            })
+            .collect();
+        Ok(Self::new(responses))
+    }
+}
+
+#[async_trait]
+impl LlmClient for MockClient {
+    async fn chat(&self, _messages: &[Message], _tools: &[ToolDefinition]) -> Result<LlmResponse> {
+        self.responses
+            .lock()
+            .unwrap()
+            .pop_front()
+            .ok_or_else(|| Error::Llm("Mock script has no more responses".to_string()))
+    }
+
+    fn default_model(&self) -> &str {
+        "mock"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_script_replays_in_order() {
+        let client = MockClient::parse(
+            r#"[
+                {"tool_calls": [{"name": "read_file", "arguments": {"path": "notes.md"}}]},
+                {"text": "Thinking", "tool_calls": []},
+                "Done"
+            ]"#,
+        )
+        .unwrap();
+
+        let first = client.chat(&[], &[]).await.unwrap();
+        assert_eq!(first.content, None);
+        assert_eq!(first.tool_calls[0].name, "read_file");
+        assert_eq!(first.tool_calls[0].id, "mock_1_1");
+        assert_eq!(first.finish_reason, "tool_calls");
+
+        assert_eq!(client.chat(&[], &[]).await.unwrap().content.as_deref(), Some("Thinking"));
+        assert_eq!(client.chat(&[], &[]).await.unwrap().content.as_deref(), Some("Done"));
+        assert!(client.chat(&[], &[]).await.is_err());
+    }
+
+    #[test]
+    fn test_invalid_script() {
+        assert!(MockClient::parse(r#"{"text": "not a list"}"#).is_err());
+        assert!(MockClient::from_script(Path::new("/nonexistent/script.json")).is_err());
+    }
+}
@@ -3,17 +3,21 @@
 //! This module provides:
 //! - [`LlmClient`] trait for swappable LLM providers
 //! - [`ProviderRegistry`] for dynamic provider creation
-//! - Concrete implementations: Gemini API key, Gemini OAuth
+//! - Concrete implementations: Gemini API key, Gemini OAuth, and a scripted
+//!   [`MockClient`] for end-to-end tests
 //!
 //! # Adding a New Provider
 //!
 //! 1. Create a new file (e.g., `openai.rs`)
 //! 2. Implement `LlmClient` trait
-//! 3. Add to `ProviderRegistry::create()`
+//! 3. Add to the built-ins in `ProviderRegistry::new()`, or call
+//!    [`ProviderRegistry::register`] from your own crate
 //! 4. Add config fields in `config.rs`
 
 mod types;
 
+use std::collections::HashMap;
+
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 
@@ -27,9 +31,11 @@ pub use types::*;
 // Re-export concrete implementations
 pub mod gemini;
 pub mod gemini_oauth;
+pub mod mock;
 
 pub use gemini::GeminiClient;
 pub use gemini_oauth::GeminiOAuthClient;
+pub use mock::MockClient;
 
 use super::message::{Message, ToolCallRequest};
 
@@ -67,6 +73,9 @@ impl LlmResponse {
     }
 }
 
+/// Receives streamed text as it arrives (see [`LlmClient::chat_stream`]).
+pub type OnText<'a> = dyn Fn(&str) + Send + Sync + 'a;
+
 /// Token usage information.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Usage {
@@ -87,43 +96,119 @@ pub trait LlmClient: Send + Sync {
         tools: &[ToolDefinition],
     ) -> Result<LlmResponse>;
 
+    /// Like [`chat`](Self::chat), passing text to `on_text` as it arrives.
+    ///
+    /// Providers without streaming keep the default, which sends the whole text at once.
+    async fn chat_stream(
+        &self,
+        messages: &[Message],
+        tools: &[ToolDefinition],
+        on_text: &OnText<'_>,
+    ) -> Result<LlmResponse> {
+        let response = self.chat(messages, tools).await?;
+        if let Some(text) = &response.content {
+            on_text(text);
+        }
+        Ok(response)
+    }
+
     /// Get the default model for this provider.
     fn default_model(&self) -> &str;
+
+    /// Embed texts into vectors, one per input, in the same order.
+    ///
+    /// Providers without an embeddings endpoint keep the default, which errors.
+    async fn embed(&self, _texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        Err(Error::Llm("embeddings unsupported".to_string()))
+    }
+
+    /// List the chat models this provider currently offers.
+    ///
+    /// Providers without a models endpoint keep the default, which errors.
+    async fn list_models(&self) -> Result<Vec<String>> {
+        Err(Error::Llm("model listing unsupported".to_string()))
+    }
 }
 
-/// Provider registry — creates LLM clients dynamically.
+/// Builds an LLM client for a provider from configuration.
+pub type ProviderFactory = Box<dyn Fn(&Config) -> Result<Box<dyn LlmClient>> + Send + Sync>;
+
+/// Provider registry — creates LLM clients by provider name.
+///
+/// Built-in providers are pre-registered; embedders can add their own.
 ///
 /// # Example
 ///
 /// ```ignore
-/// let client = ProviderRegistry::create(&config)?;
+/// let mut registry = ProviderRegistry::new();
+/// registry.register("my-llm", |config| Ok(Box::new(MyClient::new(&config.model))));
+/// let client = registry.create(&config.provider, &config)?;
 /// let response = client.chat(&messages, &tools).await?;
 /// ```
-pub struct ProviderRegistry;
+pub struct ProviderRegistry {
+    factories: HashMap<String, ProviderFactory>,
+}
 
 impl ProviderRegistry {
-    /// Create an LLM client from configuration.
-    ///
-    /// Supported providers:
+    /// Create a registry with the built-in providers:
     /// - `"gemini"`: Gemini API with API key authentication
     /// - `"google-cli"`: Gemini with OAuth (uses Gemini CLI credentials)
-    pub fn create(config: &Config) -> Result<Box<dyn LlmClient>> {
-        match config.provider.as_str() {
-            "gemini" => {
-                let client = GeminiClient::new(&config.gemini_api_key, &config.model);
-                Ok(Box::new(client))
-            }
-            "google-cli" => {
-                let client = GeminiOAuthClient::from_cli(&config.model)?;
-                Ok(Box::new(client))
-            }
-            other => Err(Error::Config(format!("Unknown provider: {other}"))),
-        }
+    /// - `"mock"`: scripted replies from `mock_script`, for tests
+    pub fn new() -> Self {
+        let mut registry = Self { factories: HashMap::new() };
+        registry.register("gemini", |config| {
+            Ok(Box::new(
+                GeminiClient::new(&config.gemini_api_key, &config.model).with_max_output_tokens(config.max_output_tokens),
+            ))
+        });
+        registry.register("google-cli", |config| {
+            Ok(Box::new(
+                GeminiOAuthClient::from_cli(&config.model)?
+                    .with_calendar(config.google_calendar)
+                    .with_max_output_tokens(config.max_output_tokens),
+            ))
+        });
+        registry.register("mock", |config| Ok(Box::new(MockClient::from_config(config)?)));
+        registry
     }
 
-    /// List available provider names.
+    /// Register (or replace) the factory for a provider name.
+    pub fn register<F>(&mut self, name: impl Into<String>, factory: F)
+    where
+        F: Fn(&Config) -> Result<Box<dyn LlmClient>> + Send + Sync + 'static,
+    {
+        self.factories.insert(name.into(), Box::new(factory));
+    }
+
+    /// Create an LLM client for the named provider.
+    pub fn create(&self, name: &str, config: &Config) -> Result<Box<dyn LlmClient>> {
+        let factory = self.factories.get(name).ok_or_else(|| {
+            Error::Config(format!("Unknown provider: {name} (available: {})", self.names().join(", ")))
+        })?;
+        factory(config)
+    }
+
+    /// Check if a provider is registered.
+    pub fn has(&self, name: &str) -> bool {
+        self.factories.contains_key(name)
+    }
+
+    /// Registered provider names, sorted.
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.factories.keys().map(|s| s.as_str()).collect();
+        names.sort();
+        names
+    }
+
+    /// List the built-in provider names.
     pub fn available() -> &'static [&'static str] {
-        &["gemini", "google-cli"]
+        &["gemini", "google-cli", "mock"]
+    }
+}
+
+impl Default for ProviderRegistry {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -144,6 +229,13 @@ impl FakeLlmClient {
         }
     }
 
+    /// Create with arbitrary predefined responses.
+    pub fn from_responses(responses: Vec<LlmResponse>) -> Self {
+        Self {
+            responses: std::sync::Mutex::new(responses.into()),
+        }
+    }
+
     /// Create with a single tool call followed by a text response.
     pub fn with_tool_call(name: &str, args: serde_json::Value, final_response: &str) -> Self {
         let tool_response = LlmResponse {
@@ -198,4 +290,20 @@ mod tests {
         let resp2 = client.chat(&[], &[]).await.unwrap();
         assert_eq!(resp2.content.as_deref(), Some("World!"));
     }
+
+    #[tokio::test]
+    async fn test_provider_registry_register() {
+        let mut registry = ProviderRegistry::new();
+        assert!(registry.has("gemini"));
+        assert!(registry.create("nope", &Config::default()).is_err());
+
+        registry.register("fake", |_config| Ok(Box::new(FakeLlmClient::new(vec!["hi"]))));
+        assert_eq!(registry.names(), vec!["fake", "gemini", "google-cli", "mock"]);
+        assert!(registry.create("mock", &Config::default()).is_err());
+
+        let client = registry.create("fake", &Config::default()).unwrap();
+        assert_eq!(client.default_model(), "fake-model");
+        let response = client.chat(&[], &[]).await.unwrap();
+        assert_eq!(response.content.as_deref(), Some("hi"));
+    }
 }
@@ -10,6 +10,8 @@ use serde_json::Value;
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct GeminiResponse {
+    /// Empty in some streamed events (e.g. a final usage-only one).
+    #[serde(default)]
     pub candidates: Vec<Candidate>,
     pub usage_metadata: Option<UsageMetadata>,
 }
@@ -51,3 +53,35 @@ pub struct UsageMetadata {
     pub candidates_token_count: Option<usize>,
     pub total_token_count: Option<usize>,
 }
+
+/// Response from `batchEmbedContents`.
+#[derive(Debug, Deserialize)]
+pub struct BatchEmbedResponse {
+    #[serde(default)]
+    pub embeddings: Vec<ContentEmbedding>,
+}
+
+/// A single embedding vector.
+#[derive(Debug, Deserialize)]
+pub struct ContentEmbedding {
+    pub values: Vec<f32>,
+}
+
+/// Response from the `models` list endpoint.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelList {
+    #[serde(default)]
+    pub models: Vec<ModelInfo>,
+    pub next_page_token: Option<String>,
+}
+
+/// A model as described by the `models` list endpoint.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelInfo {
+    /// Resource name, e.g. `models/gemini-2.0-flash`.
+    pub name: String,
+    #[serde(default)]
+    pub supported_generation_methods: Vec<String>,
+}
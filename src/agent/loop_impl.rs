@@ -1,19 +1,32 @@
 //! Agent loop - core message processing
 
-use tracing::{debug, info};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use tracing::{debug, info, info_span, warn, Instrument};
 
 use crate::Result;
 use crate::error::Error;
 
 use super::context::Context;
-use super::llm::LlmClient;
-use super::message::{Message, Response, ToolCallRequest};
+use super::limiter::{LimitedClient, RequestLimiter};
+use super::llm::{LlmClient, LlmResponse, OnText};
+use super::message::{Message, Response, Role, ToolCall, ToolCallRequest};
+use crate::tools::{stop_message, ToolDefinition, STOP_TOOL_NAME};
 use super::tokens::{estimate_tokens, TokenUsage};
 
+/// Sent once the iteration cap is hit to get a final answer without more tool calls
+const MAX_ITERATIONS_PROMPT: &str = "You have reached the tool-call limit for this request. \
+Do not call any more tools. Answer now with what you have, and say what is left unfinished.";
+
 /// The agent loop processes messages through LLM and tool execution
 pub struct AgentLoop<C: LlmClient> {
     client: C,
     max_iterations: usize,
+    limiter: Arc<RequestLimiter>,
+    /// Spend log to append each response's usage to (disabled when `None`)
+    usage_log: Option<PathBuf>,
 }
 
 impl<C: LlmClient> AgentLoop<C> {
@@ -22,24 +35,136 @@ impl<C: LlmClient> AgentLoop<C> {
         Self {
             client,
             max_iterations,
+            limiter: Arc::new(RequestLimiter::unlimited()),
+            usage_log: None,
         }
     }
     
+    /// The LLM client this loop talks to
+    pub fn client(&self) -> &C {
+        &self.client
+    }
+    
+    /// A copy of the client that waits on this loop's request limiter, for tools that call the LLM
+    pub fn limited_client(&self) -> LimitedClient<C>
+    where
+        C: Clone,
+    {
+        LimitedClient::new(self.client.clone(), self.limiter.clone())
+    }
+    
+    /// Record the estimated cost of every LLM response in `path`
+    pub fn with_usage_log(mut self, path: PathBuf) -> Self {
+        self.usage_log = Some(path);
+        self
+    }
+    
+    /// Share a request limiter for all LLM calls made by this loop
+    pub fn with_limiter(mut self, limiter: Arc<RequestLimiter>) -> Self {
+        self.limiter = limiter;
+        self
+    }
+    
+    /// Call the LLM once a request slot is free, streaming its text to `on_text` if given
+    async fn chat(&self, messages: &[Message], tools: &[ToolDefinition], on_text: Option<&OnText<'_>>) -> Result<LlmResponse> {
+        let span = info_span!(
+            "llm_call",
+            model = %self.client.default_model(),
+            prompt_tokens = tracing::field::Empty,
+            completion_tokens = tracing::field::Empty,
+        );
+        let response = async {
+            let _permit = self.limiter.acquire().await;
+            match on_text {
+                Some(on_text) => self.client.chat_stream(messages, tools, on_text).await,
+                None => self.client.chat(messages, tools).await,
+            }
+        }
+        .instrument(span.clone())
+        .await?;
+        span.record("prompt_tokens", response.usage.prompt_tokens);
+        span.record("completion_tokens", response.usage.completion_tokens);
+        
+        crate::metrics::metrics().record_tokens(&response.usage);
+        
+        if let Some(path) = &self.usage_log {
+            if let Err(e) = super::cost::record_usage(path, self.client.default_model(), &response.usage) {
+                warn!("Failed to record usage: {}", e);
+            }
+        }
+        
+        Ok(response)
+    }
+    
     /// Run the agent loop for a single message
     pub async fn run(&self, history: &[Message], message: Message, ctx: &mut Context) -> Result<Response> {
+        self.run_measured(history, message, ctx, None).await
+    }
+    
+    /// Run the agent loop, passing the model's text to `on_text` as it streams in
+    ///
+    /// Text written before tool calls is streamed too; the returned response
+    /// holds only the final answer.
+    pub async fn run_streaming(
+        &self,
+        history: &[Message],
+        message: Message,
+        ctx: &mut Context,
+        on_text: &OnText<'_>,
+    ) -> Result<Response> {
+        self.run_measured(history, message, ctx, Some(on_text)).await
+    }
+    
+    /// `run_inner`, recorded in the request metrics
+    async fn run_measured(
+        &self,
+        history: &[Message],
+        message: Message,
+        ctx: &mut Context,
+        on_text: Option<&OnText<'_>>,
+    ) -> Result<Response> {
+        let span = info_span!("agent_run", session = %ctx.session, duration_ms = tracing::field::Empty);
+        let started = std::time::Instant::now();
+        let result = self.run_inner(history, message, ctx, on_text).instrument(span.clone()).await;
+        span.record("duration_ms", started.elapsed().as_millis() as u64);
+        crate::metrics::metrics().record_request(started.elapsed(), result.as_ref().err());
+        result
+    }
+    
+    async fn run_inner(
+        &self,
+        history: &[Message],
+        message: Message,
+        ctx: &mut Context,
+        on_text: Option<&OnText<'_>>,
+    ) -> Result<Response> {
+        ctx.tool_runner.set_session(&ctx.session);
+
         // Build messages from context
+        ctx.select_skills(&LoopClient(self), &message.content).await;
+        ctx.compact_history(&LoopClient(self), history).await;
+        ctx.scratchpad.clear();
+        ctx.attachments.take();
         let mut messages = ctx.build_messages(history, &message.content);
+        let system_prompt = messages[0].content.clone();
         
         info!("Starting agent loop with message: {}", message.content);
         
+        let mut trace = Vec::new();
+        let mut call_counts: HashMap<String, usize> = HashMap::new();
+        
         for iteration in 0..self.max_iterations {
             debug!("Iteration {}/{}", iteration + 1, self.max_iterations);
+            let iteration_span = info_span!("iteration", n = iteration + 1);
+            
+            // Keep the scratchpad in view as it changes
+            messages[0].content = ctx.with_scratchpad(&system_prompt);
             
             // Get tool definitions
             let tools = ctx.tool_runner.definitions();
             
             // Call LLM
-            let response = self.client.chat(&messages, &tools).await?;
+            let response = self.chat(&messages, &tools, on_text).instrument(iteration_span.clone()).await?;
 
             // Log token usage (first iteration only)
             if iteration == 0 {
@@ -59,7 +184,7 @@ impl<C: LlmClient> AgentLoop<C> {
             if !response.has_tool_calls() {
                 let content = response.content.unwrap_or_default();
                 info!("Agent completed with response: {} chars", content.len());
-                return Ok(Response::new(content));
+                return Ok(Response::new(content).with_trace(trace).with_media(Self::media(ctx)));
             }
             
             // Add assistant message with tool calls
@@ -70,34 +195,140 @@ impl<C: LlmClient> AgentLoop<C> {
             
             // Execute tool calls
             for tool_call in &response.tool_calls {
-                let result = self.execute_tool(ctx, tool_call).await;
+                // `done` ends the run with its message, skipping any remaining calls
+                if tool_call.name == STOP_TOOL_NAME {
+                    let content = stop_message(&tool_call.arguments)
+                        .map(String::from)
+                        .or_else(|| response.content.clone().filter(|c| !c.trim().is_empty()));
+                    if let Some(content) = content {
+                        info!("Agent finished via {} with response: {} chars", STOP_TOOL_NAME, content.len());
+                        trace.push(ToolCall {
+                            id: tool_call.id.clone(),
+                            name: tool_call.name.clone(),
+                            arguments: tool_call.arguments.clone(),
+                            result: Some(content.clone()),
+                        });
+                        return Ok(Response::new(content).with_trace(trace).with_media(Self::media(ctx)));
+                    }
+                }
+                
+                let count = call_counts.entry(tool_call.name.clone()).or_insert(0);
+                *count += 1;
+                let result = match ctx.config.tool_call_limit(&tool_call.name) {
+                    Some(limit) if *count > limit => {
+                        warn!("Tool {} is over its budget of {} calls", tool_call.name, limit);
+                        format!(
+                            "Error: '{}' has already been called {} times in this request, which is its limit. \
+                             Work with the results you have or use a different approach.",
+                            tool_call.name, limit
+                        )
+                    }
+                    _ => self.execute_tool(ctx, tool_call).instrument(iteration_span.clone()).await,
+                };
+                trace.push(ToolCall {
+                    id: tool_call.id.clone(),
+                    name: tool_call.name.clone(),
+                    arguments: tool_call.arguments.clone(),
+                    result: Some(result.clone()),
+                });
                 messages.push(Message::tool_result(&tool_call.id, result));
             }
         }
         
-        Err(Error::MaxIterations)
+        if ctx.config.error_on_max_iterations {
+            return Err(Error::MaxIterations);
+        }
+        
+        Ok(self.final_answer(messages).await.with_trace(trace).with_media(Self::media(ctx)))
     }
     
-    async fn execute_tool(&self, ctx: &mut Context, tool_call: &ToolCallRequest) -> String {
-        debug!("Executing tool: {} with args: {}", tool_call.name, tool_call.arguments);
+    /// Paths of the files attached during this run
+    fn media(ctx: &Context) -> Vec<String> {
+        ctx.attachments.take()
+            .into_iter()
+            .map(|path| path.display().to_string())
+            .collect()
+    }
+    
+    /// Ask for a text-only answer after the iteration cap, falling back to the last assistant text
+    async fn final_answer(&self, mut messages: Vec<Message>) -> Response {
+        warn!("Reached max iterations ({}), requesting a final answer", self.max_iterations);
         
-        // Log tool usage for debugging
-        crate::ui::print_tool(&tool_call.name);
+        let last_text = messages.iter()
+            .rev()
+            .find(|m| m.role == Role::Assistant && !m.content.trim().is_empty())
+            .map(|m| m.content.clone());
         
-        match ctx.tool_runner.execute(&tool_call.name, tool_call.arguments.clone()).await {
-            Ok(result) => {
-                debug!("Tool {} succeeded: {} chars", tool_call.name, result.len());
-                result
-            }
+        messages.push(Message::user(MAX_ITERATIONS_PROMPT));
+        let answer = match self.chat(&messages, &[], None).await {
+            Ok(response) => response.content.filter(|c| !c.trim().is_empty()),
             Err(e) => {
-                let error_msg = format!("Error: {}", e);
-                debug!("Tool {} failed: {}", tool_call.name, error_msg);
-                error_msg
+                warn!("Final answer request failed: {}", e);
+                None
             }
+        };
+        
+        let content = answer
+            .or(last_text)
+            .unwrap_or_else(|| "I ran out of steps before I could finish.".to_string());
+        Response::new(format!(
+            "{}\n\n(Stopped after reaching the limit of {} iterations.)",
+            content, self.max_iterations
+        ))
+    }
+    
+    async fn execute_tool(&self, ctx: &mut Context, tool_call: &ToolCallRequest) -> String {
+        // Show tool usage (arguments and outcome are logged by the runner's middleware)
+        crate::ui::print_tool(&tool_call.name);
+        
+        let span = info_span!("tool_call", tool = %tool_call.name, duration_ms = tracing::field::Empty);
+        let started = std::time::Instant::now();
+        let result = ctx.tool_runner.execute(&tool_call.name, tool_call.arguments.clone())
+            .instrument(span.clone())
+            .await;
+        span.record("duration_ms", started.elapsed().as_millis() as u64);
+
+        match result {
+            Ok(result) => result,
+            Err(e) => format!("Error: {}", e),
         }
     }
 }
 
+/// Lets context helpers call the LLM through [`AgentLoop::chat`], so their requests
+/// are limited, logged and counted like the loop's own.
+struct LoopClient<'a, C: LlmClient>(&'a AgentLoop<C>);
+
+#[async_trait::async_trait]
+impl<C: LlmClient> LlmClient for LoopClient<'_, C> {
+    async fn chat(&self, messages: &[Message], tools: &[ToolDefinition]) -> Result<LlmResponse> {
+        self.0.chat(messages, tools, None).await
+    }
+
+    async fn chat_stream(
+        &self,
+        messages: &[Message],
+        tools: &[ToolDefinition],
+        on_text: &OnText<'_>,
+    ) -> Result<LlmResponse> {
+        self.0.chat(messages, tools, Some(on_text)).await
+    }
+
+    fn default_model(&self) -> &str {
+        self.0.client.default_model()
+    }
+
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        let _permit = self.0.limiter.acquire().await;
+        self.0.client.embed(texts).await
+    }
+
+    async fn list_models(&self) -> Result<Vec<String>> {
+        let _permit = self.0.limiter.acquire().await;
+        self.0.client.list_models().await
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -131,5 +362,180 @@ mod tests {
         let response = agent.run(&[], msg, &mut ctx).await.unwrap();
         
         assert_eq!(response.content, "The file contains: test content");
+        assert_eq!(response.tool_trace.len(), 1);
+        assert_eq!(response.tool_trace[0].name, "read_file");
+        assert_eq!(response.tool_trace[0].arguments, json!({"path": "test.txt"}));
+        assert!(response.tool_trace[0].result.as_deref().unwrap().contains("Unknown tool: read_file"));
+    }
+    
+    fn looping_client(tool_calls: usize, final_text: Option<&str>) -> FakeLlmClient {
+        use crate::agent::llm::{LlmResponse, Usage};
+        use serde_json::json;
+        
+        let mut responses: Vec<LlmResponse> = (0..tool_calls)
+            .map(|i| LlmResponse {
+                content: Some(format!("step {}", i)),
+                tool_calls: vec![ToolCallRequest {
+                    id: format!("tc_{}", i),
+                    name: "read_file".to_string(),
+                    arguments: json!({"path": "x"}),
+                }],
+                finish_reason: "tool_calls".to_string(),
+                usage: Usage::default(),
+            })
+            .collect();
+        if let Some(text) = final_text {
+            responses.push(LlmResponse::text(text));
+        }
+        FakeLlmClient::from_responses(responses)
+    }
+    
+    #[tokio::test]
+    async fn test_max_iterations_returns_partial_answer() {
+        let agent = AgentLoop::new(looping_client(2, Some("Here is what I found")), 2);
+        let mut ctx = Context::test();
+        
+        let response = agent.run(&[], Message::user("Dig"), &mut ctx).await.unwrap();
+        assert!(response.content.starts_with("Here is what I found"));
+        assert!(response.content.contains("limit of 2 iterations"));
+        
+        // Final request fails: fall back to the last assistant text
+        let agent = AgentLoop::new(looping_client(2, None), 2);
+        let response = agent.run(&[], Message::user("Dig"), &mut ctx).await.unwrap();
+        assert!(response.content.starts_with("step 1"));
+    }
+    
+    #[tokio::test]
+    async fn test_scratchpad_shown_on_later_iterations() {
+        use crate::tools::ScratchpadTool;
+        use serde_json::json;
+        use std::sync::Mutex;
+        
+        /// Remembers the system prompt of every request
+        struct Recording {
+            inner: FakeLlmClient,
+            prompts: Mutex<Vec<String>>,
+        }
+        
+        #[async_trait::async_trait]
+        impl LlmClient for Recording {
+            async fn chat(&self, messages: &[Message], tools: &[ToolDefinition]) -> Result<LlmResponse> {
+                self.prompts.lock().unwrap().push(messages[0].content.clone());
+                self.inner.chat(messages, tools).await
+            }
+            fn default_model(&self) -> &str { "recording" }
+        }
+        
+        let client = Recording {
+            inner: FakeLlmClient::with_tool_call(
+                "scratchpad",
+                json!({"action": "write", "content": "1. Check the logs"}),
+                "done",
+            ),
+            prompts: Mutex::new(Vec::new()),
+        };
+        let mut ctx = Context::test();
+        ctx.tool_runner.register(ScratchpadTool::new(ctx.scratchpad.clone()));
+        ctx.scratchpad.clear();
+        let agent = AgentLoop::new(client, 10);
+        
+        agent.run(&[], Message::user("Investigate"), &mut ctx).await.unwrap();
+        let prompts = agent.client().prompts.lock().unwrap().clone();
+        assert!(!prompts[0].contains("# Scratchpad"));
+        assert!(prompts[1].contains("# Scratchpad (this request only)\n\n1. Check the logs"));
+        
+        // A new run starts with an empty scratchpad
+        assert_eq!(ctx.scratchpad.read(), "1. Check the logs");
+        let agent = AgentLoop::new(FakeLlmClient::new(vec!["hi"]), 10);
+        agent.run(&[], Message::user("Hi"), &mut ctx).await.unwrap();
+        assert_eq!(ctx.scratchpad.read(), "");
+    }
+    
+    #[tokio::test]
+    async fn test_run_streaming_forwards_text() {
+        use serde_json::json;
+        use std::sync::Mutex;
+        
+        let client = FakeLlmClient::with_tool_call("read_file", json!({"path": "a.txt"}), "All done");
+        let agent = AgentLoop::new(client, 10);
+        let mut ctx = Context::test();
+        
+        let streamed = Mutex::new(String::new());
+        let on_text = |text: &str| streamed.lock().unwrap().push_str(text);
+        let response = agent.run_streaming(&[], Message::user("Go"), &mut ctx, &on_text).await.unwrap();
+        
+        assert_eq!(response.content, "All done");
+        assert!(streamed.lock().unwrap().ends_with("All done"));
+    }
+    
+    #[tokio::test]
+    async fn test_attached_files_become_response_media() {
+        use crate::tools::AttachTool;
+        use serde_json::json;
+        
+        let tmp = tempfile::TempDir::new().unwrap();
+        let report = tmp.path().join("report.md");
+        std::fs::write(&report, "# Report").unwrap();
+        
+        let mut ctx = Context::test();
+        ctx.tool_runner.register(AttachTool::new(tmp.path().to_path_buf(), ctx.attachments.clone()));
+        let client = FakeLlmClient::with_tool_call("attach_file", json!({"path": "report.md"}), "Here it is");
+        let agent = AgentLoop::new(client, 10);
+        
+        let response = agent.run(&[], Message::user("Write a report"), &mut ctx).await.unwrap();
+        assert_eq!(response.content, "Here it is");
+        assert_eq!(response.media, vec![report.display().to_string()]);
+        
+        // Attachments belong to one reply only
+        let agent = AgentLoop::new(FakeLlmClient::new(vec!["hi"]), 10);
+        let response = agent.run(&[], Message::user("Hi"), &mut ctx).await.unwrap();
+        assert!(response.media.is_empty());
+    }
+    
+    #[tokio::test]
+    async fn test_done_tool_ends_run() {
+        use serde_json::json;
+        
+        let client = FakeLlmClient::with_tool_call("done", json!({"message": "Finished early"}), "unreachable");
+        let agent = AgentLoop::new(client, 10);
+        let mut ctx = Context::test();
+        
+        let response = agent.run(&[], Message::user("Wrap up"), &mut ctx).await.unwrap();
+        assert_eq!(response.content, "Finished early");
+        assert_eq!(response.tool_trace.len(), 1);
+        assert_eq!(response.tool_trace[0].name, STOP_TOOL_NAME);
+        
+        // Without a message there is nothing to answer with, so the run goes on
+        let client = FakeLlmClient::with_tool_call("done", json!({}), "Answered instead");
+        let agent = AgentLoop::new(client, 10);
+        let response = agent.run(&[], Message::user("Wrap up"), &mut ctx).await.unwrap();
+        assert_eq!(response.content, "Answered instead");
+    }
+    
+    #[tokio::test]
+    async fn test_max_iterations_error_when_configured() {
+        let agent = AgentLoop::new(looping_client(2, Some("unused")), 2);
+        let mut ctx = Context::test();
+        ctx.config.error_on_max_iterations = true;
+        
+        let result = agent.run(&[], Message::user("Dig"), &mut ctx).await;
+        assert!(matches!(result, Err(Error::MaxIterations)));
+    }
+    
+    #[tokio::test]
+    async fn test_per_tool_call_limit() {
+        use crate::tools::DummyTool;
+        
+        let agent = AgentLoop::new(looping_client(3, Some("done")), 10);
+        let mut ctx = Context::test();
+        ctx.tool_runner.register(DummyTool { name: "read_file".to_string(), result: "ok".to_string() });
+        ctx.config.tool_call_limits.insert("read_file".to_string(), 2);
+        
+        let response = agent.run(&[], Message::user("Dig"), &mut ctx).await.unwrap();
+        let results: Vec<&str> = response.tool_trace.iter()
+            .map(|c| c.result.as_deref().unwrap())
+            .collect();
+        assert_eq!(&results[..2], &["ok", "ok"]);
+        assert!(results[2].contains("which is its limit"));
     }
 }
@@ -90,7 +90,7 @@ pub struct ToolCallRequest {
 }
 
 /// A tool call with its result
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToolCall {
     pub id: String,
     pub name: String,
@@ -129,12 +129,15 @@ impl InboundMessage {
 }
 
 /// Response from the agent
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Response {
     pub content: String,
     pub channel: String,
     pub chat_id: String,
+    /// Files to deliver with the reply (queued by the `attach_file` tool)
     pub media: Vec<String>,
+    /// Tool calls made while producing this response, in order
+    pub tool_trace: Vec<ToolCall>,
 }
 
 impl Response {
@@ -144,8 +147,21 @@ impl Response {
             channel: "cli".to_string(),
             chat_id: "default".to_string(),
             media: vec![],
+            tool_trace: vec![],
         }
     }
+    
+    /// Attach the tool calls that produced this response
+    pub fn with_trace(mut self, tool_trace: Vec<ToolCall>) -> Self {
+        self.tool_trace = tool_trace;
+        self
+    }
+    
+    /// Attach files to deliver with this response
+    pub fn with_media(mut self, media: Vec<String>) -> Self {
+        self.media = media;
+        self
+    }
 }
 
 #[cfg(test)]
@@ -11,6 +11,8 @@
 //! See [`llm::ProviderRegistry`] for instructions.
 
 mod context;
+pub mod cost;
+mod limiter;
 mod loop_impl;
 mod message;
 pub mod tokens;
@@ -20,6 +22,7 @@ pub mod llm;
 
 // Re-exports for convenience
 pub use context::Context;
-pub use llm::{GeminiClient, GeminiOAuthClient, LlmClient, LlmResponse, ProviderRegistry, Usage};
+pub use limiter::{LimitedClient, RequestLimiter};
+pub use llm::{GeminiClient, GeminiOAuthClient, LlmClient, LlmResponse, MockClient, OnText, ProviderRegistry, Usage};
 pub use loop_impl::AgentLoop;
 pub use message::{InboundMessage, Message, Response, Role, ToolCall, ToolCallRequest};
@@ -6,26 +6,99 @@
 use std::path::{Path, PathBuf};
 use std::process::Command;
 use regex::Regex;
+use serde::{Deserialize, Serialize};
+use walkdir::{DirEntry, WalkDir};
 use crate::Result;
 use crate::error::Error;
 
+/// How deep below the install directory the oauth2.js search goes
+const MAX_SEARCH_DEPTH: usize = 14;
+
+/// Most directory entries the oauth2.js search looks at before giving up
+const MAX_SEARCH_ENTRIES: usize = 50_000;
+
 /// OAuth2 client credentials extracted from the CLI
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CliCredentials {
     pub client_id: String,
     pub client_secret: String,
 }
 
+/// Extracted credentials saved for later runs, tagged with the CLI they came from
+#[derive(Serialize, Deserialize)]
+struct CachedCliCredentials {
+    cli_version: String,
+    #[serde(flatten)]
+    credentials: CliCredentials,
+}
+
 /// Extract OAuth2 credentials from the Gemini CLI binary
 ///
 /// This function:
 /// 1. Searches $PATH for the `gemini` binary
-/// 2. Locates the oauth2.js file within the Node.js package
-/// 3. Extracts client_id and client_secret using regex
+/// 2. Returns the cached credentials if they came from this CLI install
+/// 3. Otherwise locates the oauth2.js file within the Node.js package
+/// 4. Extracts client_id and client_secret using regex, and caches them
 pub fn extract_cli_credentials() -> Result<CliCredentials> {
     let gemini_path = find_gemini_binary()?;
+    let cache_path = cli_credentials_path();
+    let version = cli_version(&gemini_path);
+    if let Some(cached) = version.as_deref().and_then(|v| read_cached_credentials(&cache_path, v)) {
+        tracing::debug!("Using cached CLI credentials from {:?}", cache_path);
+        return Ok(cached);
+    }
+    
     let oauth_file = find_oauth_file(&gemini_path)?;
-    extract_credentials_from_file(&oauth_file)
+    let credentials = extract_credentials_from_file(&oauth_file)?;
+    if let Some(version) = &version {
+        if let Err(e) = write_cached_credentials(&cache_path, version, &credentials) {
+            tracing::warn!("Failed to cache CLI credentials: {}", e);
+        }
+    }
+    Ok(credentials)
+}
+
+/// Where extracted CLI credentials are cached
+fn cli_credentials_path() -> PathBuf {
+    crate::config::config_dir().join("cli_credentials.json")
+}
+
+/// Identify the installed CLI by its resolved binary and modification time,
+/// both of which change when the CLI is upgraded or reinstalled
+fn cli_version(gemini_path: &Path) -> Option<String> {
+    let resolved = std::fs::canonicalize(gemini_path).ok()?;
+    let modified = std::fs::metadata(&resolved).ok()?
+        .modified().ok()?
+        .duration_since(std::time::UNIX_EPOCH).ok()?;
+    Some(format!("{}@{}", resolved.display(), modified.as_secs()))
+}
+
+/// The cached credentials, if there are any for `version`
+fn read_cached_credentials(path: &Path, version: &str) -> Option<CliCredentials> {
+    let content = std::fs::read_to_string(path).ok()?;
+    let cached: CachedCliCredentials = serde_json::from_str(&content).ok()?;
+    (cached.cli_version == version).then_some(cached.credentials)
+}
+
+/// Save credentials for `version` with owner-only permissions
+fn write_cached_credentials(path: &Path, version: &str, credentials: &CliCredentials) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let cached = CachedCliCredentials {
+        cli_version: version.to_string(),
+        credentials: credentials.clone(),
+    };
+    std::fs::write(path, serde_json::to_string_pretty(&cached)?)?;
+    
+    // Set restrictive permissions on Unix
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+    }
+    
+    Ok(())
 }
 
 /// Find the gemini binary in the system PATH
@@ -130,7 +203,7 @@ fn find_oauth_file(gemini_path: &Path) -> Result<PathBuf> {
         }
     }
     
-    // Fallback: Use find command to search the resolved path's ancestors
+    // Fallback: search the installation directory around the resolved path
     if let Ok(oauth_path) = find_oauth_in_dir(&resolved) {
         return Ok(oauth_path);
     }
@@ -157,28 +230,44 @@ fn find_oauth_in_dir(start_path: &Path) -> Result<PathBuf> {
         }
     }
     
-    // Use find command to search for oauth2.js specifically in code_assist
-    let output = Command::new("find")
-        .args([
-            search_dir.to_string_lossy().as_ref(),
-            "-path", "*/code_assist/oauth2.js",
-            "-type", "f",
-        ])
-        .output()
-        .map_err(|e| Error::Auth(format!("Failed to search for oauth files: {}", e)))?;
-    
-    if output.status.success() {
-        let paths = String::from_utf8_lossy(&output.stdout);
-        for line in paths.lines() {
-            let path = PathBuf::from(line.trim());
-            if path.exists() {
-                tracing::debug!("Found oauth2.js via find: {:?}", path);
-                return Ok(path);
-            }
-        }
+    search_oauth_file(&search_dir)
+        .ok_or_else(|| Error::Auth("OAuth file not found".to_string()))
+}
+
+/// Look for `*/code_assist/oauth2.js` under `dir`, bounded in depth and entries
+///
+/// Inside `node_modules` only the `@google/gemini-cli*` packages are searched,
+/// so a large dependency tree isn't walked.
+fn search_oauth_file(dir: &Path) -> Option<PathBuf> {
+    WalkDir::new(dir)
+        .max_depth(MAX_SEARCH_DEPTH)
+        .into_iter()
+        .filter_entry(is_search_candidate)
+        .take(MAX_SEARCH_ENTRIES)
+        .filter_map(|e| e.ok())
+        .find(|e| {
+            e.file_type().is_file()
+                && e.file_name() == "oauth2.js"
+                && e.path().parent().and_then(|p| p.file_name()).is_some_and(|n| n == "code_assist")
+        })
+        .map(|e| {
+            tracing::debug!("Found oauth2.js by search: {:?}", e.path());
+            e.into_path()
+        })
+}
+
+/// Whether the search should visit `entry`, pruning unrelated packages
+fn is_search_candidate(entry: &DirEntry) -> bool {
+    if entry.depth() == 0 || !entry.file_type().is_dir() {
+        return true;
+    }
+    let name = entry.file_name().to_string_lossy();
+    let parent = entry.path().parent().and_then(|p| p.file_name()).map(|n| n.to_string_lossy());
+    match parent.as_deref() {
+        Some("node_modules") => name == "@google",
+        Some("@google") => name.starts_with("gemini-cli"),
+        _ => true,
     }
-    
-    Err(Error::Auth("OAuth file not found".to_string()))
 }
 
 /// Extract credentials from the oauth2.js file content
@@ -278,6 +367,65 @@ mod tests {
         assert!(!creds.client_secret.is_empty());
     }
     
+    #[test]
+    fn test_search_oauth_file_skips_other_packages() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let write = |rel: &str| {
+            let path = tmp.path().join(rel);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, "").unwrap();
+        };
+        // A decoy in an unrelated package is never visited
+        write("lib/node_modules/aaa-other/dist/code_assist/oauth2.js");
+        write("lib/node_modules/@google/genai/dist/code_assist/oauth2.js");
+        write("lib/node_modules/@google/gemini-cli/node_modules/@google/gemini-cli-core/dist/src/code_assist/oauth2.js");
+        
+        let found = search_oauth_file(tmp.path()).unwrap();
+        assert!(found.ends_with("@google/gemini-cli-core/dist/src/code_assist/oauth2.js"));
+        
+        let deep = tempfile::TempDir::new().unwrap();
+        let path = (0..MAX_SEARCH_DEPTH).fold(deep.path().to_path_buf(), |p, i| p.join(format!("d{}", i)));
+        std::fs::create_dir_all(path.join("code_assist")).unwrap();
+        std::fs::write(path.join("code_assist/oauth2.js"), "").unwrap();
+        assert_eq!(search_oauth_file(deep.path()), None);
+    }
+    
+    #[test]
+    fn test_cached_credentials() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = tmp.path().join("cli_credentials.json");
+        assert!(read_cached_credentials(&path, "v1").is_none());
+        
+        let creds = CliCredentials {
+            client_id: "id.apps.googleusercontent.com".to_string(),
+            client_secret: "GOCSPX-secret".to_string(),
+        };
+        write_cached_credentials(&path, "v1", &creds).unwrap();
+        let cached = read_cached_credentials(&path, "v1").unwrap();
+        assert_eq!(cached.client_id, creds.client_id);
+        assert_eq!(cached.client_secret, creds.client_secret);
+        
+        // A different CLI install means extracting again
+        assert!(read_cached_credentials(&path, "v2").is_none());
+        
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+    }
+    
+    #[test]
+    fn test_cli_version_tracks_binary() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let bin = tmp.path().join("gemini");
+        std::fs::write(&bin, "#!/usr/bin/env node").unwrap();
+        let version = cli_version(&bin).unwrap();
+        assert!(version.starts_with(&bin.canonicalize().unwrap().display().to_string()));
+        assert!(cli_version(&tmp.path().join("missing")).is_none());
+    }
+    
     #[test]
     fn test_missing_credentials() {
         let content = "const foo = 'bar';";
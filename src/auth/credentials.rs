@@ -1,12 +1,20 @@
 //! Credential storage and management
 //!
-//! Handles saving and loading OAuth2 tokens from ~/.leo/credentials.json
+//! Handles saving and loading OAuth2 tokens from ~/.leo/credentials.json,
+//! keyed by provider name (e.g. "google", "slack").
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use crate::Result;
 
+/// Provider key for the Google OAuth token used by Gemini
+pub const GOOGLE_PROVIDER: &str = "google";
+
+/// All stored credentials, keyed by provider
+pub type CredentialStore = HashMap<String, Credentials>;
+
 /// OAuth2 credentials with access and refresh tokens
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Credentials {
@@ -28,6 +36,10 @@ pub struct Credentials {
     /// Scopes granted
     #[serde(skip_serializing_if = "Option::is_none")]
     pub scope: Option<String>,
+    
+    /// Email address of the signed-in Google account
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
 }
 
 fn default_token_type() -> String {
@@ -51,6 +63,7 @@ impl Credentials {
             token_type: default_token_type(),
             expires_at,
             scope: None,
+            email: None,
         }
     }
     
@@ -71,6 +84,50 @@ impl Credentials {
     pub fn can_refresh(&self) -> bool {
         self.refresh_token.is_some()
     }
+    
+    /// Required scopes that were not granted to these credentials
+    ///
+    /// Credentials saved before scopes were recorded have no `scope`; nothing is
+    /// known to be granted, so every required scope counts as missing.
+    pub fn missing_scopes<'a>(&self, required: &[&'a str]) -> Vec<&'a str> {
+        let Some(granted) = &self.scope else {
+            return required.to_vec();
+        };
+        let granted: Vec<&str> = granted.split_whitespace().collect();
+        required.iter()
+            .copied()
+            .filter(|scope| !granted.contains(scope))
+            .collect()
+    }
+    
+    /// Describe the token expiry relative to now, e.g. "expires in 42 minutes"
+    pub fn expiry_description(&self) -> String {
+        let Some(expires) = self.expires_at else {
+            return "does not expire".to_string();
+        };
+        
+        let delta = expires - Utc::now();
+        if delta.num_seconds() >= 0 {
+            format!("expires in {}", format_span(delta))
+        } else {
+            format!("expired {} ago", format_span(-delta))
+        }
+    }
+}
+
+/// Format a duration using its largest whole unit
+fn format_span(span: chrono::Duration) -> String {
+    let (count, unit) = if span.num_days() > 0 {
+        (span.num_days(), "day")
+    } else if span.num_hours() > 0 {
+        (span.num_hours(), "hour")
+    } else if span.num_minutes() > 0 {
+        (span.num_minutes(), "minute")
+    } else {
+        (span.num_seconds(), "second")
+    };
+    let plural = if count == 1 { "" } else { "s" };
+    format!("{} {}{}", count, unit, plural)
 }
 
 /// Get the credentials file path
@@ -78,51 +135,76 @@ pub fn credentials_path() -> PathBuf {
     crate::config::config_dir().join("credentials.json")
 }
 
-/// Load credentials from file
-pub fn load_credentials() -> Result<Option<Credentials>> {
+/// Load credentials for `provider`
+pub fn load_credentials(provider: &str) -> Result<Option<Credentials>> {
+    Ok(load_store(&credentials_path())?.remove(provider))
+}
+
+/// Save credentials for `provider`, keeping other providers' entries
+pub fn save_credentials(provider: &str, credentials: &Credentials) -> Result<()> {
     let path = credentials_path();
+    let mut store = load_store(&path)?;
+    store.insert(provider.to_string(), credentials.clone());
+    save_store(&path, &store)
+}
+
+/// Delete stored credentials for `provider`
+pub fn delete_credentials(provider: &str) -> Result<()> {
+    let path = credentials_path();
+    let mut store = load_store(&path)?;
+    if store.remove(provider).is_none() {
+        return Ok(());
+    }
     
+    if store.is_empty() {
+        std::fs::remove_file(&path)?;
+        Ok(())
+    } else {
+        save_store(&path, &store)
+    }
+}
+
+/// Read the credential store, migrating a legacy single-token file to the keyed format
+fn load_store(path: &Path) -> Result<CredentialStore> {
     if !path.exists() {
-        return Ok(None);
+        return Ok(CredentialStore::new());
     }
     
-    let content = std::fs::read_to_string(&path)?;
-    let creds: Credentials = serde_json::from_str(&content)?;
-    Ok(Some(creds))
+    let content = std::fs::read_to_string(path)?;
+    let value: serde_json::Value = serde_json::from_str(&content)?;
+    
+    // Before multi-provider support the file held one Google token at the top level
+    if value.get("access_token").is_some() {
+        let legacy: Credentials = serde_json::from_value(value)?;
+        let store = CredentialStore::from([(GOOGLE_PROVIDER.to_string(), legacy)]);
+        save_store(path, &store)?;
+        return Ok(store);
+    }
+    
+    Ok(serde_json::from_value(value)?)
 }
 
-/// Save credentials to file
-pub fn save_credentials(credentials: &Credentials) -> Result<()> {
-    let path = credentials_path();
-    
+/// Write the credential store with owner-only permissions
+fn save_store(path: &Path, store: &CredentialStore) -> Result<()> {
     // Create parent directory
     if let Some(parent) = path.parent() {
         std::fs::create_dir_all(parent)?;
     }
     
-    let content = serde_json::to_string_pretty(credentials)?;
-    std::fs::write(&path, content)?;
+    let content = serde_json::to_string_pretty(store)?;
+    std::fs::write(path, content)?;
     
     // Set restrictive permissions on Unix
     #[cfg(unix)]
     {
         use std::os::unix::fs::PermissionsExt;
         let perms = std::fs::Permissions::from_mode(0o600);
-        std::fs::set_permissions(&path, perms)?;
+        std::fs::set_permissions(path, perms)?;
     }
     
     Ok(())
 }
 
-/// Delete stored credentials
-pub fn delete_credentials() -> Result<()> {
-    let path = credentials_path();
-    if path.exists() {
-        std::fs::remove_file(&path)?;
-    }
-    Ok(())
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -137,6 +219,24 @@ mod tests {
         assert!(!creds.is_expired());
     }
     
+    #[test]
+    fn test_missing_scopes() {
+        let mut creds = Credentials::new("test_token".to_string(), None, None);
+        let required = ["openid", "https://www.googleapis.com/auth/gmail.readonly"];
+        
+        // Unknown grants cover nothing
+        assert_eq!(creds.missing_scopes(&required), required.to_vec());
+        
+        creds.scope = Some("openid https://www.googleapis.com/auth/cloud-platform".to_string());
+        assert_eq!(
+            creds.missing_scopes(&required),
+            vec!["https://www.googleapis.com/auth/gmail.readonly"]
+        );
+        
+        creds.scope = Some("https://www.googleapis.com/auth/gmail.readonly openid".to_string());
+        assert!(creds.missing_scopes(&required).is_empty());
+    }
+    
     #[test]
     fn test_credentials_expired() {
         let mut creds = Credentials::new(
@@ -167,6 +267,7 @@ mod tests {
             token_type: "Bearer".to_string(),
             expires_at: None,
             scope: None,
+            email: None,
         };
         assert!(!creds.is_expired());
     }
@@ -184,5 +285,60 @@ mod tests {
         
         assert_eq!(parsed.access_token, creds.access_token);
         assert_eq!(parsed.refresh_token, creds.refresh_token);
+        assert!(!json.contains("email"));
+    }
+    
+    #[test]
+    fn test_expiry_description() {
+        let mut creds = Credentials::new("a".to_string(), None, None);
+        assert_eq!(creds.expiry_description(), "does not expire");
+        
+        creds.expires_at = Some(Utc::now() + chrono::Duration::seconds(42 * 60 + 30));
+        assert_eq!(creds.expiry_description(), "expires in 42 minutes");
+        
+        creds.expires_at = Some(Utc::now() - chrono::Duration::seconds(3600 + 30));
+        assert_eq!(creds.expiry_description(), "expired 1 hour ago");
+    }
+    
+    #[test]
+    fn test_store_migrates_legacy_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("credentials.json");
+        std::fs::write(&path, r#"{"access_token":"old","refresh_token":"r","token_type":"Bearer"}"#).unwrap();
+        
+        let store = load_store(&path).unwrap();
+        assert_eq!(store[GOOGLE_PROVIDER].access_token, "old");
+        
+        // The file is rewritten in the keyed format
+        let rewritten: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(rewritten["google"]["refresh_token"], "r");
+    }
+    
+    #[test]
+    fn test_store_keeps_providers_separate() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("credentials.json");
+        assert!(load_store(&path).unwrap().is_empty());
+        
+        let mut store = CredentialStore::new();
+        store.insert("google".to_string(), Credentials::new("g".to_string(), None, None));
+        store.insert("slack".to_string(), Credentials::new("s".to_string(), None, None));
+        save_store(&path, &store).unwrap();
+        
+        let loaded = load_store(&path).unwrap();
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded["slack"].access_token, "s");
+    }
+    
+    #[test]
+    fn test_credentials_email_roundtrip() {
+        let old = r#"{"access_token":"a","token_type":"Bearer"}"#;
+        let mut creds: Credentials = serde_json::from_str(old).unwrap();
+        assert!(creds.email.is_none());
+        
+        creds.email = Some("leo@example.com".to_string());
+        let json = serde_json::to_string(&creds).unwrap();
+        let parsed: Credentials = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.email.as_deref(), Some("leo@example.com"));
     }
 }
@@ -13,6 +13,8 @@ mod cli_extractor;
 mod callback_server;
 mod provider;
 
-pub use credentials::{Credentials, load_credentials, save_credentials, delete_credentials};
+pub use credentials::{
+    Credentials, CredentialStore, GOOGLE_PROVIDER, load_credentials, save_credentials, delete_credentials,
+};
 pub use cli_extractor::{extract_cli_credentials, CliCredentials};
 pub use provider::GeminiAuthProvider;
@@ -5,25 +5,41 @@
 use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
 use rand::Rng;
 use sha2::{Digest, Sha256};
+use crate::Result;
+use crate::error::Error;
 
-/// Length of the code verifier (must be 43-128 characters)
+/// Default length of the code verifier
 const CODE_VERIFIER_LENGTH: usize = 64;
 
+/// Shortest code verifier allowed by RFC 7636
+const MIN_VERIFIER_LENGTH: usize = 43;
+
+/// Longest code verifier allowed by RFC 7636
+const MAX_VERIFIER_LENGTH: usize = 128;
+
 /// Characters allowed in code verifier (unreserved URI characters per RFC 7636)
 const VERIFIER_CHARSET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-._~";
 
 /// Generate a cryptographically random code verifier
 ///
-/// The code verifier is a high-entropy random string between 43-128 characters
-/// using unreserved URI characters as defined in RFC 7636.
-pub fn generate_code_verifier() -> String {
+/// The code verifier is a high-entropy random string of `length` characters
+/// using unreserved URI characters as defined in RFC 7636, which requires
+/// 43-128 characters; other lengths are rejected.
+pub fn generate_code_verifier(length: usize) -> Result<String> {
+    if !(MIN_VERIFIER_LENGTH..=MAX_VERIFIER_LENGTH).contains(&length) {
+        return Err(Error::OAuth(format!(
+            "PKCE code verifier length must be {}-{} characters, got {}",
+            MIN_VERIFIER_LENGTH, MAX_VERIFIER_LENGTH, length
+        )));
+    }
+
     let mut rng = rand::thread_rng();
-    (0..CODE_VERIFIER_LENGTH)
+    Ok((0..length)
         .map(|_| {
             let idx = rng.gen_range(0..VERIFIER_CHARSET.len());
             VERIFIER_CHARSET[idx] as char
         })
-        .collect()
+        .collect())
 }
 
 /// Generate a code challenge from the code verifier
@@ -46,9 +62,14 @@ pub struct PkcePair {
 impl PkcePair {
     /// Generate a new PKCE pair
     pub fn new() -> Self {
-        let verifier = generate_code_verifier();
+        Self::with_length(CODE_VERIFIER_LENGTH).expect("default verifier length is within RFC 7636 bounds")
+    }
+
+    /// Generate a PKCE pair with a verifier of `length` characters (43-128)
+    pub fn with_length(length: usize) -> Result<Self> {
+        let verifier = generate_code_verifier(length)?;
         let challenge = generate_code_challenge(&verifier);
-        Self { verifier, challenge }
+        Ok(Self { verifier, challenge })
     }
 }
 
@@ -64,14 +85,23 @@ mod tests {
 
     #[test]
     fn test_code_verifier_length() {
-        let verifier = generate_code_verifier();
+        let verifier = generate_code_verifier(CODE_VERIFIER_LENGTH).unwrap();
         assert!(verifier.len() >= 43 && verifier.len() <= 128);
         assert_eq!(verifier.len(), CODE_VERIFIER_LENGTH);
     }
 
+    #[test]
+    fn test_verifier_length_bounds() {
+        assert_eq!(PkcePair::with_length(43).unwrap().verifier.len(), 43);
+        assert_eq!(PkcePair::with_length(128).unwrap().verifier.len(), 128);
+        assert!(PkcePair::with_length(42).is_err());
+        assert!(PkcePair::with_length(129).is_err());
+        assert!(generate_code_verifier(0).is_err());
+    }
+
     #[test]
     fn test_code_verifier_characters() {
-        let verifier = generate_code_verifier();
+        let verifier = generate_code_verifier(CODE_VERIFIER_LENGTH).unwrap();
         let charset = std::str::from_utf8(VERIFIER_CHARSET).unwrap();
         for c in verifier.chars() {
             assert!(charset.contains(c), "Invalid character in verifier: {}", c);
@@ -80,7 +110,7 @@ mod tests {
 
     #[test]
     fn test_code_challenge_format() {
-        let verifier = generate_code_verifier();
+        let verifier = generate_code_verifier(CODE_VERIFIER_LENGTH).unwrap();
         let challenge = generate_code_challenge(&verifier);
         
         // SHA256 produces 32 bytes, Base64URL encoding produces 43 characters (no padding)
@@ -108,8 +138,8 @@ mod tests {
 
     #[test]
     fn test_verifiers_are_unique() {
-        let v1 = generate_code_verifier();
-        let v2 = generate_code_verifier();
+        let v1 = generate_code_verifier(CODE_VERIFIER_LENGTH).unwrap();
+        let v2 = generate_code_verifier(CODE_VERIFIER_LENGTH).unwrap();
         assert_ne!(v1, v2, "Verifiers should be unique");
     }
 }
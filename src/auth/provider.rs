@@ -11,13 +11,14 @@ use url::Url;
 use crate::Result;
 use crate::error::Error;
 use super::pkce::PkcePair;
-use super::credentials::{Credentials, load_credentials, save_credentials};
+use super::credentials::{Credentials, GOOGLE_PROVIDER, load_credentials, save_credentials};
 use super::callback_server::{wait_for_callback, get_redirect_uri};
 use super::cli_extractor::{extract_cli_credentials, CliCredentials};
 
 /// Google OAuth2 endpoints
 const GOOGLE_AUTH_URL: &str = "https://accounts.google.com/o/oauth2/v2/auth";
 const GOOGLE_TOKEN_URL: &str = "https://oauth2.googleapis.com/token";
+const GOOGLE_USERINFO_URL: &str = "https://www.googleapis.com/oauth2/v3/userinfo";
 
 /// OAuth2 scopes required for Gemini API
 /// These must match the scopes registered for the Gemini CLI's client ID
@@ -27,6 +28,9 @@ const GEMINI_SCOPES: &[&str] = &[
     "https://www.googleapis.com/auth/userinfo.profile",
 ];
 
+/// Scope for reading and creating Google Calendar events (opt-in via config)
+const CALENDAR_SCOPE: &str = "https://www.googleapis.com/auth/calendar.events";
+
 /// Google OAuth2 token response
 #[derive(Debug, Deserialize)]
 struct TokenResponse {
@@ -37,10 +41,31 @@ struct TokenResponse {
     #[allow(dead_code)]
     token_type: String,
     #[serde(default)]
-    #[allow(dead_code)]
     scope: Option<String>,
 }
 
+impl TokenResponse {
+    /// Build credentials from this response, carrying over what it omits from `previous`
+    ///
+    /// Google only sometimes rotates the refresh token and may leave out the scope
+    /// on refresh, so the previous values are kept unless new ones are returned.
+    fn into_credentials(self, previous: Option<&Credentials>) -> Credentials {
+        let refresh = self.refresh_token
+            .or_else(|| previous.and_then(|p| p.refresh_token.clone()));
+        let mut creds = Credentials::new(self.access_token, refresh, self.expires_in);
+        creds.scope = self.scope.or_else(|| previous.and_then(|p| p.scope.clone()));
+        creds.email = previous.and_then(|p| p.email.clone());
+        creds
+    }
+}
+
+/// Google userinfo response (only the fields we use)
+#[derive(Debug, Deserialize)]
+struct UserInfo {
+    #[serde(default)]
+    email: Option<String>,
+}
+
 /// Token refresh request
 #[derive(Debug, Serialize)]
 struct RefreshRequest<'a> {
@@ -68,6 +93,7 @@ struct TokenExchangeRequest<'a> {
 pub struct GeminiAuthProvider {
     client_credentials: CliCredentials,
     http_client: Client,
+    calendar: bool,
 }
 
 impl GeminiAuthProvider {
@@ -77,6 +103,7 @@ impl GeminiAuthProvider {
         Ok(Self {
             client_credentials,
             http_client: Client::new(),
+            calendar: false,
         })
     }
     
@@ -88,13 +115,38 @@ impl GeminiAuthProvider {
                 client_secret,
             },
             http_client: Client::new(),
+            calendar: false,
+        }
+    }
+    
+    /// Also request (and require) the Google Calendar scope
+    pub fn with_calendar(mut self, enabled: bool) -> Self {
+        self.calendar = enabled;
+        self
+    }
+    
+    /// Scopes this provider asks for and expects stored credentials to have
+    pub fn scopes(&self) -> Vec<&'static str> {
+        let mut scopes = GEMINI_SCOPES.to_vec();
+        if self.calendar {
+            scopes.push(CALENDAR_SCOPE);
         }
+        scopes
     }
     
     /// Get a valid access token, refreshing or re-authenticating as needed
     pub async fn get_valid_token(&self) -> Result<String> {
         // Try to load existing credentials
-        if let Some(creds) = load_credentials()? {
+        if let Some(creds) = load_credentials(GOOGLE_PROVIDER)? {
+            let missing = creds.missing_scopes(&self.scopes());
+            if !missing.is_empty() {
+                tracing::info!("Stored credentials lack scopes {:?}, asking for consent again", missing);
+                println!("\n🔐 Leo needs additional Google permissions; please sign in again.");
+                let creds = self.authorize().await?;
+                save_credentials(GOOGLE_PROVIDER, &creds)?;
+                return Ok(creds.access_token);
+            }
+            
             if !creds.is_expired() {
                 tracing::debug!("Using cached access token");
                 return Ok(creds.access_token);
@@ -103,9 +155,9 @@ impl GeminiAuthProvider {
             // Try to refresh
             if creds.can_refresh() {
                 tracing::info!("Access token expired, refreshing...");
-                match self.refresh_token(creds.refresh_token.as_ref().unwrap()).await {
+                match self.refresh_token(&creds).await {
                     Ok(new_creds) => {
-                        save_credentials(&new_creds)?;
+                        save_credentials(GOOGLE_PROVIDER, &new_creds)?;
                         return Ok(new_creds.access_token);
                     }
                     Err(e) => {
@@ -118,7 +170,7 @@ impl GeminiAuthProvider {
         // Need to authenticate
         tracing::info!("No valid token found, starting OAuth2 flow");
         let creds = self.authorize().await?;
-        save_credentials(&creds)?;
+        save_credentials(GOOGLE_PROVIDER, &creds)?;
         Ok(creds.access_token)
     }
     
@@ -148,7 +200,33 @@ impl GeminiAuthProvider {
         println!("✓ Authorization received, exchanging token...\n");
         
         // Exchange code for tokens
-        self.exchange_code(&auth_result.code, &pkce.verifier).await
+        let mut creds = self.exchange_code(&auth_result.code, &pkce.verifier).await?;
+        
+        // Remember which account signed in; not fatal if the lookup fails
+        match self.fetch_user_email(&creds.access_token).await {
+            Ok(email) => creds.email = Some(email),
+            Err(e) => tracing::warn!("Failed to fetch account email: {}", e),
+        }
+        
+        Ok(creds)
+    }
+    
+    /// Look up the email address of the account that owns an access token
+    pub async fn fetch_user_email(&self, access_token: &str) -> Result<String> {
+        let response = self.http_client
+            .get(GOOGLE_USERINFO_URL)
+            .bearer_auth(access_token)
+            .send()
+            .await?;
+        
+        if !response.status().is_success() {
+            let error_text = response.text().await?;
+            return Err(Error::OAuth(format!("Userinfo request failed: {}", error_text)));
+        }
+        
+        let info: UserInfo = response.json().await?;
+        info.email
+            .ok_or_else(|| Error::OAuth("Userinfo response has no email".to_string()))
     }
     
     /// Build the authorization URL
@@ -160,7 +238,7 @@ impl GeminiAuthProvider {
             .append_pair("client_id", &self.client_credentials.client_id)
             .append_pair("redirect_uri", &get_redirect_uri())
             .append_pair("response_type", "code")
-            .append_pair("scope", &GEMINI_SCOPES.join(" "))
+            .append_pair("scope", &self.scopes().join(" "))
             .append_pair("code_challenge", code_challenge)
             .append_pair("code_challenge_method", "S256")
             .append_pair("state", state)
@@ -193,16 +271,19 @@ impl GeminiAuthProvider {
         }
         
         let token_response: TokenResponse = response.json().await?;
-        
-        Ok(Credentials::new(
-            token_response.access_token,
-            token_response.refresh_token,
-            token_response.expires_in,
-        ))
+        let mut creds = token_response.into_credentials(None);
+        // An omitted scope means everything requested was granted (RFC 6749 5.1);
+        // recording it keeps the next `get_valid_token` from asking again
+        if creds.scope.is_none() {
+            creds.scope = Some(self.scopes().join(" "));
+        }
+        Ok(creds)
     }
     
     /// Refresh an expired access token
-    async fn refresh_token(&self, refresh_token: &str) -> Result<Credentials> {
+    async fn refresh_token(&self, current: &Credentials) -> Result<Credentials> {
+        let refresh_token = current.refresh_token.as_deref()
+            .ok_or_else(|| Error::OAuth("No refresh token stored".to_string()))?;
         let request = RefreshRequest {
             client_id: &self.client_credentials.client_id,
             client_secret: &self.client_credentials.client_secret,
@@ -222,21 +303,12 @@ impl GeminiAuthProvider {
         }
         
         let token_response: TokenResponse = response.json().await?;
-        
-        // Preserve the refresh token if not returned in response
-        let refresh = token_response.refresh_token
-            .or_else(|| Some(refresh_token.to_string()));
-        
-        Ok(Credentials::new(
-            token_response.access_token,
-            refresh,
-            token_response.expires_in,
-        ))
+        Ok(token_response.into_credentials(Some(current)))
     }
     
     /// Check if we have valid cached credentials
     pub fn has_valid_credentials() -> Result<bool> {
-        match load_credentials()? {
+        match load_credentials(GOOGLE_PROVIDER)? {
             Some(creds) => Ok(!creds.is_expired() || creds.can_refresh()),
             None => Ok(false),
         }
@@ -274,6 +346,54 @@ mod tests {
         }
     }
     
+    fn token_response(json: serde_json::Value) -> TokenResponse {
+        serde_json::from_value(json).unwrap()
+    }
+    
+    #[test]
+    fn test_refresh_keeps_refresh_token_when_not_rotated() {
+        let mut previous = Credentials::new("old".to_string(), Some("refresh-1".to_string()), Some(10));
+        previous.scope = Some("openid".to_string());
+        previous.email = Some("leo@example.com".to_string());
+        
+        let creds = token_response(serde_json::json!({
+            "access_token": "new", "expires_in": 3600, "token_type": "Bearer"
+        })).into_credentials(Some(&previous));
+        
+        assert_eq!(creds.access_token, "new");
+        assert_eq!(creds.refresh_token.as_deref(), Some("refresh-1"));
+        assert_eq!(creds.scope.as_deref(), Some("openid"));
+        assert_eq!(creds.email.as_deref(), Some("leo@example.com"));
+    }
+    
+    #[test]
+    fn test_refresh_saves_rotated_refresh_token_and_scope() {
+        let previous = Credentials::new("old".to_string(), Some("refresh-1".to_string()), Some(10));
+        
+        let creds = token_response(serde_json::json!({
+            "access_token": "new",
+            "refresh_token": "refresh-2",
+            "expires_in": 3600,
+            "token_type": "Bearer",
+            "scope": "openid https://www.googleapis.com/auth/cloud-platform"
+        })).into_credentials(Some(&previous));
+        
+        assert_eq!(creds.refresh_token.as_deref(), Some("refresh-2"));
+        assert_eq!(creds.scope.as_deref(), Some("openid https://www.googleapis.com/auth/cloud-platform"));
+    }
+    
+    #[test]
+    fn test_calendar_needs_consent_for_unscoped_credentials() {
+        let provider = GeminiAuthProvider::new("id".to_string(), "secret".to_string()).with_calendar(true);
+        let mut creds = Credentials::new("token".to_string(), Some("refresh".to_string()), Some(3600));
+        
+        // Credentials saved before scopes were recorded must go through consent again
+        assert!(creds.missing_scopes(&provider.scopes()).contains(&CALENDAR_SCOPE));
+        
+        creds.scope = Some(provider.scopes().join(" "));
+        assert!(creds.missing_scopes(&provider.scopes()).is_empty());
+    }
+    
     #[test]
     fn test_states_are_unique() {
         let s1 = generate_state();
@@ -1,7 +1,8 @@
 //! Configuration management
 
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use crate::Result;
 use crate::error::Error;
 
@@ -28,6 +29,14 @@ pub struct Config {
     #[serde(default = "default_max_iterations")]
     pub max_iterations: usize,
     
+    /// Most history messages sent with each request; older ones are summarized
+    #[serde(default = "default_history_window")]
+    pub history_window: usize,
+    
+    /// Longest reply the model may write, in tokens (default: chosen per model)
+    #[serde(default)]
+    pub max_output_tokens: Option<u32>,
+    
     /// OAuth configuration (used when provider is "google-cli")
     #[serde(default)]
     pub oauth: Option<OAuthConfig>,
@@ -35,6 +44,107 @@ pub struct Config {
     /// Telegram configuration
     #[serde(default)]
     pub telegram: TelegramConfig,
+    
+    /// Append each interactive exchange to today's daily notes
+    #[serde(default)]
+    pub auto_log: bool,
+    
+    /// Fail with an error at the iteration cap instead of returning a partial answer
+    #[serde(default)]
+    pub error_on_max_iterations: bool,
+    
+    /// Maximum calls to any single tool within one run (0 = unlimited)
+    #[serde(default = "default_max_calls_per_tool")]
+    pub max_calls_per_tool: usize,
+    
+    /// Per-tool overrides of `max_calls_per_tool`, e.g. {"exec": 5}
+    #[serde(default)]
+    pub tool_call_limits: HashMap<String, usize>,
+    
+    /// Maximum LLM requests in flight at once across all chats (0 = unlimited)
+    #[serde(default = "default_max_concurrent_requests")]
+    pub max_concurrent_requests: usize,
+    
+    /// Maximum LLM requests started per minute (0 = unlimited)
+    #[serde(default)]
+    pub requests_per_minute: u32,
+    
+    /// How long `web_fetch` reuses a fetched page, in seconds (0 = no caching)
+    #[serde(default = "default_web_cache_ttl_secs")]
+    pub web_cache_ttl_secs: u64,
+    
+    /// Also keep fetched pages in ~/.leo/cache/web so they survive restarts
+    #[serde(default)]
+    pub web_cache_on_disk: bool,
+    
+    /// How long repeated read-only tool calls (read_file, search, ...) reuse
+    /// their result, in seconds (0 = no caching)
+    #[serde(default)]
+    pub tool_cache_ttl_secs: u64,
+    
+    /// Workspace files injected into every prompt, in order (missing files are skipped)
+    #[serde(default = "default_bootstrap_files")]
+    pub bootstrap_files: Vec<String>,
+    
+    /// Text placed at the very start of the system prompt
+    #[serde(default)]
+    pub system_prompt_prefix: Option<String>,
+    
+    /// File whose contents replace Leo's built-in identity in the system prompt
+    /// (relative paths are resolved against the workspace)
+    #[serde(default)]
+    pub system_prompt_path: Option<PathBuf>,
+    
+    /// Describe mutating tool calls (writes, commands, commits) instead of running them
+    #[serde(default)]
+    pub dry_run: bool,
+    
+    /// Leave skills whose required tools aren't registered out of the prompt
+    /// (by default they are listed but marked disabled)
+    #[serde(default)]
+    pub hide_unavailable_skills: bool,
+    
+    /// Refuse `chmod` on paths outside the workspace (other file tools are not affected)
+    #[serde(default)]
+    pub restrict_chmod_to_workspace: bool,
+    
+    /// Enable the `calendar` tool; adds the Google Calendar scope to `leo login`
+    #[serde(default)]
+    pub google_calendar: bool,
+    
+    /// Speech-to-text backend for voice messages
+    #[serde(default)]
+    pub transcription: TranscriptionConfig,
+    
+    /// Where browser screenshots and moments are saved (default: `<workspace>/moments`)
+    #[serde(default)]
+    pub moments_dir: Option<PathBuf>,
+    
+    /// Scripted replies for the `mock` provider (see `agent::llm::mock`)
+    #[serde(default)]
+    pub mock_script: Option<PathBuf>,
+    
+    /// Search API keys for `web_search` (the browser is used without one)
+    #[serde(default)]
+    pub web_search: SearchConfig,
+    
+    /// Engine the browser's `search` action opens: "google", "bing", "duckduckgo",
+    /// or a URL template containing `{query}`
+    #[serde(default = "default_search_engine")]
+    pub search_engine: String,
+}
+
+impl Config {
+    /// Directory browser captures are saved in and the `moments` tool reads
+    pub fn moments_path(&self) -> PathBuf {
+        self.moments_dir.clone().unwrap_or_else(|| self.workspace.join("moments"))
+    }
+    
+    /// Call budget for `tool` within a single run (`None` = unlimited)
+    pub fn tool_call_limit(&self, tool: &str) -> Option<usize> {
+        let limit = self.tool_call_limits.get(tool).copied().unwrap_or(self.max_calls_per_tool);
+        (limit > 0).then_some(limit)
+    }
 }
 
 /// OAuth configuration for manual credential setup
@@ -62,6 +172,33 @@ fn default_max_iterations() -> usize {
     20
 }
 
+fn default_history_window() -> usize {
+    40
+}
+
+fn default_search_engine() -> String {
+    "google".to_string()
+}
+
+fn default_max_calls_per_tool() -> usize {
+    10
+}
+
+fn default_max_concurrent_requests() -> usize {
+    4
+}
+
+fn default_bootstrap_files() -> Vec<String> {
+    ["AGENTS.md", "SOUL.md", "USER.md", "IDENTITY.md", "TOOLS.md", "MEMORY.md"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect()
+}
+
+fn default_web_cache_ttl_secs() -> u64 {
+    600
+}
+
 fn default_provider() -> String {
     "gemini".to_string()
 }
@@ -76,6 +213,91 @@ pub struct TelegramConfig {
     
     #[serde(default)]
     pub allow_from: Vec<String>,
+    
+    /// Give each chat its own workspace under `<workspace>/sessions/<chat_id>`
+    #[serde(default)]
+    pub per_chat_workspace: bool,
+    
+    /// Ask for Yes/No confirmation in the chat before tools change anything
+    #[serde(default)]
+    pub confirm_actions: bool,
+    
+    /// Show the answer as it is written by editing the reply in place
+    #[serde(default)]
+    pub stream_replies: bool,
+    
+    /// Transcribe voice messages (see `transcription`) and answer them like text
+    #[serde(default)]
+    pub transcription_enabled: bool,
+}
+
+/// Speech-to-text settings: a local command, or an OpenAI-compatible endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptionConfig {
+    /// Local transcriber printing the text to stdout, with `{file}` for the
+    /// audio path, e.g. "whisper-cli -m ggml-base.bin -nt -f {file}".
+    /// Takes precedence over `endpoint`.
+    #[serde(default)]
+    pub command: Option<String>,
+    
+    /// `/audio/transcriptions` endpoint of an OpenAI-compatible API
+    #[serde(default = "default_transcription_endpoint")]
+    pub endpoint: String,
+    
+    #[serde(default)]
+    pub api_key: String,
+    
+    #[serde(default = "default_transcription_model")]
+    pub model: String,
+}
+
+/// Search APIs for the `web_search` tool; Brave is used when both keys are set
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchConfig {
+    /// Brave Search API subscription token
+    #[serde(default)]
+    pub brave_api_key: String,
+    
+    /// SerpAPI key (Google results)
+    #[serde(default)]
+    pub serpapi_api_key: String,
+    
+    /// Results returned per search
+    #[serde(default = "default_search_max_results")]
+    pub max_results: usize,
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        Self {
+            brave_api_key: String::new(),
+            serpapi_api_key: String::new(),
+            max_results: default_search_max_results(),
+        }
+    }
+}
+
+fn default_search_max_results() -> usize {
+    5
+}
+
+impl Default for TranscriptionConfig {
+    fn default() -> Self {
+        Self {
+            command: None,
+            endpoint: default_transcription_endpoint(),
+            api_key: String::new(),
+            model: default_transcription_model(),
+        }
+    }
+}
+
+fn default_transcription_endpoint() -> String {
+    "https://api.openai.com/v1/audio/transcriptions".to_string()
+}
+
+fn default_transcription_model() -> String {
+    "whisper-1".to_string()
 }
 
 impl Default for Config {
@@ -86,8 +308,31 @@ impl Default for Config {
             gemini_api_key: String::new(),
             model: default_model(),
             max_iterations: default_max_iterations(),
+            history_window: default_history_window(),
+            max_output_tokens: None,
             oauth: None,
             telegram: TelegramConfig::default(),
+            auto_log: false,
+            error_on_max_iterations: false,
+            max_calls_per_tool: default_max_calls_per_tool(),
+            tool_call_limits: HashMap::new(),
+            max_concurrent_requests: default_max_concurrent_requests(),
+            requests_per_minute: 0,
+            web_cache_ttl_secs: default_web_cache_ttl_secs(),
+            web_cache_on_disk: false,
+            tool_cache_ttl_secs: 0,
+            bootstrap_files: default_bootstrap_files(),
+            system_prompt_prefix: None,
+            system_prompt_path: None,
+            dry_run: false,
+            hide_unavailable_skills: false,
+            restrict_chmod_to_workspace: false,
+            google_calendar: false,
+            transcription: TranscriptionConfig::default(),
+            moments_dir: None,
+            mock_script: None,
+            web_search: SearchConfig::default(),
+            search_engine: default_search_engine(),
         }
     }
 }
@@ -134,49 +379,189 @@ pub fn save(config: &Config) -> Result<()> {
     Ok(())
 }
 
+/// Values supplied on the command line to `leo onboard`
+#[derive(Debug, Clone, Default)]
+pub struct OnboardOptions {
+    /// Provider name ("gemini" or "google-cli")
+    pub provider: Option<String>,
+    /// Gemini API key (required for "gemini")
+    pub api_key: Option<String>,
+    /// Workspace directory
+    pub workspace: Option<PathBuf>,
+    /// Never prompt; fail if a required value is missing
+    pub non_interactive: bool,
+    /// Don't test the API key against Gemini (offline setup)
+    pub skip_key_check: bool,
+}
+
+/// Build a config purely from `options`, erroring on anything missing or invalid
+fn config_from_options(options: &OnboardOptions) -> Result<Config> {
+    let mut config = Config::default();
+
+    let provider = options.provider.as_deref()
+        .ok_or_else(|| Error::Config("--provider is required with --non-interactive (gemini or google-cli)".to_string()))?;
+    match provider {
+        "gemini" => {
+            let key = options.api_key.as_deref().unwrap_or("").trim();
+            if key.is_empty() {
+                return Err(Error::Config("--api-key is required for the gemini provider with --non-interactive".to_string()));
+            }
+            config.gemini_api_key = key.to_string();
+        }
+        "google-cli" => {}
+        other => return Err(Error::Config(format!("Unknown provider: {} (expected gemini or google-cli)", other))),
+    }
+    config.provider = provider.to_string();
+
+    if let Some(workspace) = &options.workspace {
+        config.workspace = workspace.clone();
+    }
+
+    Ok(config)
+}
+
+/// Create the workspace folders and bootstrap files
+fn setup_workspace(workspace: &Path) -> Result<()> {
+    std::fs::create_dir_all(workspace)?;
+    std::fs::create_dir_all(workspace.join("memory"))?;
+    std::fs::create_dir_all(workspace.join("skills"))?;
+    create_bootstrap_files(workspace)
+}
+
+/// Models offered when the live list isn't available (OAuth provider, unchecked key)
+const COMMON_MODELS: &[&str] = &[
+    "gemini-2.0-flash",
+    "gemini-2.0-flash-lite",
+    "gemini-2.5-flash",
+    "gemini-2.5-pro",
+    "gemini-1.5-flash",
+    "gemini-1.5-pro",
+];
+
+/// Models to offer: the live list when there is one, else `COMMON_MODELS`,
+/// always including the current model
+fn model_choices(live: Option<Vec<String>>, current: &str) -> Vec<String> {
+    let mut models = live
+        .filter(|models| !models.is_empty())
+        .unwrap_or_else(|| COMMON_MODELS.iter().map(|m| m.to_string()).collect());
+    if !models.iter().any(|m| m == current) {
+        models.insert(0, current.to_string());
+    }
+    models
+}
+
+/// Let the user pick a chat model, from the live list when `live` (the API key
+/// works) and from the common models otherwise
+async fn choose_model(config: &mut Config, live: bool) -> Result<()> {
+    use crate::agent::LlmClient;
+    use inquire::Select;
+
+    let mut listed = None;
+    if live {
+        crate::ui::print_thinking("Fetching available models");
+        match crate::agent::GeminiClient::new(&config.gemini_api_key, &config.model).list_models().await {
+            Ok(models) => listed = Some(models),
+            Err(e) => crate::ui::print_warning(&format!("Could not list models ({}), showing common ones", e)),
+        }
+    }
+    let models = model_choices(listed, &config.model);
+
+    let start = models.iter().position(|m| *m == config.model).unwrap_or(0);
+    config.model = Select::new("Choose a model:", models)
+        .with_starting_cursor(start)
+        .prompt()
+        .map_err(|e| Error::Config(format!("Prompt failed: {}", e)))?;
+    Ok(())
+}
+
+/// Test a Gemini API key with a lightweight request
+async fn check_api_key(key: &str, model: &str) -> Result<()> {
+    crate::ui::print_thinking("Checking API key");
+    crate::agent::GeminiClient::new(key, model).validate_key().await
+}
+
 /// Initialize configuration and workspace
-pub fn onboard() -> Result<()> {
+pub async fn onboard(options: &OnboardOptions) -> Result<()> {
     use crate::ui;
     use inquire::{Select, Text, Confirm};
 
+    if options.non_interactive {
+        let config = config_from_options(options)?;
+        if config.provider == "gemini" && !options.skip_key_check {
+            check_api_key(&config.gemini_api_key, &config.model).await?;
+        }
+        setup_workspace(&config.workspace)?;
+        save(&config)?;
+        ui::print_success(&format!("Configured {} with workspace {:?}", config.provider, config.workspace));
+        return Ok(());
+    }
+
     ui::print_leo_header_with_emotion("Setup Wizard", "Local", ui::LionEmotion::Happy);
     println!("  Welcome! I'll help you get Leo configured in just a few steps.\n");
 
     let mut config = Config::default();
 
-    // 1. Select Provider
-    let providers = vec!["Gemini (API Key - Fast)", "Google CLI (OAuth - No key needed)"];
-    let provider_choice = Select::new("Choose your AI provider:", providers).prompt()
-        .map_err(|e| Error::Config(format!("Prompt failed: {}", e)))?;
+    // 1. Select Provider (flags skip the prompts)
+    let provider = match options.provider.as_deref() {
+        Some(provider @ ("gemini" | "google-cli")) => provider.to_string(),
+        Some(other) => return Err(Error::Config(format!("Unknown provider: {} (expected gemini or google-cli)", other))),
+        None => {
+            let providers = vec!["Gemini (API Key - Fast)", "Google CLI (OAuth - No key needed)"];
+            let provider_choice = Select::new("Choose your AI provider:", providers).prompt()
+                .map_err(|e| Error::Config(format!("Prompt failed: {}", e)))?;
+            if provider_choice.contains("API Key") { "gemini" } else { "google-cli" }.to_string()
+        }
+    };
 
-    if provider_choice.contains("API Key") {
+    let mut key_works = false;
+    if provider == "gemini" {
         config.provider = "gemini".to_string();
-        let key = Text::new("Enter your Gemini API Key:").prompt()
-            .map_err(|e| Error::Config(format!("Prompt failed: {}", e)))?;
+        let key = match &options.api_key {
+            Some(key) => key.clone(),
+            None => Text::new("Enter your Gemini API Key:").prompt()
+                .map_err(|e| Error::Config(format!("Prompt failed: {}", e)))?,
+        };
         config.gemini_api_key = key;
+
+        if !options.skip_key_check {
+            match check_api_key(&config.gemini_api_key, &config.model).await {
+                Ok(()) => {
+                    ui::print_success("API key works");
+                    key_works = true;
+                }
+                Err(e) => {
+                    ui::print_warning(&e.to_string());
+                    let keep = Confirm::new("Save this key anyway?").with_default(false).prompt()
+                        .map_err(|e| Error::Config(format!("Prompt failed: {}", e)))?;
+                    if !keep {
+                        return Err(Error::Config("Onboarding cancelled: the API key could not be verified".to_string()));
+                    }
+                }
+            }
+        }
     } else {
         config.provider = "google-cli".to_string();
     }
+    choose_model(&mut config, key_works).await?;
 
     // 2. Confirm Workspace
-    ui::print_step(&format!("Default workspace is at {:?}", config.workspace));
-    let change_path = Confirm::new("Use default workspace path?").with_default(true).prompt()
-        .map_err(|e| Error::Config(format!("Prompt failed: {}", e)))?;
-
-    if !change_path {
-        let new_path = Text::new("Enter custom workspace path:").prompt()
+    if let Some(workspace) = &options.workspace {
+        config.workspace = workspace.clone();
+    } else {
+        ui::print_step(&format!("Default workspace is at {:?}", config.workspace));
+        let change_path = Confirm::new("Use default workspace path?").with_default(true).prompt()
             .map_err(|e| Error::Config(format!("Prompt failed: {}", e)))?;
-        config.workspace = PathBuf::from(new_path);
+
+        if !change_path {
+            let new_path = Text::new("Enter custom workspace path:").prompt()
+                .map_err(|e| Error::Config(format!("Prompt failed: {}", e)))?;
+            config.workspace = PathBuf::from(new_path);
+        }
     }
 
     // 3. Setup Folders
-    ui::print_thinking("Creating directories");
-    std::fs::create_dir_all(&config.workspace)?;
-    std::fs::create_dir_all(config.workspace.join("memory"))?;
-    std::fs::create_dir_all(config.workspace.join("skills"))?;
-    
-    ui::print_thinking("Bootstrapping AGENTS.md and MEMORY.md");
-    create_bootstrap_files(&config.workspace)?;
+    ui::print_thinking("Creating directories and bootstrapping AGENTS.md and MEMORY.md");
+    setup_workspace(&config.workspace)?;
     
     // 4. Gateway Setup (Optional)
     let gateways = vec!["None (Skip for now)", "Telegram Bot", "WhatsApp (Coming soon)", "Slack (Coming soon)"];
@@ -232,39 +617,106 @@ pub fn setup_telegram_gateway(config: &mut Config) -> Result<()> {
     Ok(())
 }
 
-fn create_bootstrap_files(workspace: &PathBuf) -> Result<()> {
+fn create_bootstrap_files(workspace: &Path) -> Result<()> {
     crate::templates::bootstrap_workspace(workspace)
         .map_err(|e| Error::Config(format!("Failed to create bootstrap files: {}", e)))
 }
 
 /// Reset Leo by deleting all configuration and data
-pub fn reset() -> Result<()> {
+pub fn reset(targets: &[ResetTarget]) -> Result<()> {
     use inquire::Confirm;
     use crate::ui;
 
-    ui::print_warning("CAUTION: This will delete all Leo configuration, memory, and moments.");
+    let full = targets.is_empty() || targets.contains(&ResetTarget::All);
+    if full {
+        ui::print_warning("CAUTION: This will delete all Leo configuration, memory, and moments.");
+    } else {
+        let names: Vec<&str> = targets.iter().map(|t| t.label()).collect();
+        ui::print_warning(&format!("This will delete Leo's {} (config and credentials are kept).", names.join(", ")));
+    }
     
     let confirmed = Confirm::new("Are you absolutely sure you want to reset Leo?")
         .with_default(false)
         .prompt()
         .map_err(|e| Error::Config(format!("Prompt failed: {}", e)))?;
 
-    if confirmed {
-        let dir = config_dir();
-        if dir.exists() {
-            ui::print_thinking(&format!("Deleting {:?}", dir));
-            std::fs::remove_dir_all(dir)?;
-            ui::print_success("Leo has been reset.");
+    if !confirmed {
+        ui::print_step("Reset cancelled.");
+        return Ok(());
+    }
+
+    let config = load().unwrap_or_default();
+    let targets = if full { vec![ResetTarget::All] } else { targets.to_vec() };
+    let paths: Vec<PathBuf> = targets
+        .iter()
+        .flat_map(|t| t.paths(&config_dir(), &config))
+        .filter(|p| p.exists())
+        .collect();
+
+    if paths.is_empty() {
+        ui::print_step("Nothing to delete.");
+        return Ok(());
+    }
+
+    for path in paths {
+        ui::print_thinking(&format!("Deleting {:?}", path));
+        if path.is_dir() {
+            std::fs::remove_dir_all(&path)?;
         } else {
-            ui::print_step("No configuration directory found.");
+            std::fs::remove_file(&path)?;
         }
-    } else {
-        ui::print_step("Reset cancelled.");
     }
+    ui::print_success("Leo has been reset.");
 
     Ok(())
 }
 
+/// What `leo reset` should delete
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResetTarget {
+    /// The whole `~/.leo` directory
+    All,
+    /// Long-term memory and daily notes in the workspace and each chat's workspace
+    Memory,
+    /// Saved conversation history (the REPL history)
+    Sessions,
+    /// Browser moments (screenshots and metadata)
+    Moments,
+}
+
+impl ResetTarget {
+    fn label(&self) -> &'static str {
+        match self {
+            Self::All => "everything",
+            Self::Memory => "memory",
+            Self::Sessions => "sessions",
+            Self::Moments => "moments",
+        }
+    }
+
+    /// Paths removed for this target
+    fn paths(&self, leo_dir: &Path, config: &Config) -> Vec<PathBuf> {
+        match self {
+            Self::All => vec![leo_dir.to_path_buf()],
+            // Chats with `per_chat_workspace` keep their memory in `sessions/<chat_id>`
+            Self::Memory => {
+                let chats = std::fs::read_dir(config.workspace.join("sessions"))
+                    .into_iter()
+                    .flatten()
+                    .flatten()
+                    .map(|entry| entry.path().join("memory"));
+                let mut paths = vec![config.workspace.join("memory")];
+                paths.extend(chats);
+                paths
+            }
+            // Only conversation state: chat workspaces under `sessions` hold user files
+            Self::Sessions => vec![leo_dir.join("history")],
+            // ~/.leo/moments is where captures were saved before `moments_dir`
+            Self::Moments => vec![config.moments_path(), leo_dir.join("moments")],
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -274,6 +726,71 @@ mod tests {
         let config = Config::default();
         assert_eq!(config.model, "gemini-2.0-flash");
         assert_eq!(config.max_iterations, 20);
+        assert_eq!(config.history_window, 40);
+    }
+    
+    #[test]
+    fn test_config_from_options() {
+        let options = OnboardOptions {
+            provider: Some("gemini".to_string()),
+            api_key: Some("key-123".to_string()),
+            workspace: Some(PathBuf::from("/srv/leo")),
+            non_interactive: true,
+            skip_key_check: true,
+        };
+        let config = config_from_options(&options).unwrap();
+        assert_eq!(config.provider, "gemini");
+        assert_eq!(config.gemini_api_key, "key-123");
+        assert_eq!(config.workspace, PathBuf::from("/srv/leo"));
+        
+        let missing_key = OnboardOptions { api_key: None, ..options.clone() };
+        assert!(config_from_options(&missing_key).unwrap_err().to_string().contains("--api-key"));
+        
+        let missing_provider = OnboardOptions { provider: None, ..options.clone() };
+        assert!(config_from_options(&missing_provider).unwrap_err().to_string().contains("--provider"));
+        
+        let oauth = OnboardOptions { provider: Some("google-cli".to_string()), api_key: None, ..options };
+        assert_eq!(config_from_options(&oauth).unwrap().provider, "google-cli");
+    }
+    
+    #[test]
+    fn test_model_choices() {
+        let live = vec!["gemini-2.0-flash".to_string(), "gemini-2.5-pro".to_string()];
+        assert_eq!(model_choices(Some(live.clone()), "gemini-2.0-flash"), live);
+        
+        let fallback = model_choices(Some(Vec::new()), "gemini-2.0-flash");
+        assert_eq!(fallback.len(), COMMON_MODELS.len());
+        assert!(fallback.contains(&"gemini-1.5-pro".to_string()));
+        
+        let custom = model_choices(None, "my-tuned-model");
+        assert_eq!(custom[0], "my-tuned-model");
+    }
+    
+    #[test]
+    fn test_reset_target_paths() {
+        let leo_dir = PathBuf::from("/home/u/.leo");
+        let tmp = tempfile::TempDir::new().unwrap();
+        let workspace = tmp.path().to_path_buf();
+        let config = Config { workspace: workspace.clone(), ..Config::default() };
+        
+        assert_eq!(ResetTarget::Memory.paths(&leo_dir, &config), vec![workspace.join("memory")]);
+        std::fs::create_dir_all(workspace.join("sessions/42/memory")).unwrap();
+        assert_eq!(
+            ResetTarget::Memory.paths(&leo_dir, &config),
+            vec![workspace.join("memory"), workspace.join("sessions/42/memory")]
+        );
+        assert_eq!(
+            ResetTarget::Moments.paths(&leo_dir, &config),
+            vec![workspace.join("moments"), leo_dir.join("moments")]
+        );
+        assert_eq!(
+            ResetTarget::Sessions.paths(&leo_dir, &config),
+            vec![leo_dir.join("history")]
+        );
+        assert_eq!(ResetTarget::All.paths(&leo_dir, &config), vec![leo_dir.clone()]);
+        
+        let custom = Config { moments_dir: Some(PathBuf::from("/data/moments")), ..config };
+        assert_eq!(custom.moments_path(), PathBuf::from("/data/moments"));
     }
     
     #[test]
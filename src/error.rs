@@ -1,5 +1,7 @@
 //! Error types for Leo
 
+use std::future::Future;
+use std::time::Duration;
 use thiserror::Error;
 
 /// Result type alias for Leo operations
@@ -41,6 +43,9 @@ pub enum Error {
     #[error("OAuth error: {0}")]
     OAuth(String),
 
+    #[error("Timed out: {0}")]
+    Timeout(String),
+
     #[error("{0}")]
     Other(String),
 }
@@ -50,3 +55,71 @@ impl From<anyhow::Error> for Error {
         Error::Other(err.to_string())
     }
 }
+
+impl Error {
+    /// Short snake_case name of the variant, e.g. for metric labels
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Error::Config(_) => "config",
+            Error::Llm(_) => "llm",
+            Error::Tool(_) => "tool",
+            Error::Memory(_) => "memory",
+            Error::Io(_) => "io",
+            Error::Telegram(_) => "telegram",
+            Error::Json(_) => "json",
+            Error::Http(_) => "http",
+            Error::MaxIterations => "max_iterations",
+            Error::Auth(_) => "auth",
+            Error::OAuth(_) => "oauth",
+            Error::Timeout(_) => "timeout",
+            Error::Other(_) => "other",
+        }
+    }
+
+    /// Whether trying the same operation again might succeed
+    ///
+    /// True for timeouts, dropped connections, rate limits and server errors.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Error::Timeout(_) => true,
+            Error::Http(e) => {
+                e.is_timeout()
+                    || e.is_connect()
+                    || e.status().is_some_and(|s| s.as_u16() == 429 || s.is_server_error())
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Run `future` with a time limit, failing with `Error::Timeout` that names `what`
+pub async fn with_timeout<T, F>(duration: Duration, what: &str, future: F) -> Result<T>
+where
+    F: Future<Output = Result<T>>,
+{
+    tokio::time::timeout(duration, future)
+        .await
+        .map_err(|_| Error::Timeout(format!("{} after {:?}", what, duration)))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_with_timeout() {
+        let done = with_timeout(Duration::from_secs(1), "quick", async { Ok(7) }).await.unwrap();
+        assert_eq!(done, 7);
+
+        let err = with_timeout(Duration::from_millis(10), "sleeping", async {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            Ok(())
+        })
+        .await
+        .unwrap_err();
+        assert!(matches!(err, Error::Timeout(_)));
+        assert_eq!(err.to_string(), "Timed out: sleeping after 10ms");
+        assert!(err.is_retryable());
+        assert!(!Error::Tool("bad input".to_string()).is_retryable());
+    }
+}
@@ -12,6 +12,8 @@ pub mod adapters;
 pub mod auth;
 pub mod config;
 pub mod error;
+pub mod metrics;
+pub mod telemetry;
 pub mod ui;
 
 pub use error::{Error, Result};
@@ -1,7 +1,6 @@
 //! Leo CLI entry point
 
 use clap::{Parser, Subcommand};
-use tracing_subscriber::EnvFilter;
 use anyhow::Result;
 use colored::Colorize;
 
@@ -12,22 +11,62 @@ use colored::Colorize;
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+    
+    /// Export traces to this OTLP/HTTP collector, e.g. http://localhost:4318 (or set LEO_OTLP_ENDPOINT)
+    #[arg(long, global = true, value_name = "ENDPOINT")]
+    otlp: Option<String>,
 }
 
 #[derive(Subcommand)]
 enum Commands {
     /// Initialize Leo configuration and workspace
-    Onboard,
+    Onboard {
+        /// AI provider: gemini or google-cli
+        #[arg(long)]
+        provider: Option<String>,
+        
+        /// Gemini API key (for the gemini provider)
+        #[arg(long)]
+        api_key: Option<String>,
+        
+        /// Workspace directory
+        #[arg(long)]
+        workspace: Option<std::path::PathBuf>,
+        
+        /// Never prompt; fail if a required value is missing
+        #[arg(long)]
+        non_interactive: bool,
+        
+        /// Don't verify the API key with Gemini (for offline setup)
+        #[arg(long)]
+        skip_key_check: bool,
+    },
     
     /// Chat with the agent
     Agent {
-        /// Message to send to the agent
+        /// Message to send to the agent ("-" reads stdin; piped stdin is also used when omitted)
         #[arg(short, long)]
         message: Option<String>,
         
         /// Session ID
         #[arg(short, long, default_value = "cli:default")]
         session: String,
+        
+        /// Print the response as a JSON payload
+        #[arg(long)]
+        json: bool,
+        
+        /// Write the response to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<std::path::PathBuf>,
+        
+        /// Override the configured tool iteration limit for this run
+        #[arg(long, value_name = "N")]
+        max_iterations: Option<usize>,
+        
+        /// Describe file writes, commands and commits instead of performing them
+        #[arg(long)]
+        dry_run: bool,
     },
     
     /// Login to Google for OAuth authentication
@@ -42,10 +81,14 @@ enum Commands {
     
     /// Start the Leo gateway
     Gateway {
-        /// Gateway port
+        /// Port for the health endpoint
         #[arg(short, long, default_value_t = 18790)]
         port: u16,
         
+        /// Address to bind the health endpoint to
+        #[arg(long, default_value = "127.0.0.1")]
+        host: String,
+        
         /// Verbose output
         #[arg(short, long)]
         verbose: bool,
@@ -53,17 +96,57 @@ enum Commands {
     
     /// Show Leo status
     Status,
+    
+    /// List, check and create skills in the workspace
+    Skills {
+        #[command(subcommand)]
+        action: SkillsCommand,
+    },
 
-    /// Reset Leo - delete all configuration and data
-    Reset,
+    /// Reset Leo - delete all configuration and data, or only the parts selected by flags
+    Reset {
+        /// Only clear memory (MEMORY.md and daily notes, including each chat's)
+        #[arg(long)]
+        memory: bool,
+        
+        /// Only clear saved conversation history
+        #[arg(long)]
+        sessions: bool,
+        
+        /// Only clear browser moments
+        #[arg(long)]
+        moments: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum SkillsCommand {
+    /// List skills with their descriptions and required tools
+    List,
+    
+    /// Parse every SKILL.md and report frontmatter errors
+    Validate,
+    
+    /// Create a skill directory with a template SKILL.md
+    New {
+        /// Skill name (lowercase letters, digits, '-' and '_')
+        name: String,
+    },
+    
+    /// Install a skill from a git repository or a zip archive URL
+    Install {
+        /// Git URL, or a URL or path ending in .zip
+        source: String,
+    },
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // Initialize logging
-    tracing_subscriber::fmt()
-        .with_env_filter(EnvFilter::from_default_env())
-        .init();
+    let cli = Cli::parse();
+    
+    // Initialize logging, and trace export when requested
+    let otlp_endpoint = leo::telemetry::otlp_endpoint(cli.otlp.clone());
+    let _telemetry = leo::telemetry::init(otlp_endpoint.as_deref());
         
     // Setup Global Ctrl+C handler
     let exit_flag = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
@@ -86,15 +169,16 @@ async fn main() -> Result<()> {
         }
     }).ok();
     
-    let cli = Cli::parse();
-    
     match cli.command {
-        Commands::Onboard => {
-            leo::config::onboard()?;
+        Commands::Onboard { provider, api_key, workspace, non_interactive, skip_key_check } => {
+            let options = leo::config::OnboardOptions { provider, api_key, workspace, non_interactive, skip_key_check };
+            leo::config::onboard(&options).await?;
             
             // Reload config to check provider
             let config = leo::config::load()?;
-            if config.provider == "google-cli" {
+            if config.provider == "google-cli" && non_interactive {
+                leo::ui::print_step("Run 'leo login' to authenticate with Google.");
+            } else if config.provider == "google-cli" {
                 println!();
                 run_login(false).await?;
             }
@@ -105,17 +189,45 @@ async fn main() -> Result<()> {
         }
         
         Commands::Logout => {
-            leo::auth::delete_credentials()?;
+            leo::auth::delete_credentials(leo::auth::GOOGLE_PROVIDER)?;
             println!("✓ Logged out successfully");
         }
         
-        Commands::Agent { message, session } => {
-            let config = leo::config::load()?;
+        Commands::Agent { message, session, json, output, max_iterations, dry_run } => {
+            let mut config = leo::config::load()?;
+            if let Some(n) = max_iterations {
+                config.max_iterations = n;
+            }
+            if dry_run {
+                config.dry_run = true;
+            }
+            
+            // Piped input becomes the message (or is appended to -m)
+            let explicit_stdin = message.as_deref() == Some("-");
+            let message = message.filter(|m| m != "-");
+            let message = match (message, read_piped_stdin(explicit_stdin)?) {
+                (Some(msg), Some(piped)) => Some(format!("{}\n\n{}", msg, piped)),
+                (msg, piped) => msg.or(piped),
+            };
             
             if let Some(msg) = message {
                 // Single message mode
                 let response = run_agent_once(&config, &msg, &session).await?;
-                println!("\n  {} {}", "🦁".green(), response);
+                let payload = if json {
+                    serde_json::to_string_pretty(&response)?
+                } else {
+                    response.media.iter()
+                        .fold(response.content.clone(), |text, path| format!("{}\n  📎 {}", text, path))
+                };
+                
+                match output {
+                    Some(path) => {
+                        std::fs::write(&path, &payload)?;
+                        leo::ui::print_success(&format!("Response written to {}", path.display()));
+                    }
+                    None if json => println!("{}", payload),
+                    None => println!("\n  {} {}", "🦁".green(), payload),
+                }
             } else {
                 // Interactive mode
                 leo::ui::print_leo_header_with_emotion(&config.model, &config.provider, leo::ui::LionEmotion::Happy);
@@ -123,14 +235,14 @@ async fn main() -> Result<()> {
             }
         }
         
-        Commands::Gateway { port, verbose } => {
+        Commands::Gateway { port, host, verbose } => {
             let config = leo::config::load()?;
             leo::ui::print_leo_header_with_emotion(&config.model, &format!("Gateway:{}", config.provider), leo::ui::LionEmotion::Anxiety);
             
             if verbose {
                 tracing::info!("Starting gateway on port {}", port);
             }
-            run_gateway(port).await?;
+            run_gateway(&host, port).await?;
         }
         
         Commands::Status => {
@@ -144,7 +256,7 @@ async fn main() -> Result<()> {
                     let status = if config.gemini_api_key.is_empty() { 
                         "not set".red() 
                     } else { 
-                        "✓".green() 
+                        format!("✓ {}", mask_secret(&config.gemini_api_key)).green() 
                     };
                     println!("  {} {}", "Gemini API:".black().bold(), status);
                 }
@@ -157,121 +269,284 @@ async fn main() -> Result<()> {
                         "not set (run 'leo login')".red() 
                     };
                     println!("  {} {}", "OAuth credentials:".black().bold(), status);
+                    if let Some(creds) = leo::auth::load_credentials(leo::auth::GOOGLE_PROVIDER).ok().flatten() {
+                        if let Some(email) = &creds.email {
+                            println!("  {} {}", "Account:".black().bold(), email);
+                        }
+                        let expiry = creds.expiry_description();
+                        let expiry = if creds.is_expired() { expiry.yellow() } else { expiry.green() };
+                        println!("  {} {}", "Access token:".black().bold(), expiry);
+                        let refresh = if creds.can_refresh() { 
+                            "✓".green() 
+                        } else { 
+                            "missing (run 'leo login')".red() 
+                        };
+                        println!("  {} {}", "Refresh token:".black().bold(), refresh);
+                    }
+                }
+                "mock" => {
+                    let script = config.mock_script.as_ref()
+                        .map(|p| p.display().to_string().green())
+                        .unwrap_or_else(|| "not set".red());
+                    println!("  {} {}", "Mock script:".black().bold(), script);
                 }
                 _ => {
                     println!("  {} {}", "Unknown provider:".black().bold(), config.provider);
                 }
             }
+            
+            if let Ok(token) = leo::tools::browser_pairing_token() {
+                println!("  {} {}", "Browser pairing token:".black().bold(), token);
+            }
+            
+            // Build the tools as a session would, to show what's actually active
+            let runner = leo::tools::ToolRunner::from_config(&config);
+            let mut tools = runner.tool_names();
+            tools.sort_unstable();
+            println!("  {} {}", "Tools:".black().bold(), tools.join(", "));
+            
+            // Give the bridge a moment to bind its port
+            let mut bridge = leo::tools::browser_bridge_status();
+            for _ in 0..10 {
+                if bridge != Some(leo::tools::BridgeStatus::Starting) {
+                    break;
+                }
+                tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+                bridge = leo::tools::browser_bridge_status();
+            }
+            if let Some(bridge) = bridge {
+                let line = match bridge {
+                    leo::tools::BridgeStatus::Listening => format!("✓ {}", bridge).green(),
+                    leo::tools::BridgeStatus::Failed(_) => bridge.to_string().red(),
+                    _ => bridge.to_string().yellow(),
+                };
+                println!("  {} {}", "Browser bridge:".black().bold(), line);
+            }
+            
+            let usage = leo::agent::cost::today_totals(&leo::agent::cost::usage_log_path())?;
+            println!(
+                "  {} {} requests, {}↓ {}↑ tokens, ~${:.4}",
+                "Today:".black().bold(),
+                usage.requests,
+                usage.prompt_tokens,
+                usage.completion_tokens,
+                usage.cost_usd
+            );
             println!();
         }
         
-        Commands::Reset => {
+        Commands::Skills { action } => {
+            let config = leo::config::load()?;
+            run_skills(&config, action).await?;
+        }
+        
+        Commands::Reset { memory, sessions, moments } => {
+            use leo::config::ResetTarget;
+            
             leo::ui::print_leo_header_with_emotion("Maintenance", "Local", leo::ui::LionEmotion::Fear);
-            leo::config::reset()?;
+            let targets: Vec<ResetTarget> = [
+                (memory, ResetTarget::Memory),
+                (sessions, ResetTarget::Sessions),
+                (moments, ResetTarget::Moments),
+            ]
+            .into_iter()
+            .filter_map(|(selected, target)| selected.then_some(target))
+            .collect();
+            leo::config::reset(&targets)?;
         }
     }
     
     Ok(())
 }
 
-async fn run_agent_once(config: &leo::config::Config, message: &str, _session: &str) -> Result<String> {
-    use leo::agent::{AgentLoop, Message, Context};
+/// Handle `leo skills` subcommands
+async fn run_skills(config: &leo::config::Config, action: SkillsCommand) -> Result<()> {
+    let skills_dir = leo::skills::skills_dir(&config.workspace);
+    match action {
+        SkillsCommand::List => {
+            // Only read the skills; building the tools would start the browser bridge
+            let registry = leo::skills::SkillRegistry::new(&config.workspace);
+            let skills = registry.skills();
+            if skills.is_empty() {
+                println!("No skills in {}", skills_dir.display());
+                return Ok(());
+            }
+            for skill in skills {
+                println!("  {} {}", skill.name.bold(), skill.description);
+                if !skill.requires.is_empty() {
+                    println!("    {}", format!("requires: {}", skill.requires.join(", ")).dimmed());
+                }
+            }
+        }
+        SkillsCommand::Validate => {
+            let registry = leo::skills::SkillRegistry::new(&config.workspace);
+            let diagnostics = registry.diagnostics();
+            if registry.is_empty() && diagnostics.is_empty() {
+                println!("No skills in {}", skills_dir.display());
+                return Ok(());
+            }
+            for skill in registry.skills() {
+                leo::ui::print_success(&format!("{} ({})", skill.name, skill.path.display()));
+            }
+            for (path, reason) in diagnostics {
+                leo::ui::print_error(&format!("{}: {}", path.display(), reason));
+            }
+            if !diagnostics.is_empty() {
+                anyhow::bail!("{} of {} skills failed to load", diagnostics.len(), registry.len() + diagnostics.len());
+            }
+        }
+        SkillsCommand::New { name } => {
+            let skill_md = leo::skills::scaffold(&skills_dir, &name)?;
+            leo::ui::print_success(&format!("Created {}", skill_md.display()));
+        }
+        SkillsCommand::Install { source } => {
+            leo::ui::print_step(&format!("Installing skill from {}", source));
+            let skill = leo::skills::install(&config.workspace, &source).await?;
+            leo::ui::print_success(&format!("Installed {} to {}", skill.name, skill.path.display()));
+        }
+    }
+    Ok(())
+}
+
+/// Mask a secret for display, keeping only its first and last few characters.
+fn mask_secret(secret: &str) -> String {
+    let chars: Vec<char> = secret.chars().collect();
+    if chars.len() <= 8 {
+        return "*".repeat(chars.len());
+    }
+    let head: String = chars[..4].iter().collect();
+    let tail: String = chars[chars.len() - 4..].iter().collect();
+    format!("{}...{}", head, tail)
+}
+
+/// How long to wait for the first byte of piped stdin nobody asked for
+const STDIN_WAIT: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// Read all of stdin when it is piped rather than an interactive terminal.
+///
+/// Unless `explicit` (the user passed `-m -`), input that doesn't start within
+/// `STDIN_WAIT` is ignored, so an open pipe without a writer (as some process
+/// supervisors leave) can't block forever.
+fn read_piped_stdin(explicit: bool) -> Result<Option<String>> {
+    use std::io::{IsTerminal, Read};
+    
+    if !explicit && std::io::stdin().is_terminal() {
+        return Ok(None);
+    }
+    
+    let (started_tx, started_rx) = std::sync::mpsc::channel();
+    let reader = std::thread::spawn(move || -> std::io::Result<Vec<u8>> {
+        let mut stdin = std::io::stdin();
+        let mut input = vec![0; 1];
+        let n = stdin.read(&mut input)?;
+        let _ = started_tx.send(());
+        input.truncate(n);
+        if n > 0 {
+            stdin.read_to_end(&mut input)?;
+        }
+        Ok(input)
+    });
+    
+    if !explicit && started_rx.recv_timeout(STDIN_WAIT).is_err() {
+        // Still waiting for a writer; leave the thread blocked and carry on
+        return Ok(None);
+    }
+    
+    let input = reader.join().map_err(|_| anyhow::anyhow!("stdin reader panicked"))??;
+    let input = String::from_utf8_lossy(&input);
+    let input = input.trim();
+    Ok(if input.is_empty() { None } else { Some(input.to_string()) })
+}
+
+/// Build the agent loop with request limits and the tools that call the LLM themselves.
+fn build_agent<C>(client: C, ctx: &mut leo::agent::Context, config: &leo::config::Config) -> leo::agent::AgentLoop<C>
+where
+    C: leo::agent::LlmClient + Clone + 'static,
+{
+    let limiter = std::sync::Arc::new(leo::agent::RequestLimiter::from_config(config));
+    ctx.tool_runner.register(leo::tools::SummarizeTool::new(leo::agent::LimitedClient::new(client.clone(), limiter.clone())));
+    leo::agent::AgentLoop::new(client, config.max_iterations)
+        .with_limiter(limiter)
+        .with_usage_log(leo::agent::cost::usage_log_path())
+}
+
+async fn run_agent_once(config: &leo::config::Config, message: &str, session: &str) -> Result<leo::agent::Response> {
+    use leo::agent::{Message, Context};
     use leo::agent::GeminiClient;
     use leo::agent::GeminiOAuthClient;
+    use leo::agent::MockClient;
     
     let mut ctx = Context::new(config)?;
+    ctx.session = format!("cli:{}", session);
     
     let response = match config.provider.as_str() {
         "google-cli" => {
             // Use OAuth authentication
-            let client = GeminiOAuthClient::from_cli(&config.model)?;
-            let agent = AgentLoop::new(client, config.max_iterations);
+            let client = GeminiOAuthClient::from_cli(&config.model)?
+                .with_calendar(config.google_calendar)
+                .with_max_output_tokens(config.max_output_tokens);
+            let agent = build_agent(client, &mut ctx, config);
+            let msg = Message::user(message);
+            agent.run(&[], msg, &mut ctx).await?
+        }
+        "mock" => {
+            let client = MockClient::from_config(config)?;
+            let agent = build_agent(client, &mut ctx, config);
             let msg = Message::user(message);
             agent.run(&[], msg, &mut ctx).await?
         }
         _ => {
             // Default: Use API key authentication
-            let client = GeminiClient::new(&config.gemini_api_key, &config.model);
-            let agent = AgentLoop::new(client, config.max_iterations);
+            let client = GeminiClient::new(&config.gemini_api_key, &config.model)
+                .with_max_output_tokens(config.max_output_tokens);
+            let agent = build_agent(client, &mut ctx, config);
             let msg = Message::user(message);
             agent.run(&[], msg, &mut ctx).await?
         }
     };
     
-    Ok(response.content)
+    Ok(response)
 }
 
-async fn run_agent_interactive(config: &leo::config::Config, _session: &str) -> Result<()> {
-    use std::io::{self, Write};
-    use leo::agent::{AgentLoop, Message, Context};
+async fn run_agent_interactive(config: &leo::config::Config, session: &str) -> Result<()> {
+    use leo::adapters::cli::CliChannel;
+    use leo::agent::Context;
     use leo::agent::GeminiClient;
     use leo::agent::GeminiOAuthClient;
+    use leo::agent::MockClient;
     use leo::ui;
     
     // Initialize Context ONCE to keep tools (like Browser Bridge) alive
     ui::print_thinking("Initializing tools");
     let mut ctx = Context::new(config)?;
+    ctx.session = format!("cli:{}", session);
     ui::print_success("Ready! (Browser Extension can now connect)\n");
     
-    // History for interactive session
-    let mut history: Vec<Message> = Vec::new();
-
-    loop {
-        // Blue "You"
-        print!("  \x1b[1;34mYou\x1b[0m: ");
-        io::stdout().flush()?;
-        
-        let mut input = String::new();
-        io::stdin().read_line(&mut input)?;
-        let input = input.trim();
-        
-        if input.eq_ignore_ascii_case("exit") || input.eq_ignore_ascii_case("quit") {
-            println!("\n  → Bye!");
-            break;
+    match config.provider.as_str() {
+        "google-cli" => {
+            let client = GeminiOAuthClient::from_cli(&config.model)?
+                .with_calendar(config.google_calendar)
+                .with_max_output_tokens(config.max_output_tokens);
+            let agent = build_agent(client, &mut ctx, config);
+            CliChannel::new(agent, ctx).run_interactive().await?;
         }
-        
-        if input.is_empty() {
-            continue;
+        "mock" => {
+            let client = MockClient::from_config(config)?;
+            let agent = build_agent(client, &mut ctx, config);
+            CliChannel::new(agent, ctx).run_interactive().await?;
         }
-        
-        // Green "Bot", Red "Error"
-        // We inline the agent run logic here to reuse ctx
-        let history_clone = history.clone();
-        let result = async {
-            let response = match config.provider.as_str() {
-                "google-cli" => {
-                    let client = GeminiOAuthClient::from_cli(&config.model)?;
-                    let agent = AgentLoop::new(client, config.max_iterations);
-                    let msg = Message::user(input);
-                    agent.run(&history_clone, msg, &mut ctx).await?
-                }
-                _ => {
-                    let client = GeminiClient::new(&config.gemini_api_key, &config.model);
-                    let agent = AgentLoop::new(client, config.max_iterations);
-                    let msg = Message::user(input);
-                    agent.run(&history_clone, msg, &mut ctx).await?
-                }
-            };
-            Ok::<leo::agent::Response, anyhow::Error>(response)
-        }.await;
-
-        match result {
-            Ok(response) => {
-                let content = response.content;
-                println!("\n  \x1b[1;32mLeo\x1b[0m: {}\n", content);
-                
-                // Update history
-                history.push(Message::user(input));
-                history.push(Message::assistant(content));
-            },
-            Err(e) => println!("\n  \x1b[1;31mError\x1b[0m: {}\n", e),
+        _ => {
+            let client = GeminiClient::new(&config.gemini_api_key, &config.model)
+                .with_max_output_tokens(config.max_output_tokens);
+            let agent = build_agent(client, &mut ctx, config);
+            CliChannel::new(agent, ctx).run_interactive().await?;
         }
     }
     Ok(())
 }
 
 async fn run_login(dry_run: bool) -> Result<()> {
-    use leo::auth::{extract_cli_credentials, GeminiAuthProvider};
+    use leo::auth::{extract_cli_credentials, load_credentials, save_credentials, GeminiAuthProvider, GOOGLE_PROVIDER};
     use leo::ui;
     
     ui::print_leo_header_with_emotion("Authentication", "Google SDK", ui::LionEmotion::Happy);
@@ -292,11 +567,28 @@ async fn run_login(dry_run: bool) -> Result<()> {
             }
             
             // Start OAuth flow
-            let provider = GeminiAuthProvider::new(creds.client_id, creds.client_secret);
-            let _token = provider.get_valid_token().await?;
+            let calendar = leo::config::load().map(|c| c.google_calendar).unwrap_or(false);
+            let provider = GeminiAuthProvider::new(creds.client_id, creds.client_secret)
+                .with_calendar(calendar);
+            let token = provider.get_valid_token().await?;
+            
+            // Older credential files predate the stored email; look it up once
+            let mut email = None;
+            if let Some(mut stored) = load_credentials(GOOGLE_PROVIDER)? {
+                if stored.email.is_none() {
+                    if let Ok(found) = provider.fetch_user_email(&token).await {
+                        stored.email = Some(found);
+                        save_credentials(GOOGLE_PROVIDER, &stored)?;
+                    }
+                }
+                email = stored.email;
+            }
             
             println!();
             ui::print_success("Authentication successful!");
+            if let Some(email) = email {
+                ui::print_step(&format!("Signed in as {}", email));
+            }
             ui::print_step("Credentials saved to ~/.leo/credentials.json");
             ui::print_step("You can now use: leo agent -m \"Hello!\"");
         }
@@ -311,11 +603,13 @@ async fn run_login(dry_run: bool) -> Result<()> {
     Ok(())
 }
 
-async fn run_gateway(_port: u16) -> Result<()> {
-    use leo::agent::AgentLoop;
+async fn run_gateway(host: &str, port: u16) -> Result<()> {
     use leo::agent::GeminiClient;
     use leo::agent::GeminiOAuthClient;
-    use leo::adapters::{Channel, telegram::TelegramChannel};
+    use leo::agent::MockClient;
+    use leo::adapters::health::{self, HealthState};
+    use leo::adapters::telegram::TelegramChannel;
+    use std::sync::Arc;
 
     println!("∴ Loading configuration...");
     let mut config = leo::config::load()?;
@@ -353,27 +647,87 @@ async fn run_gateway(_port: u16) -> Result<()> {
     
     // Initialize Context once to keep Browser Bridge alive
     println!("🦁 Initializing tools...");
-    let ctx = leo::agent::Context::new(&config)?;
+    let mut ctx = leo::agent::Context::new(&config)?;
     println!("✓ Tools ready! (Browser Extension can now connect)");
+    if let Ok(token) = leo::tools::browser_pairing_token() {
+        println!("  Pair the Leo Link extension with token: {}", token);
+    }
+
+    let health_state = Arc::new(HealthState::new());
+    let listener = tokio::net::TcpListener::bind((host, port)).await?;
+    tokio::spawn(health::serve(listener, health_state.clone()));
+    println!("✓ Health endpoint at http://{}:{}/health (metrics at /metrics)", host, port);
 
     match config.provider.as_str() {
         "google-cli" => {
-            let client = GeminiOAuthClient::from_cli(&config.model)?;
-            let agent = AgentLoop::new(client, config.max_iterations);
-            let channel = TelegramChannel::new(config, agent, ctx);
+            let client = GeminiOAuthClient::from_cli(&config.model)?
+                .with_calendar(config.google_calendar)
+                .with_max_output_tokens(config.max_output_tokens);
+            let agent = build_agent(client, &mut ctx, &config);
+            let channel = TelegramChannel::new(config, agent).with_health(health_state);
             println!("✓ Gateway started. Listening for Telegram messages...");
-            channel.start().await?;
+            run_until_shutdown(&channel).await?;
+        }
+        "mock" => {
+            let client = MockClient::from_config(&config)?;
+            let agent = build_agent(client, &mut ctx, &config);
+            let channel = TelegramChannel::new(config, agent).with_health(health_state);
+            println!("✓ Gateway started. Listening for Telegram messages...");
+            run_until_shutdown(&channel).await?;
         }
         _ => {
-            let client = GeminiClient::new(&config.gemini_api_key, &config.model);
-            let agent = AgentLoop::new(client, config.max_iterations);
-            let channel = TelegramChannel::new(config, agent, ctx);
+            let client = GeminiClient::new(&config.gemini_api_key, &config.model)
+                .with_max_output_tokens(config.max_output_tokens);
+            let agent = build_agent(client, &mut ctx, &config);
+            let channel = TelegramChannel::new(config, agent).with_health(health_state);
             println!("✓ Gateway started. Listening for Telegram messages...");
-            channel.start().await?;
+            run_until_shutdown(&channel).await?;
         }
     };
 
     Ok(())
 }
 
+/// Run a channel until it exits on its own or the process receives Ctrl+C / SIGTERM.
+async fn run_until_shutdown<Ch: leo::adapters::Channel>(channel: &Ch) -> Result<()> {
+    let run = channel.start();
+    tokio::pin!(run);
+
+    tokio::select! {
+        result = &mut run => return Ok(result?),
+        _ = shutdown_signal() => {}
+    }
+
+    println!("∴ Shutting down {}...", channel.name());
+    // Keep polling the channel while it stops so in-flight messages can finish
+    let (stopped, finished) = tokio::join!(channel.stop(), run);
+    stopped?;
+    finished?;
+    println!("✓ Gateway stopped.");
+    Ok(())
+}
+
+/// Resolve when the process is asked to terminate.
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {}
+                    _ = sigterm.recv() => {}
+                }
+            }
+            Err(_) => {
+                let _ = tokio::signal::ctrl_c().await;
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+    }
+}
+
 
@@ -16,11 +16,26 @@ pub trait MemoryStore: Send + Sync {
     /// Append to long-term memory
     fn append_long_term(&self, content: &str) -> Result<()>;
     
+    /// Overwrite long-term memory
+    fn replace_long_term(&self, content: &str) -> Result<()>;
+    
     /// Get today's notes
     fn read_today(&self) -> Result<String>;
     
     /// Append to today's notes
     fn append_today(&self, content: &str) -> Result<()>;
+    
+    /// Overwrite today's notes
+    fn replace_today(&self, content: &str) -> Result<()>;
+}
+
+/// Append an entry separated by exactly one newline from what came before
+fn append_entry(current: &mut String, content: &str) {
+    current.truncate(current.trim_end().len());
+    if !current.is_empty() {
+        current.push('\n');
+    }
+    current.push_str(content.trim_end());
 }
 
 /// File-based memory store
@@ -47,6 +62,15 @@ impl FileMemoryStore {
         let today = Local::now().format("%Y-%m-%d").to_string();
         self.memory_dir().join("daily").join(format!("{}.md", today))
     }
+    
+    /// Write a memory file, creating its directory if needed
+    fn write_file(path: &Path, content: &str) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, content)?;
+        Ok(())
+    }
 }
 
 impl MemoryStore for FileMemoryStore {
@@ -91,13 +115,16 @@ impl MemoryStore for FileMemoryStore {
             String::new()
         };
         
-        current.push('\n');
-        current.push_str(content);
+        append_entry(&mut current, content);
         std::fs::write(&path, current)?;
         
         Ok(())
     }
     
+    fn replace_long_term(&self, content: &str) -> Result<()> {
+        Self::write_file(&self.long_term_path(), content)
+    }
+    
     fn read_today(&self) -> Result<String> {
         let path = self.today_path();
         if path.exists() {
@@ -115,18 +142,22 @@ impl MemoryStore for FileMemoryStore {
             std::fs::create_dir_all(parent)?;
         }
         
-        let mut current = if path.exists() {
-            std::fs::read_to_string(&path)?
+        let current = if path.exists() {
+            let mut current = std::fs::read_to_string(&path)?;
+            append_entry(&mut current, content);
+            current
         } else {
-            format!("# Notes for {}\n\n", Local::now().format("%Y-%m-%d"))
+            format!("# Notes for {}\n\n{}", Local::now().format("%Y-%m-%d"), content.trim_end())
         };
         
-        current.push('\n');
-        current.push_str(content);
         std::fs::write(&path, current)?;
         
         Ok(())
     }
+    
+    fn replace_today(&self, content: &str) -> Result<()> {
+        Self::write_file(&self.today_path(), content)
+    }
 }
 
 /// In-memory store for testing
@@ -171,9 +202,12 @@ impl MemoryStore for InMemoryStore {
     }
     
     fn append_long_term(&self, content: &str) -> Result<()> {
-        let mut lt = self.long_term.lock().unwrap();
-        lt.push('\n');
-        lt.push_str(content);
+        append_entry(&mut self.long_term.lock().unwrap(), content);
+        Ok(())
+    }
+    
+    fn replace_long_term(&self, content: &str) -> Result<()> {
+        *self.long_term.lock().unwrap() = content.to_string();
         Ok(())
     }
     
@@ -182,9 +216,12 @@ impl MemoryStore for InMemoryStore {
     }
     
     fn append_today(&self, content: &str) -> Result<()> {
-        let mut t = self.today.lock().unwrap();
-        t.push('\n');
-        t.push_str(content);
+        append_entry(&mut self.today.lock().unwrap(), content);
+        Ok(())
+    }
+    
+    fn replace_today(&self, content: &str) -> Result<()> {
+        *self.today.lock().unwrap() = content.to_string();
         Ok(())
     }
 }
@@ -206,4 +243,56 @@ mod tests {
         assert!(context.contains("coffee"));
         assert!(context.contains("project plans"));
     }
+    
+    #[test]
+    fn test_in_memory_replace_overwrites() {
+        let store = InMemoryStore::new();
+        
+        store.append_long_term("User likes coffee").unwrap();
+        store.replace_long_term("User likes tea").unwrap();
+        assert_eq!(store.read_long_term().unwrap(), "User likes tea");
+        
+        store.append_today("Old note").unwrap();
+        store.replace_today("New note").unwrap();
+        assert_eq!(store.read_today().unwrap(), "New note");
+        
+        // Appends still work on top of the replaced content
+        store.append_long_term("User is vegan").unwrap();
+        let lt = store.read_long_term().unwrap();
+        assert!(lt.contains("tea") && lt.contains("vegan") && !lt.contains("coffee"));
+    }
+    
+    #[test]
+    fn test_file_replace_overwrites() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let store = FileMemoryStore::new(tmp.path());
+        
+        store.append_long_term("User likes coffee").unwrap();
+        store.replace_long_term("User likes tea").unwrap();
+        assert_eq!(store.read_long_term().unwrap(), "User likes tea");
+        
+        store.append_today("Old note").unwrap();
+        store.replace_today("New note").unwrap();
+        assert_eq!(store.read_today().unwrap(), "New note");
+    }
+    
+    #[test]
+    fn test_appends_do_not_accumulate_blank_lines() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let file_store = FileMemoryStore::new(tmp.path());
+        let mem_store = InMemoryStore::new();
+        let stores: [&dyn MemoryStore; 2] = [&file_store, &mem_store];
+        
+        for store in stores {
+            for entry in ["- first\n", "- second\n\n", "- third"] {
+                store.append_long_term(entry).unwrap();
+                store.append_today(entry).unwrap();
+            }
+            
+            for content in [store.read_long_term().unwrap(), store.read_today().unwrap()] {
+                assert!(!content.contains("\n\n\n"), "{:?}", content);
+                assert!(content.ends_with("- first\n- second\n- third"), "{:?}", content);
+            }
+        }
+    }
 }
@@ -0,0 +1,126 @@
+//! Prometheus metrics, served by the gateway at `/metrics`
+//!
+//! Metrics live in one process-wide registry, so every agent run and tool
+//! call is counted no matter which channel or context it came from.
+
+use std::sync::OnceLock;
+use std::time::Duration;
+use prometheus::{
+    Encoder, Histogram, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, Opts, Registry, TextEncoder,
+};
+
+/// Upper bounds, in seconds, for agent run latency buckets
+const REQUEST_BUCKETS: &[f64] = &[0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 120.0, 300.0];
+
+/// Upper bounds, in seconds, for tool call latency buckets
+const TOOL_BUCKETS: &[f64] = &[0.01, 0.05, 0.1, 0.5, 1.0, 5.0, 10.0, 30.0, 60.0];
+
+/// All of Leo's metrics
+pub struct Metrics {
+    registry: Registry,
+    /// Agent runs finished, successful or not
+    pub requests: IntCounter,
+    /// Agent run latency
+    pub request_duration: Histogram,
+    /// Failed agent runs, by `Error::kind`
+    pub errors: IntCounterVec,
+    /// Tool calls, by tool and outcome ("ok" or "error")
+    pub tool_calls: IntCounterVec,
+    /// Tool call latency, by tool
+    pub tool_duration: HistogramVec,
+    /// LLM tokens, by direction ("prompt" or "completion")
+    pub llm_tokens: IntCounterVec,
+}
+
+impl Metrics {
+    fn new() -> prometheus::Result<Self> {
+        let registry = Registry::new();
+
+        let requests = IntCounter::new("leo_requests_total", "Agent runs finished")?;
+        let request_duration = Histogram::with_opts(
+            HistogramOpts::new("leo_request_duration_seconds", "Agent run latency")
+                .buckets(REQUEST_BUCKETS.to_vec()),
+        )?;
+        let errors = IntCounterVec::new(
+            Opts::new("leo_errors_total", "Failed agent runs by error type"),
+            &["kind"],
+        )?;
+        let tool_calls = IntCounterVec::new(
+            Opts::new("leo_tool_calls_total", "Tool calls by tool and outcome"),
+            &["tool", "status"],
+        )?;
+        let tool_duration = HistogramVec::new(
+            HistogramOpts::new("leo_tool_duration_seconds", "Tool call latency")
+                .buckets(TOOL_BUCKETS.to_vec()),
+            &["tool"],
+        )?;
+        let llm_tokens = IntCounterVec::new(
+            Opts::new("leo_llm_tokens_total", "LLM tokens by direction"),
+            &["direction"],
+        )?;
+
+        registry.register(Box::new(requests.clone()))?;
+        registry.register(Box::new(request_duration.clone()))?;
+        registry.register(Box::new(errors.clone()))?;
+        registry.register(Box::new(tool_calls.clone()))?;
+        registry.register(Box::new(tool_duration.clone()))?;
+        registry.register(Box::new(llm_tokens.clone()))?;
+
+        Ok(Self { registry, requests, request_duration, errors, tool_calls, tool_duration, llm_tokens })
+    }
+
+    /// Record a finished agent run
+    pub fn record_request(&self, elapsed: Duration, error: Option<&crate::Error>) {
+        self.requests.inc();
+        self.request_duration.observe(elapsed.as_secs_f64());
+        if let Some(error) = error {
+            self.errors.with_label_values(&[error.kind()]).inc();
+        }
+    }
+
+    /// Record a finished tool call
+    pub fn record_tool_call(&self, tool: &str, ok: bool, elapsed: Duration) {
+        let status = if ok { "ok" } else { "error" };
+        self.tool_calls.with_label_values(&[tool, status]).inc();
+        self.tool_duration.with_label_values(&[tool]).observe(elapsed.as_secs_f64());
+    }
+
+    /// Record the token usage of one LLM response
+    pub fn record_tokens(&self, usage: &crate::agent::Usage) {
+        self.llm_tokens.with_label_values(&["prompt"]).inc_by(usage.prompt_tokens as u64);
+        self.llm_tokens.with_label_values(&["completion"]).inc_by(usage.completion_tokens as u64);
+    }
+
+    /// All metrics in the Prometheus text format
+    pub fn render(&self) -> String {
+        let mut buffer = Vec::new();
+        if let Err(e) = TextEncoder::new().encode(&self.registry.gather(), &mut buffer) {
+            tracing::warn!("Failed to encode metrics: {}", e);
+        }
+        String::from_utf8(buffer).unwrap_or_default()
+    }
+}
+
+/// The process-wide metrics
+pub fn metrics() -> &'static Metrics {
+    static METRICS: OnceLock<Metrics> = OnceLock::new();
+    METRICS.get_or_init(|| Metrics::new().expect("metric definitions are valid"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_includes_recorded_metrics() {
+        let metrics = metrics();
+        metrics.record_tool_call("metrics_test_tool", false, Duration::from_millis(20));
+        metrics.record_request(Duration::from_secs(2), Some(&crate::Error::MaxIterations));
+
+        let text = metrics.render();
+        assert!(text.contains(r#"leo_tool_calls_total{status="error",tool="metrics_test_tool"} 1"#));
+        assert!(text.contains(r#"leo_tool_duration_seconds_bucket{tool="metrics_test_tool",le="0.05"} 1"#));
+        assert!(text.contains(r#"leo_errors_total{kind="max_iterations"}"#));
+        assert!(text.contains("leo_request_duration_seconds_count"));
+    }
+}
@@ -0,0 +1,197 @@
+//! Skill installs - fetch a skill from a git repo or zip archive
+//!
+//! The source is cloned or downloaded into a hidden staging directory under
+//! `skills/`, checked like any other skill, then moved to `skills/<name>`.
+
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use serde_json::json;
+use crate::Result;
+use crate::error::Error;
+use crate::tools::{DownloadTool, GitTool, Tool};
+use super::{load_skill_dir, skills_dir, validate_name, Skill, SkillRegistry};
+
+/// Largest total size an archive may unpack to (100 MB)
+const MAX_UNPACKED_BYTES: u64 = 100 * 1024 * 1024;
+
+/// Install the skill at `source` (a git URL, or a zip URL or file) into the workspace
+pub async fn install(workspace: &Path, source: &str) -> Result<Skill> {
+    let skills_dir = skills_dir(workspace);
+    std::fs::create_dir_all(&skills_dir)?;
+    // Hidden, so a failed install is never loaded as a skill
+    let staging = skills_dir.join(format!(".install-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&staging)?;
+
+    let result = install_staged(workspace, &skills_dir, &staging, source).await;
+    let _ = std::fs::remove_dir_all(&staging);
+    result
+}
+
+async fn install_staged(workspace: &Path, skills_dir: &Path, staging: &Path, source: &str) -> Result<Skill> {
+    let fetched = staging.join("skill");
+    if source.ends_with(".zip") {
+        let archive = if Path::new(source).is_file() {
+            PathBuf::from(source)
+        } else {
+            let archive = staging.join("skill.zip");
+            DownloadTool::new(staging.to_path_buf())
+                .execute(json!({ "url": source, "path": archive }))
+                .await?;
+            archive
+        };
+        unpack_zip(&archive, &fetched)?;
+    } else {
+        GitTool::new(staging.to_path_buf())
+            .run_git(&["clone", "--depth", "1", "--", source, "skill"])
+            .await?;
+    }
+
+    let root = find_skill_root(&fetched)
+        .ok_or_else(|| Error::Tool(format!("No SKILL.md found in {}", source)))?;
+    let skill = load_skill_dir(&root)
+        .map_err(|e| Error::Tool(format!("Invalid skill in {}: {}", source, e)))?;
+    validate_name(&skill.name)?;
+
+    let target = skills_dir.join(&skill.name);
+    if let Some(existing) = SkillRegistry::new(workspace).get(&skill.name) {
+        return Err(Error::Config(format!(
+            "Skill '{}' is already installed at {}", skill.name, existing.path.display()
+        )));
+    }
+    if target.exists() {
+        return Err(Error::Config(format!("{} already exists", target.display())));
+    }
+
+    std::fs::rename(&root, &target)?;
+    load_skill_dir(&target).map_err(Error::Tool)
+}
+
+/// Extract `archive` into `dest`, refusing entries that would land outside it
+fn unpack_zip(archive: &Path, dest: &Path) -> Result<()> {
+    let file = std::fs::File::open(archive)?;
+    let mut zip = zip::ZipArchive::new(file)
+        .map_err(|e| Error::Tool(format!("Not a zip archive: {}", e)))?;
+
+    let mut unpacked: u64 = 0;
+    for i in 0..zip.len() {
+        let mut entry = zip.by_index(i)
+            .map_err(|e| Error::Tool(format!("Bad zip entry: {}", e)))?;
+        let Some(name) = entry.enclosed_name().map(Path::to_path_buf) else {
+            return Err(Error::Tool(format!("Zip entry escapes the archive: {}", entry.name())));
+        };
+        let path = dest.join(name);
+        if entry.is_dir() {
+            std::fs::create_dir_all(&path)?;
+            continue;
+        }
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        // Count what is actually written, not the size the header claims
+        let remaining = MAX_UNPACKED_BYTES - unpacked;
+        let mut out = std::fs::File::create(&path)?;
+        unpacked += std::io::copy(&mut (&mut entry).take(remaining + 1), &mut out)?;
+        if unpacked > MAX_UNPACKED_BYTES {
+            return Err(Error::Tool(format!(
+                "Archive unpacks to more than {} bytes", MAX_UNPACKED_BYTES
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// `dir` itself if it holds SKILL.md, or its only subdirectory that does
+/// (archives often wrap everything in a `repo-main/` folder)
+fn find_skill_root(dir: &Path) -> Option<PathBuf> {
+    if dir.join("SKILL.md").is_file() {
+        return Some(dir.to_path_buf());
+    }
+    let mut candidates = std::fs::read_dir(dir).ok()?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.join("SKILL.md").is_file());
+    match (candidates.next(), candidates.next()) {
+        (Some(root), None) => Some(root),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    const SKILL_MD: &str = "---\nname: weather\ndescription: Get weather\n---\n\n# Weather\n";
+
+    fn write_zip(path: &Path, entries: &[(&str, &str)]) {
+        let mut zip = zip::ZipWriter::new(std::fs::File::create(path).unwrap());
+        for (name, content) in entries {
+            zip.start_file(*name, zip::write::FileOptions::default()).unwrap();
+            zip.write_all(content.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_install_from_zip() {
+        let tmp = TempDir::new().unwrap();
+        let archive = tmp.path().join("weather.zip");
+        write_zip(&archive, &[("weather-main/SKILL.md", SKILL_MD), ("weather-main/run.sh", "echo hi")]);
+
+        let workspace = tmp.path().join("ws");
+        let skill = install(&workspace, archive.to_str().unwrap()).await.unwrap();
+        assert_eq!(skill.name, "weather");
+        assert_eq!(skill.path, workspace.join("skills/weather"));
+        assert!(skill.path.join("run.sh").is_file());
+
+        // Installing again collides, and no staging directory is left behind
+        let err = install(&workspace, archive.to_str().unwrap()).await.unwrap_err();
+        assert!(err.to_string().contains("already installed"));
+        assert_eq!(std::fs::read_dir(workspace.join("skills")).unwrap().count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_install_rejects_bad_archives() {
+        let tmp = TempDir::new().unwrap();
+        let workspace = tmp.path().join("ws");
+
+        let escaping = tmp.path().join("escape.zip");
+        write_zip(&escaping, &[("../evil.sh", "rm -rf ~"), ("SKILL.md", SKILL_MD)]);
+        let err = install(&workspace, escaping.to_str().unwrap()).await.unwrap_err();
+        assert!(err.to_string().contains("escapes"));
+        assert!(!tmp.path().join("evil.sh").exists());
+
+        let no_skill = tmp.path().join("empty.zip");
+        write_zip(&no_skill, &[("README.md", "hi")]);
+        let err = install(&workspace, no_skill.to_str().unwrap()).await.unwrap_err();
+        assert!(err.to_string().contains("No SKILL.md"));
+    }
+
+    #[tokio::test]
+    async fn test_install_from_git() {
+        let tmp = TempDir::new().unwrap();
+        let repo = tmp.path().join("repo");
+        std::fs::create_dir_all(&repo).unwrap();
+        std::fs::write(repo.join("SKILL.md"), SKILL_MD).unwrap();
+        let git = |args: &[&str]| {
+            let status = std::process::Command::new("git")
+                .args(["-c", "user.name=t", "-c", "user.email=t@t"])
+                .args(args)
+                .current_dir(&repo)
+                .output()
+                .unwrap()
+                .status;
+            assert!(status.success());
+        };
+        git(&["init", "-q"]);
+        git(&["add", "."]);
+        git(&["commit", "-qm", "init"]);
+
+        let workspace = tmp.path().join("ws");
+        let skill = install(&workspace, repo.to_str().unwrap()).await.unwrap();
+        assert_eq!(skill.name, "weather");
+        assert!(workspace.join("skills/weather/SKILL.md").is_file());
+    }
+}
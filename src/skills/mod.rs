@@ -1,8 +1,14 @@
 //! Skills module - extend agent capabilities
 
+mod install;
+
+pub use install::install;
+
 use std::path::{Path, PathBuf};
 use std::collections::HashMap;
+use std::sync::Mutex;
 use serde::{Deserialize, Serialize};
+use crate::agent::LlmClient;
 
 /// Skill metadata from SKILL.md frontmatter
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -11,6 +17,10 @@ pub struct SkillMetadata {
     pub description: String,
     #[serde(default)]
     pub requires: Vec<String>,
+    #[serde(default)]
+    pub scripts: Vec<String>,
+    #[serde(default)]
+    pub parameters: Vec<String>,
 }
 
 /// A loaded skill
@@ -19,28 +29,132 @@ pub struct Skill {
     pub name: String,
     pub description: String,
     pub requires: Vec<String>,
+    /// Script files in the skill's directory it may run (see `SkillTool`)
+    pub scripts: Vec<String>,
+    /// Names filled into `{{name}}` placeholders in `content` when loaded
+    pub parameters: Vec<String>,
     pub content: String,
     pub path: PathBuf,
 }
 
+impl Skill {
+    /// The skill's instructions with `args` substituted for its parameters
+    ///
+    /// Only `{{name}}` placeholders naming a declared parameter are filled in;
+    /// any other braces (e.g. in code samples) are left as written.
+    pub fn render(&self, args: &serde_json::Map<String, serde_json::Value>) -> std::result::Result<String, String> {
+        let mut output = String::with_capacity(self.content.len());
+        let mut missing: Vec<&str> = Vec::new();
+        let mut rest = self.content.as_str();
+
+        while let Some(start) = rest.find("{{") {
+            let Some(len) = rest[start + 2..].find("}}") else {
+                break;
+            };
+            let name = rest[start + 2..start + 2 + len].trim();
+            if !self.parameters.iter().any(|p| p == name) {
+                output.push_str(&rest[..start + 2]);
+                rest = &rest[start + 2..];
+                continue;
+            }
+            output.push_str(&rest[..start]);
+            match args.get(name) {
+                Some(serde_json::Value::String(s)) => output.push_str(s),
+                Some(value) => output.push_str(&value.to_string()),
+                None if !missing.contains(&name) => missing.push(name),
+                None => {}
+            }
+            rest = &rest[start + 2 + len + 2..];
+        }
+        output.push_str(rest);
+
+        if missing.is_empty() {
+            Ok(output)
+        } else {
+            Err(format!("Missing skill arguments: {}", missing.join(", ")))
+        }
+    }
+}
+
+/// SKILL.md written by `scaffold`; `{name}` is replaced with the skill's name
+const SKILL_TEMPLATE: &str = "---
+name: {name}
+description: One line saying when to use this skill
+requires: []
+---
+
+# {name}
+
+Step-by-step instructions for the agent. Name the tools to use
+(e.g. `web_fetch`, `exec`) and list them under `requires` above.
+";
+
+/// Directory skills are loaded from
+pub fn skills_dir(workspace: &Path) -> PathBuf {
+    workspace.join("skills")
+}
+
+/// Skill names double as directory names, so keep them to a safe character set
+fn validate_name(name: &str) -> crate::Result<()> {
+    let valid = !name.is_empty()
+        && name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(crate::error::Error::Config(format!(
+            "Invalid skill name '{}': use lowercase letters, digits, '-' and '_'", name
+        )))
+    }
+}
+
+/// Create `<skills_dir>/<name>/SKILL.md` from a template, returning its path
+pub fn scaffold(skills_dir: &Path, name: &str) -> crate::Result<PathBuf> {
+    validate_name(name)?;
+    let dir = skills_dir.join(name);
+    if dir.exists() {
+        return Err(crate::error::Error::Config(format!("Skill '{}' already exists at {}", name, dir.display())));
+    }
+    std::fs::create_dir_all(&dir)?;
+    let skill_md = dir.join("SKILL.md");
+    std::fs::write(&skill_md, SKILL_TEMPLATE.replace("{name}", name))?;
+    Ok(skill_md)
+}
+
+/// Load the skill in `path` (a directory holding SKILL.md), or say why it can't be
+pub fn load_skill_dir(path: &Path) -> std::result::Result<Skill, String> {
+    let skill_md = path.join("SKILL.md");
+    if !skill_md.exists() {
+        return Err("no SKILL.md".to_string());
+    }
+    let content = std::fs::read_to_string(&skill_md)
+        .map_err(|e| format!("can't read SKILL.md: {}", e))?;
+    parse_skill(&content, path)
+}
+
 /// Skill registry - manages available skills
 pub struct SkillRegistry {
     skills: HashMap<String, Skill>,
+    /// Skill directories that failed to load, with the reason
+    diagnostics: Vec<(PathBuf, String)>,
+    /// Embeddings of skill descriptions, computed on first selection
+    embeddings: Mutex<HashMap<String, Vec<f32>>>,
 }
 
 impl SkillRegistry {
     /// Create a new skill registry and load skills from workspace
     pub fn new(workspace: &Path) -> Self {
-        let mut registry = Self {
-            skills: HashMap::new(),
-        };
-        registry.load_from_directory(&workspace.join("skills"));
+        let mut registry = Self::empty();
+        registry.load_from_directory(&skills_dir(workspace));
         registry
     }
     
     /// Create an empty registry for testing
     pub fn empty() -> Self {
-        Self { skills: HashMap::new() }
+        Self {
+            skills: HashMap::new(),
+            diagnostics: Vec::new(),
+            embeddings: Mutex::new(HashMap::new()),
+        }
     }
     
     /// Load skills from a directory
@@ -49,35 +163,52 @@ impl SkillRegistry {
             return;
         }
         
-        let Ok(entries) = std::fs::read_dir(path) else {
-            return;
+        let entries = match std::fs::read_dir(path) {
+            Ok(entries) => entries,
+            Err(e) => {
+                self.warn(path, format!("can't read skills directory: {}", e));
+                return;
+            }
         };
         
-        for entry in entries.filter_map(|e| e.ok()) {
-            let skill_path = entry.path();
-            if skill_path.is_dir() {
-                self.load_skill(&skill_path);
-            }
+        let mut dirs: Vec<PathBuf> = entries
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| p.is_dir())
+            // Hidden directories hold in-progress installs (and VCS metadata)
+            .filter(|p| !p.file_name().is_some_and(|n| n.to_string_lossy().starts_with('.')))
+            .collect();
+        dirs.sort();
+        for skill_path in dirs {
+            self.load_skill(&skill_path);
         }
     }
     
     /// Load a single skill from its directory
     fn load_skill(&mut self, path: &Path) {
-        let skill_md = path.join("SKILL.md");
-        if !skill_md.exists() {
-            return;
-        }
-        
-        let Ok(content) = std::fs::read_to_string(&skill_md) else {
-            return;
-        };
-        
-        // Parse YAML frontmatter
-        if let Some(skill) = parse_skill(&content, path) {
-            self.skills.insert(skill.name.clone(), skill);
+        match load_skill_dir(path) {
+            Ok(skill) => {
+                if let Some(existing) = self.skills.get(&skill.name) {
+                    let reason = format!("name '{}' is already used by {}", skill.name, existing.path.display());
+                    self.warn(path, reason);
+                } else {
+                    self.skills.insert(skill.name.clone(), skill);
+                }
+            }
+            Err(reason) => self.warn(path, reason),
         }
     }
     
+    fn warn(&mut self, path: &Path, reason: String) {
+        tracing::warn!("Skipping skill {}: {}", path.display(), reason);
+        self.diagnostics.push((path.to_path_buf(), reason));
+    }
+    
+    /// Skill directories that failed to load, with the reason, in load order
+    pub fn diagnostics(&self) -> &[(PathBuf, String)] {
+        &self.diagnostics
+    }
+    
     /// Get a skill by name
     pub fn get(&self, name: &str) -> Option<&Skill> {
         self.skills.get(name)
@@ -88,6 +219,63 @@ impl SkillRegistry {
         self.skills.keys().map(|s| s.as_str()).collect()
     }
     
+    /// All loaded skills, sorted by name
+    pub fn skills(&self) -> Vec<&Skill> {
+        let mut skills: Vec<&Skill> = self.skills.values().collect();
+        skills.sort_by(|a, b| a.name.cmp(&b.name));
+        skills
+    }
+    
+    /// Number of loaded skills
+    pub fn len(&self) -> usize {
+        self.skills.len()
+    }
+    
+    /// Whether no skills are loaded
+    pub fn is_empty(&self) -> bool {
+        self.skills.is_empty()
+    }
+    
+    /// Pick the `k` skills whose descriptions are most similar to `query`
+    ///
+    /// Falls back to every skill when the client can't produce embeddings.
+    pub async fn select_relevant<C: LlmClient + ?Sized>(&self, client: &C, query: &str, k: usize) -> Vec<&Skill> {
+        let mut all: Vec<&Skill> = self.skills.values().collect();
+        all.sort_by(|a, b| a.name.cmp(&b.name));
+        if all.len() <= k {
+            return all;
+        }
+        
+        // Embed the query along with any skill descriptions not cached yet
+        let missing: Vec<&Skill> = {
+            let cache = self.embeddings.lock().unwrap();
+            all.iter().filter(|s| !cache.contains_key(&s.name)).copied().collect()
+        };
+        let mut texts = vec![query.to_string()];
+        texts.extend(missing.iter().map(|s| format!("{}: {}", s.name, s.description)));
+        
+        let vectors = match client.embed(&texts).await {
+            Ok(vectors) if vectors.len() == texts.len() => vectors,
+            Ok(_) | Err(_) => {
+                tracing::debug!("Skill embeddings unavailable, listing all skills");
+                return all;
+            }
+        };
+        
+        let mut vectors = vectors.into_iter();
+        let query_vec = vectors.next().unwrap_or_default();
+        let mut cache = self.embeddings.lock().unwrap();
+        for (skill, vector) in missing.iter().zip(vectors) {
+            cache.insert(skill.name.clone(), vector);
+        }
+        
+        let mut scored: Vec<(f32, &Skill)> = all.into_iter()
+            .map(|s| (cache.get(&s.name).map(|v| cosine_similarity(&query_vec, v)).unwrap_or(0.0), s))
+            .collect();
+        scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+        scored.into_iter().take(k).map(|(_, s)| s).collect()
+    }
+    
     /// Check if a skill's requirements are met
     pub fn is_available(&self, name: &str, available_tools: &[&str]) -> bool {
         if let Some(skill) = self.skills.get(name) {
@@ -98,19 +286,61 @@ impl SkillRegistry {
     }
     
     /// Build skills summary for system prompt
-    pub fn build_summary(&self) -> String {
-        if self.skills.is_empty() {
+    ///
+    /// Skills needing a tool missing from `available_tools` are marked disabled,
+    /// or left out entirely when `hide_unavailable` is set.
+    pub fn build_summary(&self, available_tools: &[&str], hide_unavailable: bool) -> String {
+        Self::render_summary(self.skills.values(), available_tools, hide_unavailable)
+    }
+    
+    /// Build skills summary restricted to the named skills
+    pub fn build_summary_for(&self, names: &[String], available_tools: &[&str], hide_unavailable: bool) -> String {
+        Self::render_summary(names.iter().filter_map(|n| self.skills.get(n)), available_tools, hide_unavailable)
+    }
+    
+    fn render_summary<'a>(
+        skills: impl Iterator<Item = &'a Skill>,
+        available_tools: &[&str],
+        hide_unavailable: bool,
+    ) -> String {
+        let mut skills = skills
+            .map(|skill| {
+                let missing: Vec<&str> = skill.requires.iter()
+                    .map(|r| r.as_str())
+                    .filter(|r| !available_tools.contains(r))
+                    .collect();
+                (skill, missing)
+            })
+            .filter(|(_, missing)| !hide_unavailable || missing.is_empty())
+            .peekable();
+        if skills.peek().is_none() {
             return String::new();
         }
         
         let mut lines = vec!["<skills>".to_string()];
         
-        for skill in self.skills.values() {
-            lines.push(format!("  <skill name=\"{}\">", skill.name));
+        for (skill, missing) in skills {
+            if missing.is_empty() {
+                lines.push(format!("  <skill name=\"{}\">", skill.name));
+            } else {
+                lines.push(format!("  <skill name=\"{}\" disabled=\"true\">", skill.name));
+            }
             lines.push(format!("    <description>{}</description>", skill.description));
             if !skill.requires.is_empty() {
                 lines.push(format!("    <requires>{}</requires>", skill.requires.join(", ")));
             }
+            if !skill.parameters.is_empty() {
+                lines.push(format!("    <parameters>{}</parameters>", skill.parameters.join(", ")));
+            }
+            if !skill.scripts.is_empty() {
+                lines.push(format!("    <scripts>{}</scripts>", skill.scripts.join(", ")));
+            }
+            if !missing.is_empty() {
+                lines.push(format!(
+                    "    <unavailable>missing tools: {} - do not use this skill</unavailable>",
+                    missing.join(", ")
+                ));
+            }
             lines.push("  </skill>".to_string());
         }
         
@@ -119,16 +349,27 @@ impl SkillRegistry {
     }
 }
 
+/// Cosine similarity of two vectors (0 when either is empty or zero)
+fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a: f32 = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b: f32 = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot / (norm_a * norm_b)
+}
+
 /// Parse skill from SKILL.md content
-fn parse_skill(content: &str, path: &Path) -> Option<Skill> {
+fn parse_skill(content: &str, path: &Path) -> std::result::Result<Skill, String> {
     // Check for YAML frontmatter
     if !content.starts_with("---") {
-        return None;
+        return Err("SKILL.md must start with a '---' frontmatter block".to_string());
     }
     
     // Find end of frontmatter
     let rest = &content[3..];
-    let end_idx = rest.find("\n---")?;
+    let end_idx = rest.find("\n---").ok_or("frontmatter is missing its closing '---'")?;
     let frontmatter = &rest[..end_idx];
     let body = &rest[end_idx + 5..];
     
@@ -136,6 +377,16 @@ fn parse_skill(content: &str, path: &Path) -> Option<Skill> {
     let mut name = None;
     let mut description = None;
     let mut requires = Vec::new();
+    let mut scripts = Vec::new();
+    let mut parameters = Vec::new();
+    // Parse array-like: [a, b, c] or just comma-separated
+    let parse_list = |value: &str| -> Vec<String> {
+        value.trim_matches(['[', ']', ' '].as_ref())
+            .split(',')
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect()
+    };
     
     for line in frontmatter.lines() {
         let line = line.trim();
@@ -145,23 +396,21 @@ fn parse_skill(content: &str, path: &Path) -> Option<Skill> {
             match key {
                 "name" => name = Some(value.to_string()),
                 "description" => description = Some(value.to_string()),
-                "requires" => {
-                    // Parse array-like: [a, b, c] or just comma-separated
-                    let value = value.trim_matches(['[', ']', ' '].as_ref());
-                    requires = value.split(',')
-                        .map(|s| s.trim().to_string())
-                        .filter(|s| !s.is_empty())
-                        .collect();
-                }
+                "requires" => requires = parse_list(value),
+                "scripts" => scripts = parse_list(value),
+                "parameters" => parameters = parse_list(value),
                 _ => {}
             }
         }
     }
     
-    Some(Skill {
-        name: name?,
+    let name = name.filter(|n| !n.is_empty()).ok_or("frontmatter has no 'name'")?;
+    Ok(Skill {
+        name,
         description: description.unwrap_or_default(),
         requires,
+        scripts,
+        parameters,
         content: body.trim().to_string(),
         path: path.to_path_buf(),
     })
@@ -170,6 +419,40 @@ fn parse_skill(content: &str, path: &Path) -> Option<Skill> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use async_trait::async_trait;
+    use crate::agent::{LlmResponse, Message};
+    use crate::tools::ToolDefinition;
+    
+    /// Embeds text as keyword counts over a tiny vocabulary
+    struct KeywordEmbedder;
+    
+    #[async_trait]
+    impl LlmClient for KeywordEmbedder {
+        async fn chat(&self, _messages: &[Message], _tools: &[ToolDefinition]) -> crate::Result<LlmResponse> {
+            Ok(LlmResponse::text(""))
+        }
+        
+        fn default_model(&self) -> &str { "keywords" }
+        
+        async fn embed(&self, texts: &[String]) -> crate::Result<Vec<Vec<f32>>> {
+            let vocab = ["weather", "git", "email", "calendar"];
+            Ok(texts.iter()
+                .map(|t| vocab.iter().map(|w| t.to_lowercase().matches(w).count() as f32).collect())
+                .collect())
+        }
+    }
+    
+    fn skill(name: &str, description: &str) -> Skill {
+        Skill {
+            name: name.to_string(),
+            description: description.to_string(),
+            requires: vec![],
+            scripts: vec![],
+            parameters: vec![],
+            content: String::new(),
+            path: PathBuf::new(),
+        }
+    }
     
     #[test]
     fn test_parse_skill() {
@@ -177,6 +460,7 @@ mod tests {
 name: weather
 description: Get weather information
 requires: [web_fetch]
+scripts: [setup.sh, run.sh]
 ---
 
 # Weather Skill
@@ -188,7 +472,74 @@ Use this skill to get weather information.
         assert_eq!(skill.name, "weather");
         assert_eq!(skill.description, "Get weather information");
         assert_eq!(skill.requires, vec!["web_fetch"]);
+        assert_eq!(skill.scripts, vec!["setup.sh", "run.sh"]);
         assert!(skill.content.contains("Weather Skill"));
+        
+        let path = PathBuf::from("/test");
+        assert!(parse_skill("# No frontmatter", &path).unwrap_err().contains("must start"));
+        assert!(parse_skill("---\nname: x\n", &path).unwrap_err().contains("closing"));
+        assert!(parse_skill("---\ndescription: x\n---\n", &path).unwrap_err().contains("no 'name'"));
+    }
+    
+    #[test]
+    fn test_render_skill_arguments() {
+        let content = "---\nname: email-draft\ndescription: Draft an email\nparameters: [recipient, tone]\n---\n\nWrite to {{recipient}} in a {{ tone }} tone.";
+        let draft = parse_skill(content, &PathBuf::from("/test")).unwrap();
+        assert_eq!(draft.parameters, vec!["recipient", "tone"]);
+        
+        let args = serde_json::json!({ "recipient": "Sam", "tone": "friendly" });
+        assert_eq!(draft.render(args.as_object().unwrap()).unwrap(), "Write to Sam in a friendly tone.");
+        
+        let args = serde_json::json!({ "recipient": "Sam" });
+        assert!(draft.render(args.as_object().unwrap()).unwrap_err().contains("tone"));
+        
+        // Undeclared placeholders, e.g. in a code sample, are kept
+        let mut sample = draft.clone();
+        sample.content = "Hi {{recipient}}, run `echo {{ .Name }}` and {{{{recipient}}}}".to_string();
+        let args = serde_json::json!({ "recipient": "Sam" });
+        assert_eq!(sample.render(args.as_object().unwrap()).unwrap(), "Hi Sam, run `echo {{ .Name }}` and {{Sam}}");
+        
+        // Without declared parameters the content is left alone
+        let mut plain = skill("plain", "");
+        plain.content = "Use {{name}} literally".to_string();
+        assert_eq!(plain.render(&serde_json::Map::new()).unwrap(), "Use {{name}} literally");
+    }
+    
+    #[test]
+    fn test_scaffold_skill() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let skill_md = scaffold(tmp.path(), "daily-report").unwrap();
+        assert_eq!(skill_md, tmp.path().join("daily-report/SKILL.md"));
+        
+        let skill = load_skill_dir(&tmp.path().join("daily-report")).unwrap();
+        assert_eq!(skill.name, "daily-report");
+        assert!(skill.requires.is_empty());
+        
+        assert!(scaffold(tmp.path(), "daily-report").is_err());
+        assert!(scaffold(tmp.path(), "../escape").is_err());
+        assert!(load_skill_dir(tmp.path()).unwrap_err().contains("no SKILL.md"));
+    }
+    
+    #[test]
+    fn test_registry_diagnostics() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let dir = skills_dir(tmp.path());
+        scaffold(&dir, "good").unwrap();
+        std::fs::create_dir_all(dir.join("empty")).unwrap();
+        std::fs::create_dir_all(dir.join("broken")).unwrap();
+        std::fs::write(dir.join("broken/SKILL.md"), "# Just markdown").unwrap();
+        std::fs::create_dir_all(dir.join("zcopy")).unwrap();
+        std::fs::copy(dir.join("good/SKILL.md"), dir.join("zcopy/SKILL.md")).unwrap();
+        
+        let registry = SkillRegistry::new(tmp.path());
+        assert_eq!(registry.list(), vec!["good"]);
+        assert_eq!(registry.get("good").unwrap().path, dir.join("good"));
+        let diagnostics = registry.diagnostics();
+        assert_eq!(diagnostics.len(), 3);
+        assert_eq!(diagnostics[0].0, dir.join("broken"));
+        assert!(diagnostics[0].1.contains("frontmatter"));
+        assert_eq!(diagnostics[1].0, dir.join("empty"));
+        assert!(diagnostics[2].1.contains("already used"));
     }
     
     #[test]
@@ -198,13 +549,59 @@ Use this skill to get weather information.
             name: "test".to_string(),
             description: "A test skill".to_string(),
             requires: vec![],
+            scripts: vec![],
+            parameters: vec![],
             content: String::new(),
             path: PathBuf::new(),
         });
         
-        let summary = registry.build_summary();
+        let summary = registry.build_summary(&[], false);
         assert!(summary.contains("<skills>"));
         assert!(summary.contains("test"));
         assert!(summary.contains("A test skill"));
     }
+    
+    #[test]
+    fn test_skill_summary_marks_missing_tools() {
+        let mut registry = SkillRegistry::empty();
+        registry.skills.insert("notes".to_string(), skill("notes", "Take notes"));
+        let mut mail = skill("mail", "Send email");
+        mail.requires = vec!["read_file".to_string(), "gmail".to_string()];
+        registry.skills.insert("mail".to_string(), mail);
+        let tools = ["read_file", "write_file"];
+        
+        assert!(!registry.is_available("mail", &tools));
+        let summary = registry.build_summary(&tools, false);
+        assert!(summary.contains("<skill name=\"mail\" disabled=\"true\">"));
+        assert!(summary.contains("missing tools: gmail"));
+        assert!(summary.contains("<skill name=\"notes\">"));
+        
+        let hidden = registry.build_summary(&tools, true);
+        assert!(!hidden.contains("mail"));
+        assert!(hidden.contains("notes"));
+        
+        let only_mail = vec!["mail".to_string()];
+        assert_eq!(registry.build_summary_for(&only_mail, &tools, true), "");
+    }
+    
+    #[tokio::test]
+    async fn test_select_relevant_skills() {
+        let mut registry = SkillRegistry::empty();
+        for (name, description) in [
+            ("forecast", "Check the weather"),
+            ("review", "Review git changes"),
+            ("inbox", "Triage email"),
+            ("agenda", "Read the calendar"),
+        ] {
+            registry.skills.insert(name.to_string(), skill(name, description));
+        }
+        
+        let selected = registry.select_relevant(&KeywordEmbedder, "what's in my git diff?", 1).await;
+        assert_eq!(selected.len(), 1);
+        assert_eq!(selected[0].name, "review");
+        
+        // Without embeddings every skill is kept
+        let fallback = registry.select_relevant(&crate::agent::llm::FakeLlmClient::new(vec![]), "git", 1).await;
+        assert_eq!(fallback.len(), 4);
+    }
 }
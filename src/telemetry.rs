@@ -0,0 +1,148 @@
+//! Logging setup, with optional OpenTelemetry trace export
+//!
+//! Logs always go to stderr, filtered by `RUST_LOG`. When an OTLP endpoint is
+//! given (`--otlp` or `LEO_OTLP_ENDPOINT`) and Leo was built with the `otlp`
+//! feature, the `agent_run`, `iteration`, `llm_call` and `tool_call` spans are
+//! also exported as distributed traces over OTLP/HTTP.
+
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Layer as _};
+
+/// Environment variable read when `--otlp` isn't passed
+pub const OTLP_ENDPOINT_ENV: &str = "LEO_OTLP_ENDPOINT";
+
+/// Keeps trace export running; flushes pending spans when dropped
+#[derive(Default)]
+pub struct Telemetry {
+    #[cfg(feature = "otlp")]
+    provider: Option<opentelemetry_sdk::trace::SdkTracerProvider>,
+}
+
+impl Drop for Telemetry {
+    fn drop(&mut self) {
+        #[cfg(feature = "otlp")]
+        if let Some(provider) = self.provider.take() {
+            if let Err(e) = provider.shutdown() {
+                eprintln!("Failed to flush traces: {}", e);
+            }
+        }
+    }
+}
+
+/// The OTLP endpoint to export to: the flag, else the environment variable
+pub fn otlp_endpoint(flag: Option<String>) -> Option<String> {
+    flag.or_else(|| std::env::var(OTLP_ENDPOINT_ENV).ok())
+        .filter(|endpoint| !endpoint.trim().is_empty())
+}
+
+/// Install the global subscriber. Keep the returned value alive until exit
+pub fn init(otlp_endpoint: Option<&str>) -> Telemetry {
+    let fmt = tracing_subscriber::fmt::layer().with_filter(EnvFilter::from_default_env());
+    let registry = tracing_subscriber::registry().with(fmt);
+
+    let Some(endpoint) = otlp_endpoint else {
+        registry.init();
+        return Telemetry::default();
+    };
+
+    #[cfg(feature = "otlp")]
+    {
+        match otlp::tracer_provider(endpoint) {
+            Ok(provider) => {
+                registry.with(otlp::layer(&provider)).init();
+                tracing::info!("Exporting traces to {}", otlp::traces_url(endpoint));
+                Telemetry { provider: Some(provider) }
+            }
+            Err(e) => {
+                registry.init();
+                tracing::warn!("OpenTelemetry export disabled: {}", e);
+                Telemetry::default()
+            }
+        }
+    }
+
+    #[cfg(not(feature = "otlp"))]
+    {
+        registry.init();
+        tracing::warn!(
+            "Ignoring OTLP endpoint {}: Leo was built without the `otlp` feature",
+            endpoint
+        );
+        Telemetry::default()
+    }
+}
+
+#[cfg(feature = "otlp")]
+mod otlp {
+    use opentelemetry::trace::TracerProvider as _;
+    use opentelemetry_otlp::{SpanExporter, WithExportConfig};
+    use opentelemetry_sdk::trace::SdkTracerProvider;
+    use opentelemetry_sdk::Resource;
+    use tracing::Subscriber;
+    use tracing_subscriber::filter::{LevelFilter, Targets};
+    use tracing_subscriber::registry::LookupSpan;
+    use tracing_subscriber::Layer;
+
+    /// Path the OTLP/HTTP collector receives traces on
+    const TRACES_PATH: &str = "/v1/traces";
+
+    /// Full traces URL for a collector base URL like `http://localhost:4318`
+    pub fn traces_url(endpoint: &str) -> String {
+        let endpoint = endpoint.trim().trim_end_matches('/');
+        if endpoint.ends_with(TRACES_PATH) {
+            endpoint.to_string()
+        } else {
+            format!("{}{}", endpoint, TRACES_PATH)
+        }
+    }
+
+    pub fn tracer_provider(endpoint: &str) -> Result<SdkTracerProvider, String> {
+        let exporter = SpanExporter::builder()
+            .with_http()
+            .with_endpoint(traces_url(endpoint))
+            .build()
+            .map_err(|e| e.to_string())?;
+
+        Ok(SdkTracerProvider::builder()
+            .with_batch_exporter(exporter)
+            .with_resource(Resource::builder().with_service_name("leo").build())
+            .build())
+    }
+
+    /// Exports Leo's own spans, independent of `RUST_LOG`
+    pub fn layer<S>(provider: &SdkTracerProvider) -> impl Layer<S>
+    where
+        S: Subscriber + for<'span> LookupSpan<'span>,
+    {
+        tracing_opentelemetry::layer()
+            .with_tracer(provider.tracer("leo"))
+            .with_filter(Targets::new().with_target("leo", LevelFilter::INFO))
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_traces_url() {
+            assert_eq!(traces_url("http://localhost:4318"), "http://localhost:4318/v1/traces");
+            assert_eq!(traces_url("http://localhost:4318/"), "http://localhost:4318/v1/traces");
+            assert_eq!(traces_url("https://otel.example.com/v1/traces"), "https://otel.example.com/v1/traces");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_otlp_endpoint_prefers_flag() {
+        assert_eq!(
+            otlp_endpoint(Some("http://collector:4318".to_string())),
+            Some("http://collector:4318".to_string())
+        );
+        assert_eq!(otlp_endpoint(Some("  ".to_string())), None);
+    }
+}
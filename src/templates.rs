@@ -35,6 +35,45 @@ pub fn bootstrap_workspace(workspace: &std::path::Path) -> std::io::Result<()> {
     Ok(())
 }
 
+/// Substitute `{{name}}` placeholders with values from `vars`.
+///
+/// Whitespace inside the braces is ignored and non-string values are inserted
+/// as JSON. An unclosed `{{` is kept as-is. Returns the names of any
+/// placeholders without a value as the error.
+pub fn render(
+    template: &str,
+    vars: &serde_json::Map<String, serde_json::Value>,
+) -> Result<String, Vec<String>> {
+    let mut output = String::with_capacity(template.len());
+    let mut missing: Vec<String> = Vec::new();
+    let mut rest = template;
+
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        output.push_str(&rest[..start]);
+        let name = rest[start + 2..start + 2 + len].trim();
+        match vars.get(name) {
+            Some(serde_json::Value::String(s)) => output.push_str(s),
+            Some(value) => output.push_str(&value.to_string()),
+            None => {
+                if !missing.iter().any(|m| m == name) {
+                    missing.push(name.to_string());
+                }
+            }
+        }
+        rest = &rest[start + 2 + len + 2..];
+    }
+    output.push_str(rest);
+
+    if missing.is_empty() {
+        Ok(output)
+    } else {
+        Err(missing)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -47,6 +86,22 @@ mod tests {
         assert!(!MEMORY.is_empty());
     }
 
+    #[test]
+    fn test_render() {
+        let vars = serde_json::json!({ "name": "Leo", "count": 3 });
+        let vars = vars.as_object().unwrap();
+
+        assert_eq!(
+            render("Hi {{name}}, {{ count }} new {{name}}", vars).unwrap(),
+            "Hi Leo, 3 new Leo"
+        );
+        assert_eq!(render("open {{ brace", vars).unwrap(), "open {{ brace");
+        assert_eq!(
+            render("{{a}} {{name}} {{b}} {{a}}", vars).unwrap_err(),
+            vec!["a".to_string(), "b".to_string()]
+        );
+    }
+
     #[test]
     fn test_templates_have_headers() {
         assert!(AGENTS.contains("# AGENTS.md"));
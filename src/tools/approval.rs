@@ -0,0 +1,15 @@
+//! Approval - let the user confirm tool calls that change things
+//!
+//! When a handler is set on the `ToolRunner`, every call whose tool reports
+//! `is_mutating` waits for `approve` before it runs. Channels provide the
+//! handler (e.g. Telegram asks with Yes/No buttons).
+
+use async_trait::async_trait;
+
+/// Asks the user whether a mutating tool call may run
+#[async_trait]
+pub trait ApprovalHandler: Send + Sync {
+    /// `preview` is the call's arguments as compact JSON, long values shortened.
+    /// Return `false` to block the call.
+    async fn approve(&self, tool: &str, preview: &str) -> bool;
+}
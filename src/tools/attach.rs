@@ -0,0 +1,113 @@
+//! Attach tool - files to deliver alongside the agent's reply
+
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use crate::Result;
+use crate::error::Error;
+use super::{Tool, resolve_path};
+
+/// Largest file that can be attached (Telegram's bot upload limit)
+const MAX_ATTACHMENT_BYTES: u64 = 50 * 1024 * 1024;
+
+/// Files queued for the current reply, shared between the tool and the agent loop
+#[derive(Clone, Default)]
+pub struct Attachments(Arc<Mutex<Vec<PathBuf>>>);
+
+impl Attachments {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Remove and return everything queued so far
+    pub fn take(&self) -> Vec<PathBuf> {
+        std::mem::take(&mut *self.0.lock().unwrap())
+    }
+
+    fn push(&self, path: PathBuf) -> usize {
+        let mut files = self.0.lock().unwrap();
+        if !files.contains(&path) {
+            files.push(path);
+        }
+        files.len()
+    }
+}
+
+/// Attach a file to the reply
+pub struct AttachTool {
+    workspace: PathBuf,
+    attachments: Attachments,
+}
+
+impl AttachTool {
+    pub fn new(workspace: PathBuf, attachments: Attachments) -> Self {
+        Self { workspace, attachments }
+    }
+}
+
+#[async_trait]
+impl Tool for AttachTool {
+    fn name(&self) -> &str { "attach_file" }
+    fn description(&self) -> &str {
+        "Attach a file to your reply so the user receives it (e.g. as a document on Telegram). \
+         Create the file first, then attach it"
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "File to send with the reply"
+                }
+            },
+            "required": ["path"]
+        })
+    }
+
+    async fn execute(&self, params: Value) -> Result<String> {
+        let path = params.get("path")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| Error::Tool("Missing 'path' parameter".to_string()))?;
+
+        let file = resolve_path(&self.workspace, path);
+        let meta = std::fs::metadata(&file)
+            .map_err(|_| Error::Tool(format!("No such file: {}", file.display())))?;
+        if !meta.is_file() {
+            return Err(Error::Tool(format!("{} is not a file", file.display())));
+        }
+        if meta.len() > MAX_ATTACHMENT_BYTES {
+            return Err(Error::Tool(format!(
+                "{} is {} MB, over the {} MB attachment limit",
+                file.display(), meta.len() / (1024 * 1024), MAX_ATTACHMENT_BYTES / (1024 * 1024)
+            )));
+        }
+
+        let count = self.attachments.push(file.clone());
+        Ok(format!("Attached {} ({} file(s) will be sent with your reply)", file.display(), count))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_attach_file() {
+        let tmp = TempDir::new().unwrap();
+        std::fs::write(tmp.path().join("report.md"), "# Report").unwrap();
+        let attachments = Attachments::new();
+        let tool = AttachTool::new(tmp.path().to_path_buf(), attachments.clone());
+
+        tool.execute(json!({ "path": "report.md" })).await.unwrap();
+        tool.execute(json!({ "path": "report.md" })).await.unwrap();
+        assert!(tool.execute(json!({ "path": "missing.md" })).await.is_err());
+        assert!(tool.execute(json!({ "path": "." })).await.is_err());
+
+        assert_eq!(attachments.take(), vec![tmp.path().join("report.md")]);
+        assert!(attachments.take().is_empty());
+    }
+}
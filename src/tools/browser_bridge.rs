@@ -1,44 +1,402 @@
 use base64::{Engine as _, engine::general_purpose};
 use crate::Result;
-use crate::tools::Tool;
+use crate::error::Error;
+use crate::tools::{DangerLevel, Tool};
 use async_trait::async_trait;
 use serde_json::json;
-use std::sync::{Arc, Mutex};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
 use tokio::net::{TcpListener, TcpStream};
 use tokio_tungstenite::accept_async;
 use tokio_tungstenite::tungstenite::Message as WsMessage;
 use futures_util::{SinkExt, StreamExt};
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, oneshot};
 use tracing::{info, warn, debug};
 
+/// Supported actions and their parameters, as reported by the `help` action
+///
+/// Each command reaches the extension as `{"action": ..., "id": ..., <params>}`.
+/// Actions that return data (`get_elements`, `fill_form`, `read_selection`) wait for
+/// `{"type": "result", "id": <same id>, "data": ...}` or `{"type": "error", "id", "message"}`:
+/// - `get_elements`: `[{"text", "selector", "role"}]`
+/// - `fill_form`: `{"filled": [selector], "missing": [selector]}`
+/// - `read_selection`: the highlighted text as a string
+const ACTIONS: &[(&str, &str)] = &[
+    ("open", "url - open a URL in a new tab"),
+    ("search", "query - search the web (search_engine config, default Google) in a new tab"),
+    ("click", "selector - click an element by CSS selector or visible text"),
+    ("type", "selector, text - type text into an input"),
+    ("fill_form", "fields - fill several inputs at once ({selector: value})"),
+    ("read_selection", "return the text highlighted in the active tab"),
+    ("read", "max_length (optional) - read the active page's main text"),
+    ("scroll", "y (optional, default 500) - scroll down by pixels"),
+    ("screenshot", "capture the visible tab to the moments folder"),
+    ("moment", "capture screenshot + page text to the moments folder"),
+    ("get_elements", "list clickable elements (text, selector, role) to pick a click target"),
+    ("wait", "ms - wait before the next action"),
+    ("help", "show this list and the connection status"),
+];
+
+/// Search URL for `query` on `engine` (a known name or a `{query}` template).
+/// Unknown engines fall back to Google.
+fn search_url(engine: &str, query: &str) -> String {
+    let encoded = url::form_urlencoded::byte_serialize(query.as_bytes()).collect::<String>();
+    let engine = engine.trim();
+    let template = match engine.to_lowercase().as_str() {
+        "" | "google" => "https://www.google.com/search?q={query}",
+        "bing" => "https://www.bing.com/search?q={query}",
+        "duckduckgo" | "ddg" => "https://duckduckgo.com/?q={query}",
+        _ if engine.contains("{query}") => engine,
+        _ => {
+            warn!("Unknown search_engine '{}', searching Google instead", engine);
+            "https://www.google.com/search?q={query}"
+        }
+    };
+    template.replace("{query}", &encoded)
+}
+
+/// Directory a new screenshot or moment is saved in: <moments_dir>/<slug>/<timestamp>
+pub(crate) fn moment_dir(moments_dir: &Path, slug: &str) -> PathBuf {
+    let timestamp = chrono::Local::now().format(super::moments::MOMENT_TIMESTAMP_FORMAT).to_string();
+    moments_dir.join(slug).join(timestamp)
+}
+
+/// How long a new connection has to send its auth message
+const AUTH_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// How long to wait for the extension to answer a command that returns data
+const REPLY_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(15);
+
+/// Most elements `get_elements` returns
+const MAX_ELEMENTS: usize = 50;
+
+/// Where the pairing token is kept between runs
+fn token_path() -> std::path::PathBuf {
+    crate::config::config_dir().join("browser_token")
+}
+
+/// Shared secret the extension must send before it can receive commands.
+///
+/// Generated on first use and stored in ~/.leo/browser_token so pairing survives restarts.
+pub fn pairing_token() -> Result<String> {
+    let path = token_path();
+    if let Ok(token) = std::fs::read_to_string(&path) {
+        let token = token.trim();
+        if !token.is_empty() {
+            return Ok(token.to_string());
+        }
+    }
+
+    use rand::Rng;
+    let token: String = rand::thread_rng()
+        .sample_iter(&rand::distributions::Alphanumeric)
+        .take(32)
+        .map(char::from)
+        .collect();
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&path, &token)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))?;
+    }
+    Ok(token)
+}
+
+/// Compare tokens without short-circuiting on the first differing byte
+fn tokens_match(expected: &str, given: &str) -> bool {
+    expected.len() == given.len()
+        && expected.bytes().zip(given.bytes()).fold(0u8, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
+/// Command channels of the connected extensions, keyed by connection id.
+/// Ids only increase, so the last entry is the most recent connection.
+type Connections = Arc<Mutex<BTreeMap<u64, mpsc::UnboundedSender<String>>>>;
+
+/// Commands waiting for the extension's reply, keyed by the `id` sent with them
+type PendingReplies = Arc<Mutex<HashMap<u64, oneshot::Sender<serde_json::Value>>>>;
+
+/// Where each `screenshot`/`moment` in flight is saved, keyed by the `id` sent with it
+type CaptureDirs = Arc<Mutex<HashMap<u64, PathBuf>>>;
+
+/// Hand a `result`/`error` message carrying a request `id` to whoever is waiting on it.
+/// Returns false if nobody is (e.g. it already timed out).
+fn deliver_reply(pending: &PendingReplies, message: &serde_json::Value) -> bool {
+    if message["type"] != "result" && message["type"] != "error" {
+        return false;
+    }
+    let Some(id) = message["id"].as_u64() else { return false };
+    let waiter = pending.lock().unwrap().remove(&id);
+    waiter.is_some_and(|tx| tx.send(message.clone()).is_ok())
+}
+
+/// Summary of a `fill_form` reply
+fn format_fill_result(data: &serde_json::Value) -> String {
+    let list = |key: &str| -> Vec<String> {
+        data[key].as_array()
+            .map(|items| items.iter().filter_map(|v| v.as_str()).map(String::from).collect())
+            .unwrap_or_default()
+    };
+    let (filled, missing) = (list("filled"), list("missing"));
+    let mut out = format!("Filled {} field(s)", filled.len());
+    if !filled.is_empty() {
+        out.push_str(&format!(": {}", filled.join(", ")));
+    }
+    if !missing.is_empty() {
+        out.push_str(&format!("\nNot found: {} (use get_elements to find their selectors)", missing.join(", ")));
+    }
+    out
+}
+
+/// The `get_elements` reply as one JSON object per line, capped at `MAX_ELEMENTS`
+///
+/// The extension sends a list of `{text, selector, role}`; older versions sent
+/// `{tag, text, id}` as a JSON string, which is mapped onto the same fields.
+fn format_elements(data: &serde_json::Value) -> String {
+    let parsed;
+    let data = match data.as_str() {
+        Some(text) => {
+            parsed = serde_json::from_str(text).unwrap_or(serde_json::Value::Null);
+            &parsed
+        }
+        None => data,
+    };
+    let field = |el: &serde_json::Value, key: &str| el[key].as_str().unwrap_or("").trim().to_string();
+    let elements: Vec<serde_json::Value> = data.as_array()
+        .map(|items| items.iter()
+            .map(|el| {
+                let text = field(el, "text");
+                let id = field(el, "id");
+                let selector = match field(el, "selector") {
+                    s if !s.is_empty() => s,
+                    _ if !id.is_empty() => format!("#{}", id),
+                    _ => text.clone(),
+                };
+                let role = match field(el, "role") {
+                    r if !r.is_empty() => r,
+                    _ => field(el, "tag"),
+                };
+                json!({ "text": text, "selector": selector, "role": role })
+            })
+            .filter(|el| el["selector"] != "")
+            .collect())
+        .unwrap_or_default();
+
+    if elements.is_empty() {
+        return "No clickable elements found on the page.".to_string();
+    }
+    let shown = elements.len().min(MAX_ELEMENTS);
+    let mut out = format!("{} clickable elements (pass a selector to click or type):\n", elements.len());
+    for el in &elements[..shown] {
+        out.push_str(&format!("\n{}", el));
+    }
+    if shown < elements.len() {
+        out.push_str(&format!("\n\n[Showing first {} of {} elements]", shown, elements.len()));
+    }
+    out
+}
+
+/// Address the WebSocket server listens on
+const BRIDGE_ADDR: &str = "127.0.0.1:2345";
+
+/// State of the bridge's WebSocket server
+#[derive(Debug, Clone, PartialEq)]
+pub enum BridgeStatus {
+    /// Not bound yet
+    Starting,
+    /// Port taken (e.g. by a running gateway); still retrying
+    PortInUse,
+    /// Accepting extension connections
+    Listening,
+    /// Gave up; the browser tool can't be used
+    Failed(String),
+}
+
+impl std::fmt::Display for BridgeStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Starting => write!(f, "starting"),
+            Self::PortInUse => write!(f, "port {} in use (is a gateway or another Leo running?)", BRIDGE_ADDR),
+            Self::Listening => write!(f, "listening on {}", BRIDGE_ADDR),
+            Self::Failed(reason) => write!(f, "unavailable: {}", reason),
+        }
+    }
+}
+
+/// Status of this process's browser bridge (`None` if no tool runner created it)
+pub fn bridge_status() -> Option<BridgeStatus> {
+    SHARED_BRIDGE.get().map(|bridge| bridge.status.lock().unwrap().clone())
+}
+
 /// A tool that acts as a bridge to a Chrome Extension via WebSocket
 #[derive(Clone)]
 pub struct BrowserBridgeTool {
-    // Channel to send commands to the active connection handler
-    command_sender: Arc<Mutex<Option<mpsc::UnboundedSender<String>>>>,
+    // Channels to send commands to each connection handler
+    connections: Connections,
+    // Id handed to the next connection
+    next_id: Arc<AtomicU64>,
+    // Commands awaiting a reply, and the id handed to the next one
+    pending: PendingReplies,
+    next_request: Arc<AtomicU64>,
     // Latest state/content received from browser
     last_content: Arc<Mutex<Option<String>>>,
+    // Whether the WebSocket server is listening
+    status: Arc<Mutex<BridgeStatus>>,
+    // Captures sent by any runner that are waiting to be saved
+    captures: CaptureDirs,
+    // Where this runner's screenshots and moments are saved
+    moments_dir: PathBuf,
+    // `search_engine` config: engine name or URL template for 'search'
+    search_engine: String,
 }
 
+/// The process-wide bridge; there is only one WebSocket port to listen on
+static SHARED_BRIDGE: OnceLock<BrowserBridgeTool> = OnceLock::new();
+
 impl BrowserBridgeTool {
-    pub fn new() -> Self {
-        let tool = Self {
-            command_sender: Arc::new(Mutex::new(None)),
+    /// The shared bridge, starting its WebSocket server on first use
+    ///
+    /// Every tool runner (e.g. one per gateway chat) talks to the same browsers;
+    /// each gets its own handle so captures go to its `moments_dir` and searches
+    /// use its `search_engine`.
+    pub fn new(moments_dir: PathBuf, search_engine: &str) -> Self {
+        let mut tool = SHARED_BRIDGE.get_or_init(|| {
+            let tool = Self::unconnected(PathBuf::new());
+            
+            // Start the WebSocket server in the background
+            tool.start_server();
+            
+            tool
+        }).clone();
+        tool.moments_dir = moments_dir;
+        tool.search_engine = search_engine.to_string();
+        tool
+    }
+
+    /// A bridge with no server running (connections are added by the server)
+    fn unconnected(moments_dir: PathBuf) -> Self {
+        Self {
+            connections: Arc::new(Mutex::new(BTreeMap::new())),
+            next_id: Arc::new(AtomicU64::new(1)),
+            pending: Arc::new(Mutex::new(HashMap::new())),
+            next_request: Arc::new(AtomicU64::new(1)),
             last_content: Arc::new(Mutex::new(None)),
+            status: Arc::new(Mutex::new(BridgeStatus::Starting)),
+            captures: Arc::new(Mutex::new(HashMap::new())),
+            moments_dir,
+            search_engine: "google".to_string(),
+        }
+    }
+
+    /// Pick the connections a command goes to: a specific id, "all", or the most recent
+    fn targets(&self, connection: &serde_json::Value) -> Result<Vec<(u64, mpsc::UnboundedSender<String>)>> {
+        let connections = self.connections.lock().unwrap();
+        if connections.is_empty() {
+            return Err(anyhow::anyhow!("No browser connected! Please install the Leo Link extension and ensure Chrome is open.").into());
+        }
+
+        match connection {
+            serde_json::Value::Null => {
+                let (id, tx) = connections.iter().next_back().unwrap();
+                Ok(vec![(*id, tx.clone())])
+            }
+            serde_json::Value::String(s) if s == "all" => {
+                Ok(connections.iter().map(|(id, tx)| (*id, tx.clone())).collect())
+            }
+            other => {
+                let id = other.as_u64()
+                    .or_else(|| other.as_str().and_then(|s| s.parse().ok()));
+                match id.and_then(|id| connections.get(&id).map(|tx| (id, tx.clone()))) {
+                    Some(target) => Ok(vec![target]),
+                    None => {
+                        let ids: Vec<String> = connections.keys().map(|id| id.to_string()).collect();
+                        Err(anyhow::anyhow!(
+                            "Unknown browser connection {}. Connected: {}", other, ids.join(", ")
+                        ).into())
+                    }
+                }
+            }
+        }
+    }
+
+    /// Send `command` to one browser and wait for the `data` of its reply
+    async fn request(&self, connection: &serde_json::Value, mut command: serde_json::Value) -> Result<serde_json::Value> {
+        let action = command["action"].as_str().unwrap_or("").to_string();
+        let targets = self.targets(connection)?;
+        let [(connection_id, sender)] = targets.as_slice() else {
+            return Err(anyhow::anyhow!("'{}' needs a single browser connection, not \"all\"", action).into());
         };
-        
-        // Start the WebSocket server in the background
-        tool.start_server();
-        
-        tool
+
+        let request_id = self.next_request.fetch_add(1, Ordering::Relaxed);
+        command["id"] = json!(request_id);
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().unwrap().insert(request_id, tx);
+        if sender.send(command.to_string()).is_err() {
+            self.pending.lock().unwrap().remove(&request_id);
+            return Err(anyhow::anyhow!("Failed to send command to browser connection {}", connection_id).into());
+        }
+
+        let reply = tokio::time::timeout(REPLY_TIMEOUT, rx).await;
+        self.pending.lock().unwrap().remove(&request_id);
+        match reply {
+            Ok(Ok(reply)) if reply["type"] == "error" => Err(anyhow::anyhow!(
+                "Browser '{}' failed: {}", action, reply["message"].as_str().unwrap_or("unknown error")
+            ).into()),
+            Ok(Ok(reply)) => Ok(reply["data"].clone()),
+            Ok(Err(_)) => Err(anyhow::anyhow!("Browser connection {} closed before replying", connection_id).into()),
+            Err(_) => Err(Error::Timeout(format!(
+                "browser '{}' after {}s (is the Leo Link extension up to date?)",
+                action, REPLY_TIMEOUT.as_secs()
+            ))),
+        }
+    }
+
+    /// List the supported actions and which browsers are connected
+    fn help(&self) -> String {
+        let ids: Vec<String> = self.connections.lock().unwrap()
+            .keys()
+            .map(|id| id.to_string())
+            .collect();
+        let status = if ids.is_empty() {
+            "Browser: not connected (install the Leo Link extension, open Chrome and pair it with the token from 'leo status')".to_string()
+        } else {
+            format!("Browser: connected ({} connection(s): {})", ids.len(), ids.join(", "))
+        };
+
+        let actions: Vec<String> = ACTIONS.iter()
+            .map(|(name, params)| format!("- {}: {}", name, params))
+            .collect();
+        format!(
+            "{}\n\nActions:\n{}\n\nAll actions accept 'connection' (id or \"all\") to pick a browser.",
+            status,
+            actions.join("\n")
+        )
     }
 
     fn start_server(&self) {
-        let sender_store = self.command_sender.clone();
+        let connections = self.connections.clone();
+        let next_id = self.next_id.clone();
         let content_store = self.last_content.clone();
+        let status = self.status.clone();
+        let pending = self.pending.clone();
+        let captures = self.captures.clone();
+        let set_status = move |new: BridgeStatus| *status.lock().unwrap() = new;
 
         tokio::spawn(async move {
-            let addr = "127.0.0.1:2345";
+            let token = match pairing_token() {
+                Ok(token) => Arc::new(token),
+                Err(e) => {
+                    warn!("Browser Bridge disabled - could not create pairing token: {}", e);
+                    set_status(BridgeStatus::Failed(format!("no pairing token: {}", e)));
+                    return;
+                }
+            };
+            let addr = BRIDGE_ADDR;
             let mut retry_count = 0;
             let max_retries = 5;
             
@@ -46,11 +404,13 @@ impl BrowserBridgeTool {
                 match TcpListener::bind(&addr).await {
                     Ok(l) => {
                         info!("Browser Bridge listening on: {}", addr);
+                        set_status(BridgeStatus::Listening);
                         break l;
                     },
                     Err(e) => {
                         if e.kind() == std::io::ErrorKind::AddrInUse && retry_count < max_retries {
                             retry_count += 1;
+                            set_status(BridgeStatus::PortInUse);
                             // Only log first attempt, then stay quiet
                             if retry_count == 1 {
                                 debug!("Browser Bridge port {} in use, waiting...", addr);
@@ -59,19 +419,24 @@ impl BrowserBridgeTool {
                             continue;
                         }
                         // After retries exhausted, just note it's not available
-                        warn!("Browser Bridge unavailable (port {} in use) - browser tool disabled", addr);
+                        warn!("Browser Bridge unavailable ({} on {}) - browser tool disabled", e, addr);
+                        set_status(BridgeStatus::Failed(format!("{} on {}", e, addr)));
                         return;
                     }
                 }
             };
 
             while let Ok((stream, _)) = listener.accept().await {
-                info!("New browser connection incoming");
-                let sender_store = sender_store.clone();
+                let id = next_id.fetch_add(1, Ordering::Relaxed);
+                info!("New browser connection incoming (id {})", id);
+                let connections = connections.clone();
                 let content_store = content_store.clone();
+                let pending = pending.clone();
+                let token = token.clone();
+                let captures = captures.clone();
                 
                 tokio::spawn(async move {
-                    if let Err(e) = handle_connection(stream, sender_store, content_store).await {
+                    if let Err(e) = handle_connection(stream, id, &token, connections, content_store, pending, captures).await {
                         debug!("Browser connection ended: {}", e);
                     }
                 });
@@ -82,22 +447,42 @@ impl BrowserBridgeTool {
 
 async fn handle_connection(
     stream: TcpStream, 
-    sender_store: Arc<Mutex<Option<mpsc::UnboundedSender<String>>>>,
-    content_store: Arc<Mutex<Option<String>>>
+    id: u64,
+    token: &str,
+    connections: Connections,
+    content_store: Arc<Mutex<Option<String>>>,
+    pending: PendingReplies,
+    captures: CaptureDirs,
 ) -> Result<()> {
     let ws_stream = accept_async(stream).await.map_err(|e| anyhow::anyhow!("Failed to accept WS: {}", e))?;
     info!("WebSocket connection established");
 
     let (mut ws_sender, mut ws_receiver) = ws_stream.split();
+
+    // The first message must be {"type": "auth", "token": "..."}
+    let first = tokio::time::timeout(AUTH_TIMEOUT, ws_receiver.next()).await.ok().flatten();
+    let authorized = match first {
+        Some(Ok(WsMessage::Text(text))) => serde_json::from_str::<serde_json::Value>(&text)
+            .ok()
+            .filter(|json| json["type"] == "auth")
+            .and_then(|json| json["token"].as_str().map(|given| tokens_match(token, given)))
+            .unwrap_or(false),
+        _ => false,
+    };
+    if !authorized {
+        warn!("Rejected browser connection {}: missing or invalid pairing token", id);
+        let reply = json!({ "type": "error", "message": "unauthorized: invalid pairing token" });
+        ws_sender.send(WsMessage::Text(reply.to_string().into())).await.ok();
+        ws_sender.send(WsMessage::Close(None)).await.ok();
+        return Err(anyhow::anyhow!("unauthorized browser connection").into());
+    }
+    ws_sender.send(WsMessage::Text(json!({ "type": "auth_ok" }).to_string().into())).await.ok();
     
     // Create channel for this connection
     let (tx, mut rx) = mpsc::unbounded_channel();
     
-    // Store the sender so the Tool execute method can use it
-    {
-        let mut guard = sender_store.lock().unwrap();
-        *guard = Some(tx);
-    } // Drop lock
+    // Register the sender so the Tool execute method can use it
+    connections.lock().unwrap().insert(id, tx);
 
     // Loop to handle incoming messages (browser -> leo) and outgoing commands (leo -> browser)
     loop {
@@ -109,13 +494,16 @@ async fn handle_connection(
                         // info!("Received from browser: {}", text); // Too noisy for large messages
                         
                         if let Ok(json) = serde_json::from_str::<serde_json::Value>(&text) {
-                            if json["type"] == "result" && (json["action"] == "screenshot" || json["action"] == "moment") {
+                            deliver_reply(&pending, &json);
+                            let capture = (json["type"] == "result" && (json["action"] == "screenshot" || json["action"] == "moment"))
+                                .then(|| json["id"].as_u64().and_then(|id| captures.lock().unwrap().remove(&id)))
+                                .flatten();
+                            if let Some(moments_dir) = capture {
                                 if let Some(screenshot_b64) = json["data"]["screenshot"].as_str() {
                                     // Remove data:image/png;base64, prefix
                                     let b64_data = screenshot_b64.split(",").nth(1).unwrap_or(screenshot_b64);
                                     
                                     if let Ok(bytes) = general_purpose::STANDARD.decode(b64_data) {
-                                        let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S").to_string();
                                         let slug = if json["action"] == "moment" {
                                             json["data"]["page"]["title"].as_str().unwrap_or("snapshot")
                                                 .chars().filter(|c| c.is_alphanumeric() || *c == ' ').collect::<String>()
@@ -124,16 +512,16 @@ async fn handle_connection(
                                             "screenshot".to_string()
                                         };
                                         
-                                        let moments_dir = dirs::home_dir().unwrap_or_default().join(".leo").join("moments").join(&slug).join(&timestamp);
-                                        std::fs::create_dir_all(&moments_dir).ok();
+                                        let capture_dir = moment_dir(&moments_dir, &slug);
+                                        std::fs::create_dir_all(&capture_dir).ok();
                                         
-                                        let img_path = moments_dir.join("screenshot.png");
+                                        let img_path = capture_dir.join("screenshot.png");
                                         std::fs::write(&img_path, bytes).ok();
                                         info!("Saved {} to {:?}", json["action"], img_path);
                                         
                                         if json["action"] == "moment" {
                                             if let Some(page) = json["data"]["page"].as_object() {
-                                                let meta_path = moments_dir.join("metadata.json");
+                                                let meta_path = capture_dir.join("metadata.json");
                                                 std::fs::write(&meta_path, serde_json::to_string_pretty(page).unwrap()).ok();
                                             }
                                         }
@@ -172,9 +560,8 @@ async fn handle_connection(
     }
     
     // Cleanup
-    let mut guard = sender_store.lock().unwrap();
-    *guard = None;
-    info!("Connection handler finished");
+    connections.lock().unwrap().remove(&id);
+    info!("Connection handler {} finished", id);
     
     Ok(())
 }
@@ -186,25 +573,38 @@ impl Tool for BrowserBridgeTool {
     }
 
     fn description(&self) -> &str {
-        "Control Chrome via extension. Actions: open, search, click, type, read, scroll, screenshot, moment (snapshot)."
+        "Control Chrome via extension. Actions: open, search, click, type, fill_form (several inputs at once), read, read_selection (highlighted text), scroll, screenshot, moment (snapshot), get_elements (clickable elements with selectors), help (list actions and connection status)."
+    }
+
+    fn is_mutating(&self, params: &serde_json::Value) -> bool {
+        // Clicking and typing can submit forms on real sites
+        matches!(params.get("action").and_then(|v| v.as_str()), Some("click" | "type" | "fill_form"))
     }
 
+    fn danger_level(&self) -> DangerLevel { DangerLevel::Caution }
+
     fn parameters(&self) -> serde_json::Value {
         json!({
             "type": "object",
             "properties": {
                 "action": {
                     "type": "string",
-                    "enum": ["open", "click", "type", "read", "scroll", "search", "screenshot", "moment", "get_elements", "wait"],
-                    "description": "Action to perform. 'moment' captures screenshot + text. 'get_elements' lists buttons/links."
+                    "enum": ACTIONS.iter().map(|(name, _)| *name).collect::<Vec<_>>(),
+                    "description": "Action to perform. 'moment' captures screenshot + text. 'get_elements' returns clickable elements with selectors for 'click'. 'help' lists actions and connection status."
                 },
                 "url": { "type": "string", "description": "URL to open (for 'open')" },
                 "query": { "type": "string", "description": "Search query (for 'search')" },
                 "selector": { "type": "string", "description": "CSS selector OR visible text/label (e.g. 'Find Jobs', '#search-btn')" },
                 "text": { "type": "string", "description": "Text to type" },
+                "fields": {
+                    "type": "object",
+                    "additionalProperties": { "type": "string" },
+                    "description": "For 'fill_form': selector (as for 'type') -> value. Checkboxes take \"true\"/\"false\", selects an option's value or label"
+                },
                 "y": { "type": "number", "description": "Pixels to scroll down (default 500)" },
                 "ms": { "type": "number", "description": "Milliseconds to wait (for 'wait')" },
-                "max_length": { "type": "number", "description": "Max text length for 'read' (default 10000)" }
+                "max_length": { "type": "number", "description": "Max text length for 'read' (default 10000)" },
+                "connection": { "description": "Browser connection id to target, or \"all\" to broadcast (default: most recent connection)" }
             },
             "required": ["action"]
         })
@@ -213,39 +613,262 @@ impl Tool for BrowserBridgeTool {
     async fn execute(&self, args: serde_json::Value) -> Result<String> {
         let action = args["action"].as_str().unwrap_or("help").to_string();
         
+        if action == "help" {
+            return Ok(self.help());
+        }
+        if !ACTIONS.iter().any(|(name, _)| *name == action) {
+            return Ok(format!("Unknown browser action '{}'.\n\n{}", action, self.help()));
+        }
+        
         // Handle 'search' by converting it to an 'open' action
         let final_args = if action == "search" {
             if let Some(query) = args["query"].as_str() {
-                let encoded = url::form_urlencoded::byte_serialize(query.as_bytes()).collect::<String>();
                 json!({
                     "action": "open",
-                    "url": format!("https://www.google.com/search?q={}", encoded)
+                    "url": search_url(&self.search_engine, query)
                 })
             } else {
                 return Ok("Error: 'query' parameter required for search action".to_string());
             }
         } else {
-            args
+            args.clone()
         };
 
-        // Construct the JSON command to send to the extension
-        let command = serde_json::to_string(&final_args)?;
+        match action.as_str() {
+            "get_elements" => {
+                let data = self.request(&args["connection"], final_args).await?;
+                return Ok(format_elements(&data));
+            }
+            "fill_form" => {
+                if args["fields"].as_object().is_none_or(|fields| fields.is_empty()) {
+                    return Err(anyhow::anyhow!("'fill_form' needs 'fields': an object of selector -> value").into());
+                }
+                let data = self.request(&args["connection"], final_args).await?;
+                return Ok(format_fill_result(&data));
+            }
+            "read_selection" => {
+                let data = self.request(&args["connection"], final_args).await?;
+                return Ok(match data.as_str().map(str::trim) {
+                    Some(text) if !text.is_empty() => text.to_string(),
+                    _ => "Nothing is selected in the active tab.".to_string(),
+                });
+            }
+            _ => {}
+        }
+
+        let targets = self.targets(&args["connection"])?;
+        for (id, sender) in &targets {
+            // Captures carry their own id so the reply is saved in this runner's moments folder
+            let mut command = final_args.clone();
+            if action == "screenshot" || action == "moment" {
+                let request_id = self.next_request.fetch_add(1, Ordering::Relaxed);
+                command["id"] = json!(request_id);
+                self.captures.lock().unwrap().insert(request_id, self.moments_dir.clone());
+            }
+            sender.send(command.to_string())
+                .map_err(|_| anyhow::anyhow!("Failed to send command to browser connection {}", id))?;
+        }
         
-        let tx = {
-            let guard = self.command_sender.lock().unwrap();
-            guard.clone()
+        let connected = self.connections.lock().unwrap().len();
+        let destination = if connected > 1 {
+            let ids: Vec<String> = targets.iter().map(|(id, _)| id.to_string()).collect();
+            format!(" (to connection {} of {} connected)", ids.join(", "), connected)
+        } else {
+            String::new()
         };
+        
+        if action == "search" {
+            Ok(format!("Search results opened in browser{}! Check your browser tabs.", destination))
+        } else {
+            Ok(format!("Browser action '{}' sent{}!", action, destination))
+        }
+    }
+}
 
-        if let Some(sender) = tx {
-            sender.send(command).map_err(|_| anyhow::anyhow!("Failed to send command to browser"))?;
-            
-            if action == "search" {
-                Ok("Search results opened in browser! Check your browser tabs.".to_string())
-            } else {
-                Ok(format!("Browser action '{}' sent!", action))
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_routes_to_selected_connection() {
+        let tool = BrowserBridgeTool::unconnected(PathBuf::from("moments"));
+        assert!(tool.execute(json!({"action": "read"})).await.is_err());
+
+        let (tx1, mut rx1) = mpsc::unbounded_channel();
+        let (tx2, mut rx2) = mpsc::unbounded_channel();
+        tool.connections.lock().unwrap().insert(1, tx1);
+        tool.connections.lock().unwrap().insert(2, tx2);
+
+        // Defaults to the most recent connection
+        let result = tool.execute(json!({"action": "read"})).await.unwrap();
+        assert!(result.contains("to connection 2 of 2"));
+        assert!(rx2.try_recv().is_ok());
+        assert!(rx1.try_recv().is_err());
+
+        tool.execute(json!({"action": "read", "connection": 1})).await.unwrap();
+        assert!(rx1.try_recv().is_ok());
+
+        tool.execute(json!({"action": "read", "connection": "all"})).await.unwrap();
+        assert!(rx1.try_recv().is_ok() && rx2.try_recv().is_ok());
+
+        let err = tool.execute(json!({"action": "read", "connection": 7})).await.unwrap_err();
+        assert!(err.to_string().contains("Connected: 1, 2"));
+    }
+
+    #[tokio::test]
+    async fn test_captures_go_to_each_runners_moments_dir() {
+        let first = BrowserBridgeTool::unconnected(PathBuf::from("first"));
+        let mut second = first.clone();
+        second.moments_dir = PathBuf::from("second");
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        first.connections.lock().unwrap().insert(1, tx);
+
+        first.execute(json!({"action": "screenshot"})).await.unwrap();
+        second.execute(json!({"action": "moment"})).await.unwrap();
+
+        for expected in ["first", "second"] {
+            let sent: serde_json::Value = serde_json::from_str(&rx.try_recv().unwrap()).unwrap();
+            let id = sent["id"].as_u64().unwrap();
+            assert_eq!(first.captures.lock().unwrap()[&id], PathBuf::from(expected));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_help_works_without_browser() {
+        let tool = BrowserBridgeTool::unconnected(PathBuf::from("moments"));
+        let help = tool.execute(json!({"action": "help"})).await.unwrap();
+        assert!(help.contains("not connected"));
+        assert!(help.contains("- get_elements:"));
+
+        let unknown = tool.execute(json!({"action": "teleport"})).await.unwrap();
+        assert!(unknown.starts_with("Unknown browser action 'teleport'"));
+
+        let (tx, _rx) = mpsc::unbounded_channel();
+        tool.connections.lock().unwrap().insert(3, tx);
+        let help = tool.execute(json!({})).await.unwrap();
+        assert!(help.contains("connected (1 connection(s): 3)"));
+    }
+
+    #[tokio::test]
+    async fn test_get_elements_waits_for_reply() {
+        let tool = BrowserBridgeTool::unconnected(PathBuf::from("moments"));
+        let (tx, mut rx) = mpsc::unbounded_channel::<String>();
+        tool.connections.lock().unwrap().insert(1, tx);
+
+        // Play the extension: answer the command under its request id
+        let pending = tool.pending.clone();
+        tokio::spawn(async move {
+            let command: serde_json::Value = serde_json::from_str(&rx.recv().await.unwrap()).unwrap();
+            assert_eq!(command["action"], "get_elements");
+            let mut elements: Vec<serde_json::Value> = vec![
+                json!({ "text": "Find Jobs", "selector": "#search-btn", "role": "button" }),
+            ];
+            elements.extend((0..MAX_ELEMENTS).map(|i| json!({ "text": format!("Page {}", i), "selector": format!("a:nth-of-type({})", i), "role": "link" })));
+            assert!(!deliver_reply(&pending, &json!({ "type": "result", "id": 999, "data": [] })));
+            assert!(deliver_reply(&pending, &json!({ "type": "result", "id": command["id"], "data": elements })));
+        });
+
+        let result = tool.execute(json!({ "action": "get_elements" })).await.unwrap();
+        assert!(result.starts_with("51 clickable elements"));
+        assert!(result.contains(r##"{"role":"button","selector":"#search-btn","text":"Find Jobs"}"##));
+        assert!(result.ends_with("[Showing first 50 of 51 elements]"));
+        assert!(tool.pending.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_fill_form_and_read_selection() {
+        let tool = BrowserBridgeTool::unconnected(PathBuf::from("moments"));
+        let (tx, mut rx) = mpsc::unbounded_channel::<String>();
+        tool.connections.lock().unwrap().insert(1, tx);
+        assert!(tool.is_mutating(&json!({ "action": "fill_form" })));
+        assert!(tool.execute(json!({ "action": "fill_form", "fields": {} })).await.is_err());
+
+        let pending = tool.pending.clone();
+        tokio::spawn(async move {
+            while let Some(text) = rx.recv().await {
+                let command: serde_json::Value = serde_json::from_str(&text).unwrap();
+                let data = match command["action"].as_str().unwrap() {
+                    "fill_form" => {
+                        assert_eq!(command["fields"]["#email"], "sam@example.com");
+                        json!({ "filled": ["#email", "Name"], "missing": ["#phone"] })
+                    }
+                    _ => json!("  highlighted words "),
+                };
+                deliver_reply(&pending, &json!({ "type": "result", "id": command["id"], "data": data }));
             }
-        } else {
-            Err(anyhow::anyhow!("No browser connected! Please install the Leo Link extension and ensure Chrome is open.").into())
+        });
+
+        let filled = tool.execute(json!({ "action": "fill_form", "fields": {
+            "#email": "sam@example.com", "Name": "Sam", "#phone": "123"
+        }})).await.unwrap();
+        assert_eq!(filled, "Filled 2 field(s): #email, Name\nNot found: #phone (use get_elements to find their selectors)");
+
+        let selection = tool.execute(json!({ "action": "read_selection" })).await.unwrap();
+        assert_eq!(selection, "highlighted words");
+    }
+
+    #[test]
+    fn test_format_legacy_elements() {
+        let legacy = json!(r#"[{"tag":"a","text":"Next","id":""},{"tag":"button","text":"Go","id":"go"}]"#);
+        assert_eq!(
+            format_elements(&legacy),
+            "2 clickable elements (pass a selector to click or type):\n\n\
+             {\"role\":\"a\",\"selector\":\"Next\",\"text\":\"Next\"}\n\
+             {\"role\":\"button\",\"selector\":\"#go\",\"text\":\"Go\"}"
+        );
+        assert_eq!(format_elements(&json!([])), "No clickable elements found on the page.");
+    }
+
+    #[test]
+    fn test_search_url() {
+        assert_eq!(search_url("google", "rust async"), "https://www.google.com/search?q=rust+async");
+        assert_eq!(search_url("DuckDuckGo", "a&b"), "https://duckduckgo.com/?q=a%26b");
+        assert_eq!(search_url("https://search.brave.com/search?q={query}&source=web", "leo"), "https://search.brave.com/search?q=leo&source=web");
+        assert_eq!(search_url("altavista", "x"), "https://www.google.com/search?q=x");
+    }
+
+    #[test]
+    fn test_tokens_match() {
+        assert!(tokens_match("secret", "secret"));
+        assert!(!tokens_match("secret", "secreT"));
+        assert!(!tokens_match("secret", "secret2"));
+    }
+
+    #[tokio::test]
+    async fn test_connection_requires_token() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let tool = BrowserBridgeTool::unconnected(PathBuf::from("moments"));
+        let connections = tool.connections.clone();
+        tokio::spawn(async move {
+            for id in 1.. {
+                let (stream, _) = listener.accept().await.unwrap();
+                let connections = connections.clone();
+                tokio::spawn(async move {
+                    let pending = Arc::new(Mutex::new(HashMap::new()));
+                    let _ = handle_connection(stream, id, "s3cret", connections, Arc::new(Mutex::new(None)), pending, Arc::new(Mutex::new(HashMap::new()))).await;
+                });
+            }
+        });
+
+        let (mut bad, _) = tokio_tungstenite::connect_async(&url).await.unwrap();
+        bad.send(WsMessage::Text(json!({"type": "auth", "token": "guess"}).to_string().into())).await.unwrap();
+        let reply = bad.next().await.unwrap().unwrap();
+        assert!(reply.to_text().unwrap().contains("unauthorized"));
+        assert!(tool.connections.lock().unwrap().is_empty());
+
+        let (mut good, _) = tokio_tungstenite::connect_async(&url).await.unwrap();
+        good.send(WsMessage::Text(json!({"type": "auth", "token": "s3cret"}).to_string().into())).await.unwrap();
+        let reply = good.next().await.unwrap().unwrap();
+        assert!(reply.to_text().unwrap().contains("auth_ok"));
+
+        // The handler registers the connection right after replying
+        for _ in 0..50 {
+            if !tool.connections.lock().unwrap().is_empty() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
         }
+        assert!(tool.execute(json!({"action": "read"})).await.is_ok());
     }
 }
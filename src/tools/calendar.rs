@@ -0,0 +1,308 @@
+//! Calendar tool - Google Calendar events via the OAuth sign-in
+
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate};
+use reqwest::{Client, Url};
+use serde_json::{json, Value};
+use crate::Result;
+use crate::auth::GeminiAuthProvider;
+use crate::error::Error;
+use super::{DangerLevel, Tool};
+
+const CALENDAR_API: &str = "https://www.googleapis.com/calendar/v3";
+
+/// Upcoming events listed when `max_results` is not given
+const DEFAULT_MAX_RESULTS: u64 = 10;
+
+/// List and create Google Calendar events
+pub struct CalendarTool {
+    auth: GeminiAuthProvider,
+    client: Client,
+}
+
+impl CalendarTool {
+    /// `auth` must request the calendar scope (see `GeminiAuthProvider::with_calendar`)
+    pub fn new(auth: GeminiAuthProvider) -> Self {
+        Self { auth, client: Client::new() }
+    }
+
+    /// URL of the events collection of `calendar_id`
+    fn events_url(calendar_id: &str) -> Result<Url> {
+        let mut url = Url::parse(CALENDAR_API)
+            .map_err(|e| Error::Tool(format!("Invalid calendar URL: {}", e)))?;
+        url.path_segments_mut()
+            .map_err(|_| Error::Tool("Invalid calendar URL".to_string()))?
+            .extend(["calendars", calendar_id, "events"]);
+        Ok(url)
+    }
+
+    async fn list(&self, calendar_id: &str, max_results: u64) -> Result<String> {
+        let token = self.auth.get_valid_token().await?;
+        let now = chrono::Utc::now().to_rfc3339();
+        let response = self.client.get(Self::events_url(calendar_id)?)
+            .bearer_auth(token)
+            .query(&[
+                ("timeMin", now.as_str()),
+                ("singleEvents", "true"),
+                ("orderBy", "startTime"),
+                ("maxResults", &max_results.to_string()),
+            ])
+            .send()
+            .await
+            .map_err(|e| Error::Tool(format!("Calendar request failed: {}", e)))?;
+
+        let body = check_response(response).await?;
+        Ok(format_events(&body))
+    }
+
+    async fn create(&self, calendar_id: &str, params: &Value) -> Result<String> {
+        let event = event_body(params)?;
+        let token = self.auth.get_valid_token().await?;
+        let response = self.client.post(Self::events_url(calendar_id)?)
+            .bearer_auth(token)
+            .json(&event)
+            .send()
+            .await
+            .map_err(|e| Error::Tool(format!("Calendar request failed: {}", e)))?;
+
+        let created = check_response(response).await?;
+        let mut result = format!("Created event: {}", format_event(&created));
+        if let Some(link) = created.get("htmlLink").and_then(|v| v.as_str()) {
+            result.push_str(&format!("\n{}", link));
+        }
+        Ok(result)
+    }
+}
+
+/// Parse a Calendar API response, turning API errors into tool errors
+async fn check_response(response: reqwest::Response) -> Result<Value> {
+    let status = response.status();
+    let body: Value = response.json().await
+        .map_err(|e| Error::Tool(format!("Invalid calendar response: {}", e)))?;
+    if status.is_success() {
+        return Ok(body);
+    }
+
+    let message = body.pointer("/error/message")
+        .and_then(|v| v.as_str())
+        .unwrap_or("unknown error");
+    if status == reqwest::StatusCode::FORBIDDEN {
+        return Err(Error::Tool(format!(
+            "Calendar access denied ({}). Set \"google_calendar\": true in the config and run `leo login` again",
+            message
+        )));
+    }
+    Err(Error::Tool(format!("Calendar API error {}: {}", status, message)))
+}
+
+/// Build an event from `title`, `start`, `end` and optional `description`/`location`
+///
+/// Times are RFC 3339 (`2026-05-01T15:00:00+02:00`); plain dates make an all-day event.
+fn event_body(params: &Value) -> Result<Value> {
+    let title = params.get("title")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| Error::Tool("Missing 'title' parameter".to_string()))?;
+    let start = params.get("start")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| Error::Tool("Missing 'start' parameter".to_string()))?;
+    let end = params.get("end")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| Error::Tool("Missing 'end' parameter".to_string()))?;
+
+    let mut event = json!({
+        "summary": title,
+        "start": event_time(start)?,
+        "end": event_time(end)?,
+    });
+    for field in ["description", "location"] {
+        if let Some(value) = params.get(field).and_then(|v| v.as_str()) {
+            event[field] = json!(value);
+        }
+    }
+    Ok(event)
+}
+
+fn event_time(value: &str) -> Result<Value> {
+    if DateTime::parse_from_rfc3339(value).is_ok() {
+        Ok(json!({ "dateTime": value }))
+    } else if NaiveDate::parse_from_str(value, "%Y-%m-%d").is_ok() {
+        Ok(json!({ "date": value }))
+    } else {
+        Err(Error::Tool(format!(
+            "Invalid time '{}': use RFC 3339 (2026-05-01T15:00:00+02:00) or a date (2026-05-01)",
+            value
+        )))
+    }
+}
+
+/// One line per event: start, end and title (plus location)
+fn format_events(body: &Value) -> String {
+    let events = body.get("items")
+        .and_then(|v| v.as_array())
+        .map(|items| items.as_slice())
+        .unwrap_or_default();
+    if events.is_empty() {
+        return "No upcoming events".to_string();
+    }
+
+    events.iter()
+        .map(|event| format!("- {}", format_event(event)))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn format_event(event: &Value) -> String {
+    let time = |key: &str| {
+        event.get(key)
+            .and_then(|t| t.get("dateTime").or_else(|| t.get("date")))
+            .and_then(|v| v.as_str())
+            .unwrap_or("?")
+            .to_string()
+    };
+    let title = event.get("summary").and_then(|v| v.as_str()).unwrap_or("(no title)");
+
+    let mut line = format!("{} → {}: {}", time("start"), time("end"), title);
+    if let Some(location) = event.get("location").and_then(|v| v.as_str()) {
+        line.push_str(&format!(" @ {}", location));
+    }
+    line
+}
+
+#[async_trait]
+impl Tool for CalendarTool {
+    fn name(&self) -> &str { "calendar" }
+    fn description(&self) -> &str {
+        "Google Calendar: list upcoming events or create an event"
+    }
+
+    fn is_mutating(&self, params: &Value) -> bool {
+        params.get("action").and_then(|v| v.as_str()) == Some("create")
+    }
+
+    fn danger_level(&self) -> DangerLevel { DangerLevel::Caution }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["list", "create"],
+                    "description": "'list' upcoming events or 'create' an event"
+                },
+                "title": {
+                    "type": "string",
+                    "description": "Event title (create)"
+                },
+                "start": {
+                    "type": "string",
+                    "description": "Start time in RFC 3339, e.g. 2026-05-01T15:00:00+02:00, or a date for all-day events (create)"
+                },
+                "end": {
+                    "type": "string",
+                    "description": "End time, same format as start (create)"
+                },
+                "description": {
+                    "type": "string",
+                    "description": "Event notes (create, optional)"
+                },
+                "location": {
+                    "type": "string",
+                    "description": "Event location (create, optional)"
+                },
+                "max_results": {
+                    "type": "integer",
+                    "description": "How many upcoming events to list (default 10)"
+                },
+                "calendar_id": {
+                    "type": "string",
+                    "description": "Calendar to use (default 'primary')"
+                }
+            },
+            "required": ["action"]
+        })
+    }
+
+    async fn execute(&self, params: Value) -> Result<String> {
+        let action = params.get("action")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| Error::Tool("Missing 'action' parameter".to_string()))?;
+        let calendar_id = params.get("calendar_id")
+            .and_then(|v| v.as_str())
+            .unwrap_or("primary");
+
+        match action {
+            "list" => {
+                let max_results = params.get("max_results")
+                    .and_then(|v| v.as_u64())
+                    .unwrap_or(DEFAULT_MAX_RESULTS)
+                    .clamp(1, 250);
+                self.list(calendar_id, max_results).await
+            }
+            "create" => self.create(calendar_id, &params).await,
+            other => Err(Error::Tool(format!("Unknown action: {}. Use 'list' or 'create'", other))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_body() {
+        let event = event_body(&json!({
+            "title": "Dentist",
+            "start": "2026-05-01T15:00:00+02:00",
+            "end": "2026-05-01T16:00:00+02:00",
+            "location": "Main St"
+        })).unwrap();
+        assert_eq!(event["summary"], "Dentist");
+        assert_eq!(event["start"]["dateTime"], "2026-05-01T15:00:00+02:00");
+        assert_eq!(event["location"], "Main St");
+
+        let all_day = event_body(&json!({
+            "title": "Holiday", "start": "2026-05-01", "end": "2026-05-02"
+        })).unwrap();
+        assert_eq!(all_day["start"]["date"], "2026-05-01");
+
+        assert!(event_body(&json!({
+            "title": "Bad", "start": "tomorrow 3pm", "end": "2026-05-02"
+        })).is_err());
+        assert!(event_body(&json!({ "start": "2026-05-01", "end": "2026-05-02" })).is_err());
+    }
+
+    #[test]
+    fn test_format_events() {
+        assert_eq!(format_events(&json!({ "items": [] })), "No upcoming events");
+
+        let body = json!({ "items": [
+            {
+                "summary": "Standup",
+                "start": { "dateTime": "2026-05-01T09:00:00Z" },
+                "end": { "dateTime": "2026-05-01T09:15:00Z" }
+            },
+            {
+                "summary": "Holiday",
+                "location": "Beach",
+                "start": { "date": "2026-05-02" },
+                "end": { "date": "2026-05-03" }
+            }
+        ]});
+        assert_eq!(
+            format_events(&body),
+            "- 2026-05-01T09:00:00Z → 2026-05-01T09:15:00Z: Standup\n- 2026-05-02 → 2026-05-03: Holiday @ Beach"
+        );
+    }
+
+    #[test]
+    fn test_events_url_escapes_calendar_id() {
+        let url = CalendarTool::events_url("team@group.calendar.google.com").unwrap();
+        assert_eq!(
+            url.as_str(),
+            "https://www.googleapis.com/calendar/v3/calendars/team@group.calendar.google.com/events"
+        );
+        let url = CalendarTool::events_url("a/b").unwrap();
+        assert!(url.as_str().ends_with("/calendars/a%2Fb/events"));
+    }
+}
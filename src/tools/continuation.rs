@@ -0,0 +1,192 @@
+//! Continuation - the rest of tool outputs too large to return at once
+//!
+//! `ToolRunner` cuts any result over `MAX_OUTPUT_CHARS` and keeps the
+//! remainder under a random token along with the session (e.g. chat) that
+//! produced it; the `more_output` tool hands it back a chunk at a time, and only
+//! to that session.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use crate::Result;
+use crate::error::Error;
+use super::Tool;
+
+/// Most characters of one tool result sent to the model
+pub(crate) const MAX_OUTPUT_CHARS: usize = 20_000;
+
+/// Remainders kept at once; the oldest is dropped beyond this
+const MAX_PENDING: usize = 16;
+
+/// Name of the tool that fetches remainders
+pub(crate) const MORE_OUTPUT_TOOL: &str = "more_output";
+
+/// Parameter the runner fills in with the calling session before running `more_output`
+pub(crate) const SESSION_PARAM: &str = "session";
+
+/// Remainders of truncated outputs, shared by the runner and `more_output`
+#[derive(Clone, Default)]
+pub struct Continuations(Arc<Mutex<VecDeque<Pending>>>);
+
+struct Pending {
+    session: String,
+    token: String,
+    rest: String,
+}
+
+impl Continuations {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `output` unchanged when it fits, else its first part and a token for the
+    /// rest, which only `session` can fetch
+    pub fn paginate(&self, session: &str, output: String) -> String {
+        let total = output.chars().count();
+        if total <= MAX_OUTPUT_CHARS {
+            return output;
+        }
+
+        let limit = output.char_indices().nth(MAX_OUTPUT_CHARS).map(|(i, _)| i).unwrap_or(output.len());
+        // Prefer ending on a line break near the limit
+        let cut = output[..limit]
+            .rfind('\n')
+            .filter(|&i| i >= limit * 4 / 5)
+            .map(|i| i + 1)
+            .unwrap_or(limit);
+        let (head, rest) = output.split_at(cut);
+        let remaining = rest.chars().count();
+        let token = self.store(session, rest.to_string());
+
+        format!(
+            "{}\n\n[Output truncated: {} more chars. Call more_output with token=\"{}\" to continue]",
+            head, remaining, token
+        )
+    }
+
+    fn store(&self, session: &str, rest: String) -> String {
+        let mut pending = self.0.lock().unwrap();
+        let token = format!("out-{}", uuid::Uuid::new_v4().simple());
+        pending.push_back(Pending { session: session.to_string(), token: token.clone(), rest });
+        if pending.len() > MAX_PENDING {
+            pending.pop_front();
+        }
+        token
+    }
+
+    /// Remove and return the remainder stored under `token` by `session`
+    fn take(&self, session: &str, token: &str) -> Option<String> {
+        let mut pending = self.0.lock().unwrap();
+        let index = pending.iter().position(|p| p.session == session && p.token == token)?;
+        pending.remove(index).map(|p| p.rest)
+    }
+}
+
+/// Fetch the next part of a truncated tool output
+pub struct MoreOutputTool {
+    continuations: Continuations,
+}
+
+impl MoreOutputTool {
+    pub fn new(continuations: Continuations) -> Self {
+        Self { continuations }
+    }
+}
+
+#[async_trait]
+impl Tool for MoreOutputTool {
+    fn name(&self) -> &str { MORE_OUTPUT_TOOL }
+    fn description(&self) -> &str {
+        "Get the next part of a tool output that was truncated, using the token from its [Output truncated] note"
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "token": {
+                    "type": "string",
+                    "description": "Token from the truncation note, e.g. \"out-3f2a...\""
+                }
+            },
+            "required": ["token"]
+        })
+    }
+
+    async fn execute(&self, params: Value) -> Result<String> {
+        let token = params.get("token")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| Error::Tool("Missing 'token' parameter".to_string()))?;
+        let session = params.get(SESSION_PARAM).and_then(|v| v.as_str()).unwrap_or_default();
+
+        // The runner truncates this again if the remainder is still too long
+        self.continuations.take(session, token.trim()).ok_or_else(|| Error::Tool(format!(
+            "No pending output for token {}. It was already read or has expired; rerun the original call",
+            token
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The token in a truncation note
+    fn token_of(note: &str) -> String {
+        note.split("token=\"").nth(1).unwrap().split('"').next().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_paginate_keeps_remainder() {
+        let continuations = Continuations::new();
+        assert_eq!(continuations.paginate("cli", "short".to_string()), "short");
+
+        let line = "x".repeat(99);
+        let output = vec![line.as_str(); 300].join("\n");
+        let first = continuations.paginate("cli", output.clone());
+        assert!(first.ends_with("\" to continue]"));
+        let head = first.split_once("\n\n[Output truncated").unwrap().0;
+        assert!(head.ends_with(&format!("{}\n", line)));
+        assert!(head.chars().count() <= MAX_OUTPUT_CHARS);
+
+        let token = token_of(&first);
+        assert!(token.len() > 20);
+        let rest = continuations.take("cli", &token).unwrap();
+        assert_eq!(format!("{}{}", head, rest), output);
+        assert!(continuations.take("cli", &token).is_none());
+    }
+
+    #[test]
+    fn test_paginate_keeps_boundary_whitespace() {
+        let continuations = Continuations::new();
+        let output = format!("{}   {}", "a".repeat(MAX_OUTPUT_CHARS - 2), "b".repeat(10));
+        let first = continuations.paginate("cli", output.clone());
+        let head = first.split_once("\n\n[Output truncated").unwrap().0;
+        let rest = continuations.take("cli", &token_of(&first)).unwrap();
+        assert!(head.ends_with("a  "));
+        assert_eq!(format!("{}{}", head, rest), output);
+    }
+
+    #[test]
+    fn test_remainders_stay_with_their_session() {
+        let continuations = Continuations::new();
+        let one = token_of(&continuations.paginate("telegram:1", "x".repeat(MAX_OUTPUT_CHARS + 10)));
+        let two = token_of(&continuations.paginate("telegram:2", "y".repeat(MAX_OUTPUT_CHARS + 10)));
+
+        assert!(continuations.take("telegram:2", &one).is_none());
+        assert_eq!(continuations.take("telegram:2", &two).unwrap(), "y".repeat(10));
+        assert_eq!(continuations.take("telegram:1", &one).unwrap(), "x".repeat(10));
+    }
+
+    #[tokio::test]
+    async fn test_more_output_tool() {
+        let continuations = Continuations::new();
+        let tool = MoreOutputTool::new(continuations.clone());
+        let token = token_of(&continuations.paginate("cli", "é".repeat(MAX_OUTPUT_CHARS + 10)));
+
+        assert!(tool.execute(json!({ "token": token, "session": "other" })).await.is_err());
+        assert_eq!(tool.execute(json!({ "token": token, "session": "cli" })).await.unwrap(), "é".repeat(10));
+        assert!(tool.execute(json!({ "token": token, "session": "cli" })).await.is_err());
+    }
+}
@@ -0,0 +1,212 @@
+//! Count tool - line, word, character and token counts for files
+
+use std::path::{Path, PathBuf};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use crate::Result;
+use crate::agent::tokens::estimate_tokens;
+use crate::error::Error;
+use super::find::FindFilesTool;
+use super::{Tool, resolve_path};
+
+/// Most files counted for one glob
+const MAX_FILES: usize = 500;
+
+/// Count lines, words, characters or tokens in files (like `wc`)
+pub struct CountTool {
+    workspace: PathBuf,
+}
+
+/// Counts for one file, or the total
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+struct Counts {
+    lines: usize,
+    words: usize,
+    chars: usize,
+    tokens: usize,
+}
+
+impl Counts {
+    fn of(text: &str) -> Self {
+        Self {
+            lines: text.lines().count(),
+            words: text.split_whitespace().count(),
+            chars: text.chars().count(),
+            tokens: estimate_tokens(text),
+        }
+    }
+
+    fn add(&mut self, other: Counts) {
+        self.lines += other.lines;
+        self.words += other.words;
+        self.chars += other.chars;
+        self.tokens += other.tokens;
+    }
+
+    fn describe(&self, mode: &str) -> String {
+        match mode {
+            "lines" => format!("{} lines", self.lines),
+            "words" => format!("{} words", self.words),
+            "chars" => format!("{} chars", self.chars),
+            "tokens" => format!("~{} tokens", self.tokens),
+            _ => format!(
+                "{} lines, {} words, {} chars, ~{} tokens",
+                self.lines, self.words, self.chars, self.tokens
+            ),
+        }
+    }
+}
+
+impl CountTool {
+    pub fn new(workspace: PathBuf) -> Self {
+        Self { workspace }
+    }
+
+    /// Files matched by `pattern`: a plain path, `dir/*.ext`, or `dir/**/*.ext` to recurse
+    fn expand(&self, pattern: &str) -> Result<Vec<PathBuf>> {
+        if !pattern.contains(['*', '?']) {
+            let path = resolve_path(&self.workspace, pattern);
+            if !path.is_file() {
+                return Err(Error::Tool(format!("File not found: {}", pattern)));
+            }
+            return Ok(vec![path]);
+        }
+
+        let (dir, name_pattern) = pattern.rsplit_once('/').unwrap_or(("", pattern));
+        let (dir, recursive) = match dir.strip_suffix("**") {
+            Some(base) => (base.trim_end_matches('/'), true),
+            None => (dir, false),
+        };
+        if dir.contains(['*', '?']) {
+            return Err(Error::Tool(format!(
+                "Unsupported pattern '{}': wildcards only in the file name, or 'dir/**/*.ext'", pattern
+            )));
+        }
+
+        let base = if dir.is_empty() { self.workspace.clone() } else { resolve_path(&self.workspace, dir) };
+        let mut files = Vec::new();
+        collect_files(&base, name_pattern, recursive, &mut files)?;
+        files.sort();
+        Ok(files)
+    }
+}
+
+fn collect_files(dir: &Path, pattern: &str, recursive: bool, files: &mut Vec<PathBuf>) -> Result<()> {
+    let entries = std::fs::read_dir(dir)
+        .map_err(|e| Error::Tool(format!("Failed to read {}: {}", dir.display(), e)))?;
+    for entry in entries.filter_map(|e| e.ok()) {
+        if files.len() >= MAX_FILES {
+            break;
+        }
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with('.') {
+            continue;
+        }
+        let Ok(file_type) = entry.file_type() else { continue };
+        if file_type.is_dir() {
+            if recursive {
+                collect_files(&entry.path(), pattern, recursive, files)?;
+            }
+        } else if FindFilesTool::matches_glob(&name, pattern) {
+            files.push(entry.path());
+        }
+    }
+    Ok(())
+}
+
+#[async_trait]
+impl Tool for CountTool {
+    fn name(&self) -> &str { "count" }
+    fn description(&self) -> &str {
+        "Count lines, words, characters and estimated tokens in a file or a glob of files (like wc)"
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "File path or glob, e.g. 'notes.md', 'src/*.rs' or 'src/**/*.rs'"
+                },
+                "mode": {
+                    "type": "string",
+                    "enum": ["all", "lines", "words", "chars", "tokens"],
+                    "description": "What to count (default: all)"
+                }
+            },
+            "required": ["path"]
+        })
+    }
+
+    async fn execute(&self, params: Value) -> Result<String> {
+        let path = params.get("path")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| Error::Tool("Missing 'path' parameter".to_string()))?;
+
+        let mode = params.get("mode")
+            .and_then(|v| v.as_str())
+            .unwrap_or("all");
+        if !["all", "lines", "words", "chars", "tokens"].contains(&mode) {
+            return Err(Error::Tool(format!(
+                "Unknown mode: {}. Use lines, words, chars, tokens or all", mode
+            )));
+        }
+
+        let files = self.expand(path)?;
+        if files.is_empty() {
+            return Ok(format!("No files match {}", path));
+        }
+
+        let mut total = Counts::default();
+        let mut lines = Vec::with_capacity(files.len() + 1);
+        for file in &files {
+            let bytes = tokio::fs::read(file).await?;
+            let counts = Counts::of(&String::from_utf8_lossy(&bytes));
+            total.add(counts);
+            let shown = file.strip_prefix(&self.workspace).unwrap_or(file);
+            lines.push(format!("{}: {}", shown.display(), counts.describe(mode)));
+        }
+
+        if files.len() > 1 {
+            let capped = if files.len() >= MAX_FILES { " (file limit reached)" } else { "" };
+            lines.push(format!("Total ({} files{}): {}", files.len(), capped, total.describe(mode)));
+        }
+        Ok(lines.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_counts() {
+        let counts = Counts::of("one two\nthree\n");
+        assert_eq!(counts, Counts { lines: 2, words: 3, chars: 14, tokens: 4 });
+        assert_eq!(counts.describe("words"), "3 words");
+    }
+
+    #[tokio::test]
+    async fn test_count_glob() {
+        let tmp = TempDir::new().unwrap();
+        std::fs::create_dir_all(tmp.path().join("src/nested")).unwrap();
+        std::fs::write(tmp.path().join("src/a.rs"), "fn a() {}\n").unwrap();
+        std::fs::write(tmp.path().join("src/nested/b.rs"), "fn b() {}\nfn c() {}\n").unwrap();
+        std::fs::write(tmp.path().join("src/notes.md"), "skip me\n").unwrap();
+        let tool = CountTool::new(tmp.path().to_path_buf());
+
+        let single = tool.execute(json!({ "path": "src/a.rs", "mode": "lines" })).await.unwrap();
+        assert_eq!(single, "src/a.rs: 1 lines");
+
+        let flat = tool.execute(json!({ "path": "src/*.rs", "mode": "lines" })).await.unwrap();
+        assert_eq!(flat, "src/a.rs: 1 lines");
+
+        let deep = tool.execute(json!({ "path": "src/**/*.rs", "mode": "lines" })).await.unwrap();
+        assert!(deep.contains("src/nested/b.rs: 2 lines"));
+        assert!(deep.ends_with("Total (2 files): 3 lines"));
+
+        assert!(tool.execute(json!({ "path": "missing.txt" })).await.is_err());
+    }
+}
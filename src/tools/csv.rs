@@ -0,0 +1,357 @@
+//! CSV tool - query tabular data without loading it into context
+
+use std::path::PathBuf;
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use crate::Result;
+use crate::error::Error;
+use super::{Tool, resolve_path};
+
+/// Maximum rows rendered in a single result table
+const MAX_ROWS: usize = 100;
+
+/// Query CSV files: head/tail, column selection, filtering, aggregation
+pub struct CsvTool {
+    workspace: PathBuf,
+}
+
+/// A parsed `column op value` predicate
+struct Predicate {
+    column: usize,
+    op: String,
+    value: String,
+}
+
+impl Predicate {
+    fn parse(expr: &str, headers: &[String]) -> Result<Self> {
+        // Two-character operators before their one-character prefixes; `=` means `==`
+        const OPS: [&str; 8] = [">=", "<=", "!=", "==", ">", "<", "=", " contains "];
+
+        // Split at the leftmost operator, so operators inside the value are kept
+        let found = expr.char_indices()
+            .find_map(|(i, _)| OPS.iter().find(|op| expr[i..].starts_with(**op)).map(|op| (i, *op)));
+        if let Some((i, op)) = found {
+            let col = expr[..i].trim();
+            let column = headers.iter().position(|h| h == col)
+                .ok_or_else(|| Error::Tool(format!("Unknown column in filter: {}", col)))?;
+            let value = expr[i + op.len()..].trim().trim_matches(['"', '\'']);
+            return Ok(Self {
+                column,
+                op: if op == "=" { "==".to_string() } else { op.trim().to_string() },
+                value: value.to_string(),
+            });
+        }
+
+        Err(Error::Tool(format!(
+            "Invalid filter '{}'. Use 'column op value' with op one of ==, =, !=, >, <, >=, <=, contains",
+            expr
+        )))
+    }
+
+    fn matches(&self, row: &[String]) -> bool {
+        let Some(cell) = row.get(self.column) else {
+            return false;
+        };
+
+        if self.op == "contains" {
+            return cell.to_lowercase().contains(&self.value.to_lowercase());
+        }
+
+        // Compare numerically when both sides are numbers, otherwise as strings
+        let ordering = match (cell.trim().parse::<f64>(), self.value.parse::<f64>()) {
+            (Ok(a), Ok(b)) => a.partial_cmp(&b),
+            _ => Some(cell.as_str().cmp(self.value.as_str())),
+        };
+
+        let Some(ordering) = ordering else {
+            return false;
+        };
+
+        match self.op.as_str() {
+            "==" => ordering.is_eq(),
+            "!=" => ordering.is_ne(),
+            ">" => ordering.is_gt(),
+            "<" => ordering.is_lt(),
+            ">=" => ordering.is_ge(),
+            "<=" => ordering.is_le(),
+            _ => false,
+        }
+    }
+}
+
+impl CsvTool {
+    pub fn new(workspace: PathBuf) -> Self {
+        Self { workspace }
+    }
+
+    fn load(&self, path: &str) -> Result<(Vec<String>, Vec<Vec<String>>)> {
+        let file_path = resolve_path(&self.workspace, path);
+        let mut reader = ::csv::ReaderBuilder::new()
+            .flexible(true)
+            .from_path(&file_path)
+            .map_err(|e| Error::Tool(format!("Failed to open {}: {}", path, e)))?;
+
+        let headers = reader.headers()
+            .map_err(|e| Error::Tool(format!("Failed to read CSV header: {}", e)))?
+            .iter()
+            .map(|h| h.trim().to_string())
+            .collect();
+
+        let mut rows = Vec::new();
+        for record in reader.records() {
+            let record = record.map_err(|e| Error::Tool(format!("Failed to parse CSV: {}", e)))?;
+            rows.push(record.iter().map(|c| c.to_string()).collect());
+        }
+
+        Ok((headers, rows))
+    }
+
+    /// Resolve column names to indices (all columns when none requested)
+    fn select_columns(headers: &[String], columns: &[String]) -> Result<Vec<usize>> {
+        if columns.is_empty() {
+            return Ok((0..headers.len()).collect());
+        }
+        columns.iter()
+            .map(|c| headers.iter().position(|h| h == c)
+                .ok_or_else(|| Error::Tool(format!("Unknown column: {}", c))))
+            .collect()
+    }
+
+    fn aggregate(func: &str, column: &str, headers: &[String], rows: &[&Vec<String>]) -> Result<String> {
+        if func == "count" {
+            return Ok(format!("count = {}", rows.len()));
+        }
+
+        let idx = headers.iter().position(|h| h == column)
+            .ok_or_else(|| Error::Tool(format!("Unknown column: {}", column)))?;
+
+        let values: Vec<f64> = rows.iter()
+            .filter_map(|r| r.get(idx))
+            .filter_map(|c| c.trim().parse::<f64>().ok())
+            .collect();
+
+        if values.is_empty() {
+            return Ok(format!("No numeric values in column '{}'", column));
+        }
+
+        let sum: f64 = values.iter().sum();
+        let result = match func {
+            "sum" => sum,
+            "avg" => sum / values.len() as f64,
+            "min" => values.iter().cloned().fold(f64::INFINITY, f64::min),
+            "max" => values.iter().cloned().fold(f64::NEG_INFINITY, f64::max),
+            other => return Err(Error::Tool(format!("Unknown aggregate function: {}", other))),
+        };
+
+        Ok(format!("{}({}) = {} (over {} rows)", func, column, result, values.len()))
+    }
+}
+
+/// Render rows as a Markdown table
+fn to_markdown(headers: &[String], rows: &[&Vec<String>], columns: &[usize]) -> String {
+    let escape = |s: &str| s.replace('|', "\\|").replace('\n', " ");
+
+    let mut lines = Vec::with_capacity(rows.len() + 2);
+    lines.push(format!("| {} |", columns.iter().map(|&i| escape(&headers[i])).collect::<Vec<_>>().join(" | ")));
+    lines.push(format!("|{}", " --- |".repeat(columns.len())));
+    for row in rows {
+        let cells: Vec<String> = columns.iter()
+            .map(|&i| escape(row.get(i).map(|s| s.as_str()).unwrap_or("")))
+            .collect();
+        lines.push(format!("| {} |", cells.join(" | ")));
+    }
+    lines.join("\n")
+}
+
+#[async_trait]
+impl Tool for CsvTool {
+    fn name(&self) -> &str { "csv" }
+    fn description(&self) -> &str {
+        "Query a CSV file: head/tail rows, select columns, filter rows, or aggregate (sum/avg/count/min/max). Returns a Markdown table."
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "Path to the CSV file (relative paths resolve against the workspace)"
+                },
+                "operation": {
+                    "type": "string",
+                    "enum": ["head", "tail", "filter", "aggregate", "columns"],
+                    "description": "Operation to perform (default: head)"
+                },
+                "columns": {
+                    "type": "string",
+                    "description": "Comma-separated columns to include (optional, defaults to all)"
+                },
+                "where": {
+                    "type": "string",
+                    "description": "Row filter like 'age > 30' or 'city contains york' (ops: ==, !=, >, <, >=, <=, contains)"
+                },
+                "function": {
+                    "type": "string",
+                    "enum": ["sum", "avg", "count", "min", "max"],
+                    "description": "Aggregate function (for 'aggregate')"
+                },
+                "column": {
+                    "type": "string",
+                    "description": "Column to aggregate (for 'aggregate', not needed for count)"
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": "Number of rows to return (default: 10, max 100)"
+                }
+            },
+            "required": ["path"]
+        })
+    }
+
+    async fn execute(&self, params: Value) -> Result<String> {
+        let path = params.get("path")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| Error::Tool("Missing 'path' parameter".to_string()))?;
+
+        let operation = params.get("operation")
+            .and_then(|v| v.as_str())
+            .unwrap_or("head");
+
+        let columns: Vec<String> = params.get("columns")
+            .and_then(|v| v.as_str())
+            .map(|s| s.split(',').map(|c| c.trim().to_string()).filter(|c| !c.is_empty()).collect())
+            .unwrap_or_default();
+
+        let limit = (params.get("limit")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(10) as usize)
+            .min(MAX_ROWS);
+
+        let (headers, rows) = self.load(path)?;
+
+        if operation == "columns" {
+            return Ok(format!("{} rows, columns: {}", rows.len(), headers.join(", ")));
+        }
+
+        let predicate = params.get("where")
+            .and_then(|v| v.as_str())
+            .map(|w| Predicate::parse(w, &headers))
+            .transpose()?;
+
+        let matching: Vec<&Vec<String>> = rows.iter()
+            .filter(|r| predicate.as_ref().map(|p| p.matches(r)).unwrap_or(true))
+            .collect();
+
+        match operation {
+            "aggregate" => {
+                let func = params.get("function")
+                    .and_then(|v| v.as_str())
+                    .ok_or_else(|| Error::Tool("Missing 'function' parameter for aggregate".to_string()))?;
+                let column = params.get("column")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("");
+                Self::aggregate(func, column, &headers, &matching)
+            }
+            "head" | "filter" | "tail" => {
+                let selected = Self::select_columns(&headers, &columns)?;
+                let total = matching.len();
+                let shown: Vec<&Vec<String>> = if operation == "tail" {
+                    matching[total.saturating_sub(limit)..].to_vec()
+                } else {
+                    matching.into_iter().take(limit).collect()
+                };
+
+                if shown.is_empty() {
+                    return Ok("No matching rows.".to_string());
+                }
+
+                Ok(format!(
+                    "{}\n\n(showing {} of {} rows)",
+                    to_markdown(&headers, &shown, &selected),
+                    shown.len(),
+                    total
+                ))
+            }
+            other => Err(Error::Tool(format!("Unknown operation: {}", other))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn sample() -> (TempDir, CsvTool) {
+        let tmp = TempDir::new().unwrap();
+        std::fs::write(
+            tmp.path().join("people.csv"),
+            "name,age,city\nAlice,34,New York\nBob,27,Boston\nCara,41,New York\n",
+        ).unwrap();
+        let tool = CsvTool::new(tmp.path().to_path_buf());
+        (tmp, tool)
+    }
+
+    #[tokio::test]
+    async fn test_csv_head_with_columns() {
+        let (_tmp, tool) = sample();
+        let result = tool.execute(json!({
+            "path": "people.csv",
+            "columns": "name,city",
+            "limit": 2
+        })).await.unwrap();
+
+        assert!(result.contains("| name | city |"));
+        assert!(result.contains("| Alice | New York |"));
+        assert!(!result.contains("Cara"));
+        assert!(result.contains("showing 2 of 3 rows"));
+    }
+
+    #[test]
+    fn test_predicate_operators() {
+        let headers = vec!["a".to_string(), "note".to_string()];
+        let parse = |expr| Predicate::parse(expr, &headers).unwrap();
+        for (expr, op, value) in [
+            ("a>=1", ">=", "1"),
+            ("a != 1", "!=", "1"),
+            ("a<=2", "<=", "2"),
+            ("a=3", "==", "3"),
+            ("a > 4", ">", "4"),
+            ("note == x>=y", "==", "x>=y"),
+            ("note contains 'hi'", "contains", "hi"),
+        ] {
+            let p = parse(expr);
+            assert_eq!((p.op.as_str(), p.value.as_str()), (op, value), "{}", expr);
+        }
+        assert_eq!(parse("note contains a").column, 1);
+    }
+
+    #[tokio::test]
+    async fn test_csv_filter() {
+        let (_tmp, tool) = sample();
+        let result = tool.execute(json!({
+            "path": "people.csv",
+            "operation": "filter",
+            "where": "age > 30"
+        })).await.unwrap();
+
+        assert!(result.contains("Alice"));
+        assert!(result.contains("Cara"));
+        assert!(!result.contains("Bob"));
+    }
+
+    #[tokio::test]
+    async fn test_csv_aggregate() {
+        let (_tmp, tool) = sample();
+        let result = tool.execute(json!({
+            "path": "people.csv",
+            "operation": "aggregate",
+            "function": "avg",
+            "column": "age",
+            "where": "city == New York"
+        })).await.unwrap();
+
+        assert!(result.contains("avg(age) = 37.5"));
+    }
+}
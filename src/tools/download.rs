@@ -0,0 +1,260 @@
+//! Download tool - save a URL to a file
+
+use std::path::{Component, Path, PathBuf};
+use std::time::Duration;
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use tokio::io::AsyncWriteExt;
+use crate::Result;
+use crate::error::Error;
+use super::{DangerLevel, Tool, resolve_path};
+use super::web::fetch_error;
+
+/// Largest download accepted (100 MB)
+const MAX_DOWNLOAD_BYTES: u64 = 100 * 1024 * 1024;
+
+/// Time allowed for the whole download
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Download a URL (binary or text) to a file
+pub struct DownloadTool {
+    workspace: PathBuf,
+    max_bytes: u64,
+}
+
+impl DownloadTool {
+    pub fn new(workspace: PathBuf) -> Self {
+        Self { workspace, max_bytes: MAX_DOWNLOAD_BYTES }
+    }
+
+    /// Refuse targets outside the workspace, following symlinks in the part of the path that exists
+    fn check_scope(&self, target: &Path) -> Result<()> {
+        let workspace = self.workspace.canonicalize()?;
+        let mut existing = target.parent();
+        while let Some(dir) = existing.filter(|dir| !dir.exists()) {
+            existing = dir.parent();
+        }
+        let inside = match existing {
+            Some(dir) => {
+                let rest = target.strip_prefix(dir).unwrap_or(target);
+                dir.canonicalize()?.starts_with(&workspace)
+                    && !rest.components().any(|c| matches!(c, Component::ParentDir))
+            }
+            None => false,
+        };
+        if inside {
+            Ok(())
+        } else {
+            Err(Error::Tool(format!(
+                "{} is outside the workspace ({})", target.display(), workspace.display()
+            )))
+        }
+    }
+
+    /// Stream the response body into `path`, returning bytes written and content type
+    async fn download(&self, url: &str, path: &Path) -> Result<(u64, String)> {
+        let client = reqwest::Client::builder()
+            .timeout(DOWNLOAD_TIMEOUT)
+            .build()
+            .map_err(|e| Error::Tool(format!("Failed to create HTTP client: {}", e)))?;
+
+        let mut response = client.get(url)
+            .header("User-Agent", "leo/1.0")
+            .send()
+            .await
+            .map_err(|e| fetch_error(url, e))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            return Err(Error::Tool(format!("HTTP error: {}", status)));
+        }
+
+        if let Some(len) = response.content_length() {
+            if len > self.max_bytes {
+                return Err(Error::Tool(format!(
+                    "Download is {} bytes, over the {} byte limit", len, self.max_bytes
+                )));
+            }
+        }
+
+        let content_type = response.headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("unknown")
+            .to_string();
+
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let mut file = tokio::fs::File::create(path).await?;
+
+        let mut written: u64 = 0;
+        while let Some(chunk) = response.chunk().await
+            .map_err(|e| if e.is_timeout() {
+                Error::Timeout(format!("downloading {} after {}s", url, DOWNLOAD_TIMEOUT.as_secs()))
+            } else {
+                Error::Tool(format!("Download interrupted: {}", e))
+            })?
+        {
+            written += chunk.len() as u64;
+            if written > self.max_bytes {
+                return Err(Error::Tool(format!(
+                    "Download exceeded the {} byte limit", self.max_bytes
+                )));
+            }
+            file.write_all(&chunk).await?;
+        }
+        file.flush().await?;
+
+        Ok((written, content_type))
+    }
+}
+
+#[async_trait]
+impl Tool for DownloadTool {
+    fn name(&self) -> &str { "download" }
+    fn description(&self) -> &str {
+        "Download a URL (PDF, image, archive, any file) and save it to disk. Returns bytes written and content type"
+    }
+
+    fn is_mutating(&self, _params: &Value) -> bool { true }
+
+    fn danger_level(&self) -> DangerLevel { DangerLevel::Caution }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "url": {
+                    "type": "string",
+                    "description": "http(s) URL to download"
+                },
+                "path": {
+                    "type": "string",
+                    "description": "Where to save the file inside the workspace (e.g. 'downloads/report.pdf')"
+                }
+            },
+            "required": ["url", "path"]
+        })
+    }
+
+    async fn execute(&self, params: Value) -> Result<String> {
+        let url = params.get("url")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| Error::Tool("Missing 'url' parameter".to_string()))?;
+
+        let path = params.get("path")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| Error::Tool("Missing 'path' parameter".to_string()))?;
+
+        if !url.starts_with("http://") && !url.starts_with("https://") {
+            return Err(Error::Tool(format!("Only http(s) URLs can be downloaded: {}", url)));
+        }
+
+        let target = resolve_path(&self.workspace, path);
+        self.check_scope(&target)?;
+        let file_name = target.file_name()
+            .ok_or_else(|| Error::Tool(format!("Not a file path: {}", path)))?;
+        // Stream into a sibling so a failed download leaves any existing file alone
+        let partial = target.with_file_name(format!(
+            "{}.part-{}", file_name.to_string_lossy(), uuid::Uuid::new_v4()
+        ));
+        let result = match self.download(url, &partial).await {
+            Ok(done) => tokio::fs::rename(&partial, &target).await.map(|_| done).map_err(Error::from),
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok((bytes, content_type)) => Ok(format!(
+                "Downloaded {} bytes ({}) to {}", bytes, content_type, target.display()
+            )),
+            Err(e) => {
+                // Don't leave a partial file behind
+                let _ = tokio::fs::remove_file(&partial).await;
+                Err(e)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+    use tokio::io::AsyncReadExt;
+
+    /// Serve a single response with `body` on a local port
+    async fn serve_once(body: &'static [u8]) -> std::net::SocketAddr {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; 1024];
+            let _ = socket.read(&mut buf).await;
+            let header = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/pdf\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                body.len()
+            );
+            socket.write_all(header.as_bytes()).await.unwrap();
+            socket.write_all(body).await.unwrap();
+        });
+        addr
+    }
+
+    #[tokio::test]
+    async fn test_download_to_file() {
+        let addr = serve_once(b"%PDF-1.4\x00\x01binary").await;
+        let tmp = TempDir::new().unwrap();
+        let tool = DownloadTool::new(tmp.path().to_path_buf());
+
+        let result = tool.execute(json!({
+            "url": format!("http://{}/report.pdf", addr),
+            "path": "files/report.pdf"
+        })).await.unwrap();
+
+        assert!(result.contains("Downloaded 16 bytes (application/pdf)"));
+        let saved = std::fs::read(tmp.path().join("files/report.pdf")).unwrap();
+        assert_eq!(saved, b"%PDF-1.4\x00\x01binary");
+    }
+
+    #[tokio::test]
+    async fn test_download_over_limit() {
+        let addr = serve_once(b"0123456789").await;
+        let tmp = TempDir::new().unwrap();
+        let mut tool = DownloadTool::new(tmp.path().to_path_buf());
+        tool.max_bytes = 4;
+
+        std::fs::write(tmp.path().join("big.bin"), "keep me").unwrap();
+
+        let err = tool.execute(json!({
+            "url": format!("http://{}/big", addr),
+            "path": "big.bin"
+        })).await.unwrap_err();
+
+        assert!(err.to_string().contains("byte limit"));
+        // The existing file is untouched and no partial file is left
+        assert_eq!(std::fs::read_to_string(tmp.path().join("big.bin")).unwrap(), "keep me");
+        assert_eq!(std::fs::read_dir(tmp.path()).unwrap().count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_download_outside_workspace_refused() {
+        let tmp = TempDir::new().unwrap();
+        let outside = TempDir::new().unwrap();
+        let tool = DownloadTool::new(tmp.path().join("ws"));
+        std::fs::create_dir(tmp.path().join("ws")).unwrap();
+
+        for path in [
+            outside.path().join("report.pdf").to_string_lossy().to_string(),
+            "../report.pdf".to_string(),
+            "new/../../report.pdf".to_string(),
+        ] {
+            let err = tool.execute(json!({
+                "url": "http://127.0.0.1:1/report.pdf",
+                "path": path
+            })).await.unwrap_err();
+            assert!(err.to_string().contains("outside the workspace"), "{}: {}", path, err);
+        }
+        assert_eq!(std::fs::read_dir(outside.path()).unwrap().count(), 0);
+        assert_eq!(std::fs::read_dir(tmp.path()).unwrap().count(), 1);
+    }
+}
@@ -1,19 +1,32 @@
 //! Edit tool - replace content in files
 
+use std::path::PathBuf;
 use async_trait::async_trait;
 use serde_json::{json, Value};
 use crate::Result;
 use crate::error::Error;
-use super::Tool;
+use super::{DangerLevel, Tool, resolve_path};
 
 /// Edit file content (replace string)
-pub struct EditTool;
+pub struct EditTool {
+    workspace: PathBuf,
+}
+
+impl EditTool {
+    pub fn new(workspace: PathBuf) -> Self {
+        Self { workspace }
+    }
+}
 
 #[async_trait]
 impl Tool for EditTool {
     fn name(&self) -> &str { "edit_file" }
     fn description(&self) -> &str { "Replace text in a file" }
     
+    fn is_mutating(&self, _params: &Value) -> bool { true }
+
+    fn danger_level(&self) -> DangerLevel { DangerLevel::Caution }
+
     fn parameters(&self) -> Value {
         json!({
             "type": "object",
@@ -48,8 +61,10 @@ impl Tool for EditTool {
             .and_then(|v| v.as_str())
             .ok_or_else(|| Error::Tool("Missing 'replacement' parameter".to_string()))?;
             
+        let file_path = resolve_path(&self.workspace, path);
+        
         // Read file
-        let content = std::fs::read_to_string(path)
+        let content = std::fs::read_to_string(&file_path)
             .map_err(|e| Error::Tool(format!("Failed to read {}: {}", path, e)))?;
             
         // Check if target exists
@@ -72,7 +87,7 @@ impl Tool for EditTool {
         let new_content = content.replace(target, replacement);
         
         // Write back
-        std::fs::write(path, &new_content)
+        std::fs::write(&file_path, &new_content)
             .map_err(|e| Error::Tool(format!("Failed to write {}: {}", path, e)))?;
             
         Ok(format!("Successfully replaced {} occurrence(s) in {}", count, path))
@@ -1,18 +1,43 @@
 //! Filesystem tools - read, write, and list files
 
+use std::path::PathBuf;
 use async_trait::async_trait;
 use serde_json::{json, Value};
+use similar::{ChangeTag, TextDiff};
 use crate::Result;
 use crate::error::Error;
-use super::Tool;
+use super::{DangerLevel, Tool, Page, resolve_path};
+use super::web::fetch_text;
+
+/// Maximum characters returned when `read_file` is pointed at a URL
+const MAX_URL_CHARS: usize = 10000;
+
+/// Maximum characters of unified diff shown in a write preview
+const MAX_DIFF_CHARS: usize = 4000;
+
+/// Lines `read_file` returns per page by default
+const DEFAULT_READ_LINES: usize = 1000;
 
 /// Read file contents
-pub struct ReadFileTool;
+pub struct ReadFileTool {
+    workspace: PathBuf,
+}
+
+impl ReadFileTool {
+    pub fn new(workspace: PathBuf) -> Self {
+        Self { workspace }
+    }
+}
 
 #[async_trait]
 impl Tool for ReadFileTool {
     fn name(&self) -> &str { "read_file" }
-    fn description(&self) -> &str { "Read the contents of a file at the specified path" }
+    fn description(&self) -> &str {
+        "Read the contents of a file at the specified path (http/https URLs are fetched as text). \
+         Long files are returned in pages of lines; pass the offset from the footer to read on"
+    }
+    
+    fn cacheable(&self) -> bool { true }
     
     fn parameters(&self) -> Value {
         json!({
@@ -20,7 +45,15 @@ impl Tool for ReadFileTool {
             "properties": {
                 "path": {
                     "type": "string",
-                    "description": "Path to the file to read"
+                    "description": "Path to the file to read, or an http(s) URL"
+                },
+                "offset": {
+                    "type": "integer",
+                    "description": "Skip this many lines (for paging)"
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": "Most lines to return (default 1000)"
                 }
             },
             "required": ["path"]
@@ -32,19 +65,46 @@ impl Tool for ReadFileTool {
             .and_then(|v| v.as_str())
             .ok_or_else(|| Error::Tool("Missing 'path' parameter".to_string()))?;
         
-        std::fs::read_to_string(path)
-            .map_err(|e| Error::Tool(format!("Failed to read {}: {}", path, e)))
+        if path.starts_with("http://") || path.starts_with("https://") {
+            return fetch_text(path, MAX_URL_CHARS).await;
+        }
+        
+        let content = std::fs::read_to_string(resolve_path(&self.workspace, path))
+            .map_err(|e| Error::Tool(format!("Failed to read {}: {}", path, e)))?;
+        
+        let page = Page::from_params(&params, DEFAULT_READ_LINES);
+        let lines: Vec<String> = content.lines().map(String::from).collect();
+        if page.offset == 0 && lines.len() <= page.limit {
+            return Ok(content);
+        }
+        Ok(page.apply(&lines, "lines"))
     }
 }
 
 /// Write content to a file
-pub struct WriteFileTool;
+pub struct WriteFileTool {
+    workspace: PathBuf,
+}
+
+impl WriteFileTool {
+    pub fn new(workspace: PathBuf) -> Self {
+        Self { workspace }
+    }
+}
 
 #[async_trait]
 impl Tool for WriteFileTool {
     fn name(&self) -> &str { "write_file" }
-    fn description(&self) -> &str { "Write content to a file at the specified path" }
+    fn description(&self) -> &str {
+        "Write content to a file at the specified path. Overwrites report a diff summary; use preview_only to see the diff without writing"
+    }
     
+    fn is_mutating(&self, params: &Value) -> bool {
+        !params.get("preview_only").and_then(|v| v.as_bool()).unwrap_or(false)
+    }
+
+    fn danger_level(&self) -> DangerLevel { DangerLevel::Caution }
+
     fn parameters(&self) -> Value {
         json!({
             "type": "object",
@@ -56,6 +116,10 @@ impl Tool for WriteFileTool {
                 "content": {
                     "type": "string",
                     "description": "Content to write to the file"
+                },
+                "preview_only": {
+                    "type": "boolean",
+                    "description": "Show the diff against the existing file without writing (default: false)"
                 }
             },
             "required": ["path", "content"]
@@ -71,23 +135,104 @@ impl Tool for WriteFileTool {
             .and_then(|v| v.as_str())
             .ok_or_else(|| Error::Tool("Missing 'content' parameter".to_string()))?;
         
+        let preview_only = params.get("preview_only")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        
+        let file_path = resolve_path(&self.workspace, path);
+        let existing = if file_path.is_file() {
+            Some(std::fs::read_to_string(&file_path)
+                .map_err(|e| Error::Tool(format!("Failed to read {}: {}", path, e)))?)
+        } else {
+            None
+        };
+        
+        if preview_only {
+            return Ok(match existing {
+                Some(old) => format!("Preview of {} (not written): {}", path, diff_summary(path, &old, content, true)),
+                None => format!("{} does not exist; writing would create it with {} bytes", path, content.len()),
+            });
+        }
+        
         // Create parent directories if needed
-        if let Some(parent) = std::path::Path::new(path).parent() {
+        if let Some(parent) = file_path.parent() {
             std::fs::create_dir_all(parent)
                 .map_err(|e| Error::Tool(format!("Failed to create directory: {}", e)))?;
         }
         
-        std::fs::write(path, content)
+        std::fs::write(&file_path, content)
             .map_err(|e| Error::Tool(format!("Failed to write {}: {}", path, e)))?;
         
-        Ok(format!("Successfully wrote {} bytes to {}", content.len(), path))
+        let written = format!("Successfully wrote {} bytes to {}", content.len(), path);
+        Ok(match existing {
+            Some(old) => format!("{} (overwrote existing file: {})", written, diff_summary(path, &old, content, false)),
+            None => written,
+        })
+    }
+}
+
+/// Summarize the change from `old` to `new`, optionally with the unified diff
+fn diff_summary(path: &str, old: &str, new: &str, with_diff: bool) -> String {
+    let diff = TextDiff::from_lines(old, new);
+    let (mut added, mut removed) = (0, 0);
+    for change in diff.iter_all_changes() {
+        match change.tag() {
+            ChangeTag::Insert => added += 1,
+            ChangeTag::Delete => removed += 1,
+            ChangeTag::Equal => {}
+        }
+    }
+    
+    if added == 0 && removed == 0 {
+        return "No changes (content is identical)".to_string();
+    }
+    
+    let summary = format!("{} line(s) added, {} line(s) removed", added, removed);
+    if !with_diff {
+        return summary;
     }
+    
+    let mut unified = diff.unified_diff().header(path, path).to_string();
+    if unified.len() > MAX_DIFF_CHARS {
+        let mut end = MAX_DIFF_CHARS;
+        while !unified.is_char_boundary(end) {
+            end -= 1;
+        }
+        unified.truncate(end);
+        unified.push_str("\n[Diff truncated]");
+    }
+    format!("{}\n\n{}", summary, unified)
 }
 
+/// Entries `list_dir` returns when no `limit` is given
+const DEFAULT_LIST_LIMIT: usize = 200;
+
 /// List directory contents
-pub struct ListDirTool;
+pub struct ListDirTool {
+    workspace: PathBuf,
+}
 
 impl ListDirTool {
+    pub fn new(workspace: PathBuf) -> Self {
+        Self { workspace }
+    }
+    
+    /// Symlink target of `path`, or `None` if it is not a symlink
+    ///
+    /// Uses `symlink_metadata` so the link itself is inspected, not what it points to.
+    fn symlink_target(path: &std::path::Path) -> Option<String> {
+        let meta = std::fs::symlink_metadata(path).ok()?;
+        if !meta.file_type().is_symlink() {
+            return None;
+        }
+        let target = std::fs::read_link(path)
+            .map(|t| t.display().to_string())
+            .unwrap_or_else(|_| "?".to_string());
+        // `is_dir` follows the link; broken links are just shown with their target
+        let slash = if path.is_dir() { "/" } else { "" };
+        Some(format!("{}{}", target, slash))
+    }
+    
     fn list_recursive(
         path: &std::path::Path,
         prefix: &str,
@@ -115,6 +260,12 @@ impl ListDirTool {
                 continue;
             }
             
+            // Never follow symlinks while walking, so links to an ancestor can't loop
+            if let Some(target) = Self::symlink_target(&entry.path()) {
+                results.push(format!("{}{} -> {}", prefix, name, target));
+                continue;
+            }
+            
             let is_dir = entry.file_type().map(|ft| ft.is_dir()).unwrap_or(false);
             
             if is_dir {
@@ -169,6 +320,14 @@ impl Tool for ListDirTool {
                 "max_depth": {
                     "type": "integer",
                     "description": "Max depth for recursive listing (default: 3)"
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": "Max entries to return (default: 200)"
+                },
+                "offset": {
+                    "type": "integer",
+                    "description": "Entries to skip, for paging through large directories (default: 0)"
                 }
             },
             "required": ["path"]
@@ -192,7 +351,9 @@ impl Tool for ListDirTool {
             .and_then(|v| v.as_u64())
             .unwrap_or(3) as usize;
         
-        let dir_path = std::path::Path::new(path);
+        let page = Page::from_params(&params, DEFAULT_LIST_LIMIT);
+        
+        let dir_path = resolve_path(&self.workspace, path);
         
         if !dir_path.exists() {
             return Err(Error::Tool(format!("Directory not found: {}", path)));
@@ -200,27 +361,31 @@ impl Tool for ListDirTool {
         
         if recursive {
             let mut results = Vec::new();
-            Self::list_recursive(dir_path, "", &mut results, 0, max_depth, show_size)
+            Self::list_recursive(&dir_path, "", &mut results, 0, max_depth, show_size)
                 .map_err(|e| Error::Tool(format!("Failed to list {}: {}", path, e)))?;
             
             if results.is_empty() {
                 Ok("Directory is empty.".to_string())
             } else {
-                Ok(results.join("\n"))
+                Ok(page.apply(&results, "entries"))
             }
         } else {
-            // Original non-recursive behavior
-            let entries: Vec<String> = std::fs::read_dir(path)
+            // Original non-recursive behavior, sorted so pages are stable
+            let mut entries: Vec<String> = std::fs::read_dir(&dir_path)
                 .map_err(|e| Error::Tool(format!("Failed to read directory {}: {}", path, e)))?
                 .filter_map(|e| e.ok())
                 .map(|e| {
                     let name = e.file_name().to_string_lossy().to_string();
+                    if let Some(target) = Self::symlink_target(&e.path()) {
+                        return format!("{} -> {}", name, target);
+                    }
                     let is_dir = e.file_type().map(|ft| ft.is_dir()).unwrap_or(false);
                     if is_dir { format!("{}/", name) } else { name }
                 })
                 .collect();
+            entries.sort();
             
-            Ok(entries.join("\n"))
+            Ok(page.apply(&entries, "entries"))
         }
     }
 }
@@ -236,19 +401,35 @@ mod tests {
         let file_path = tmp.path().join("test.txt");
         
         // Write
-        let write_result = WriteFileTool.execute(json!({
+        let write_result = WriteFileTool::new(tmp.path().to_path_buf()).execute(json!({
             "path": file_path.to_str().unwrap(),
             "content": "Hello, World!"
         })).await.unwrap();
         assert!(write_result.contains("Successfully wrote"));
         
         // Read
-        let read_result = ReadFileTool.execute(json!({
+        let read_result = ReadFileTool::new(tmp.path().to_path_buf()).execute(json!({
             "path": file_path.to_str().unwrap()
         })).await.unwrap();
         assert_eq!(read_result, "Hello, World!");
     }
     
+    #[tokio::test]
+    async fn test_read_file_pages() {
+        let tmp = TempDir::new().unwrap();
+        let lines: Vec<String> = (1..=1500).map(|i| format!("line {}", i)).collect();
+        std::fs::write(tmp.path().join("long.txt"), lines.join("\n")).unwrap();
+        let tool = ReadFileTool::new(tmp.path().to_path_buf());
+        
+        let first = tool.execute(json!({ "path": "long.txt" })).await.unwrap();
+        assert!(first.starts_with("line 1\n"));
+        assert!(first.ends_with("line 1000\n\n[Showing 1-1000 of 1500 lines; use offset=1000 for more]"));
+        
+        let rest = tool.execute(json!({ "path": "long.txt", "offset": 1000 })).await.unwrap();
+        assert!(rest.starts_with("line 1001\n"));
+        assert!(rest.ends_with("line 1500\n\n[Showing 1001-1500 of 1500 lines]"));
+    }
+    
     #[tokio::test]
     async fn test_list_dir() {
         let tmp = TempDir::new().unwrap();
@@ -256,7 +437,7 @@ mod tests {
         std::fs::write(tmp.path().join("b.txt"), "").unwrap();
         std::fs::create_dir(tmp.path().join("subdir")).unwrap();
         
-        let result = ListDirTool.execute(json!({
+        let result = ListDirTool::new(tmp.path().to_path_buf()).execute(json!({
             "path": tmp.path().to_str().unwrap()
         })).await.unwrap();
         
@@ -264,4 +445,96 @@ mod tests {
         assert!(result.contains("b.txt"));
         assert!(result.contains("subdir/"));
     }
+    
+    #[tokio::test]
+    async fn test_list_dir_pages() {
+        let tmp = TempDir::new().unwrap();
+        for i in 0..250 {
+            std::fs::write(tmp.path().join(format!("f{:03}.txt", i)), "").unwrap();
+        }
+        let tool = ListDirTool::new(tmp.path().to_path_buf());
+        
+        let first = tool.execute(json!({ "path": "." })).await.unwrap();
+        assert!(first.starts_with("f000.txt\n"));
+        assert!(first.contains("f199.txt\n\n[Showing 1-200 of 250 entries; use offset=200 for more]"));
+        
+        let rest = tool.execute(json!({ "path": ".", "offset": 200, "limit": 100 })).await.unwrap();
+        assert!(rest.starts_with("f200.txt\n"));
+        assert!(rest.ends_with("f249.txt\n\n[Showing 201-250 of 250 entries]"));
+    }
+    
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_list_dir_symlinks() {
+        let tmp = TempDir::new().unwrap();
+        std::fs::create_dir(tmp.path().join("sub")).unwrap();
+        std::fs::write(tmp.path().join("sub/file.txt"), "").unwrap();
+        // A link back to an ancestor would loop forever if followed
+        std::os::unix::fs::symlink(tmp.path(), tmp.path().join("sub/loop")).unwrap();
+        std::os::unix::fs::symlink("sub/file.txt", tmp.path().join("link.txt")).unwrap();
+        
+        let tool = ListDirTool::new(tmp.path().to_path_buf());
+        let result = tool.execute(json!({
+            "path": tmp.path().to_str().unwrap(),
+            "recursive": true,
+            "max_depth": 10
+        })).await.unwrap();
+        
+        assert!(result.contains("link.txt -> sub/file.txt"));
+        assert!(result.contains(&format!("  loop -> {}/", tmp.path().display())));
+        assert_eq!(result.matches("file.txt").count(), 2);
+        
+        let flat = tool.execute(json!({ "path": "sub" })).await.unwrap();
+        assert!(flat.contains("loop -> "));
+    }
+    
+    #[tokio::test]
+    async fn test_read_file_url() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; 1024];
+            let _ = socket.read(&mut buf).await;
+            let body = "<html><body><h1>Hello</h1><p>from the web</p></body></html>";
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: text/html\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            socket.write_all(response.as_bytes()).await.unwrap();
+        });
+        
+        let tmp = TempDir::new().unwrap();
+        let result = ReadFileTool::new(tmp.path().to_path_buf()).execute(json!({
+            "path": format!("http://{}/page", addr)
+        })).await.unwrap();
+        
+        assert_eq!(result, "Hello from the web");
+    }
+    
+    #[tokio::test]
+    async fn test_write_file_reports_diff() {
+        let tmp = TempDir::new().unwrap();
+        let tool = WriteFileTool::new(tmp.path().to_path_buf());
+        std::fs::write(tmp.path().join("notes.txt"), "one\ntwo\nthree\n").unwrap();
+        
+        let preview = tool.execute(json!({
+            "path": "notes.txt",
+            "content": "one\n2\nthree\nfour\n",
+            "preview_only": true
+        })).await.unwrap();
+        assert!(preview.contains("2 line(s) added, 1 line(s) removed"));
+        assert!(preview.contains("-two"));
+        assert_eq!(std::fs::read_to_string(tmp.path().join("notes.txt")).unwrap(), "one\ntwo\nthree\n");
+        
+        let result = tool.execute(json!({
+            "path": "notes.txt",
+            "content": "one\n2\nthree\nfour\n"
+        })).await.unwrap();
+        assert!(result.contains("overwrote existing file: 2 line(s) added, 1 line(s) removed"));
+        assert_eq!(std::fs::read_to_string(tmp.path().join("notes.txt")).unwrap(), "one\n2\nthree\nfour\n");
+    }
 }
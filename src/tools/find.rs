@@ -5,7 +5,7 @@ use async_trait::async_trait;
 use serde_json::{json, Value};
 use crate::Result;
 use crate::error::Error;
-use super::Tool;
+use super::{Tool, Page, resolve_path};
 
 /// Find files by name pattern
 pub struct FindFilesTool {
@@ -17,7 +17,7 @@ impl FindFilesTool {
         Self { workspace }
     }
     
-    fn matches_glob(name: &str, pattern: &str) -> bool {
+    pub(super) fn matches_glob(name: &str, pattern: &str) -> bool {
         // Simple glob matching: * matches any sequence, ? matches single char
         let pattern = pattern.to_lowercase();
         let name = name.to_lowercase();
@@ -27,8 +27,7 @@ impl FindFilesTool {
         }
         
         // Handle *.ext pattern
-        if pattern.starts_with("*.") {
-            let ext = &pattern[2..];
+        if let Some(ext) = pattern.strip_prefix("*.") {
             return name.ends_with(&format!(".{}", ext));
         }
         
@@ -127,6 +126,8 @@ impl Tool for FindFilesTool {
         "Find files by name pattern (glob). Supports *.ext, prefix*, *suffix patterns." 
     }
     
+    fn cacheable(&self) -> bool { true }
+    
     fn parameters(&self) -> Value {
         json!({
             "type": "object",
@@ -147,6 +148,14 @@ impl Tool for FindFilesTool {
                 "max_depth": {
                     "type": "integer",
                     "description": "Maximum depth to search (optional, defaults to 10)"
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": "Max results to return (optional, defaults to 50)"
+                },
+                "offset": {
+                    "type": "integer",
+                    "description": "Results to skip, for paging through many matches (optional, defaults to 0)"
                 }
             },
             "required": ["pattern"]
@@ -159,8 +168,7 @@ impl Tool for FindFilesTool {
             .ok_or_else(|| Error::Tool("Missing 'pattern' parameter".to_string()))?;
         
         let sub_path = params.get("path")
-            .and_then(|v| v.as_str())
-            .map(PathBuf::from);
+            .and_then(|v| v.as_str());
         
         let file_type = params.get("type")
             .and_then(|v| v.as_str())
@@ -170,8 +178,10 @@ impl Tool for FindFilesTool {
             .and_then(|v| v.as_u64())
             .unwrap_or(10) as usize;
         
+        let page = Page::from_params(&params, 50);
+        
         let search_path = if let Some(p) = sub_path {
-            self.workspace.join(p)
+            resolve_path(&self.workspace, p)
         } else {
             self.workspace.clone()
         };
@@ -187,22 +197,13 @@ impl Tool for FindFilesTool {
         if results.is_empty() {
             Ok(format!("No files matching '{}' found.", pattern))
         } else {
-            // Sort and limit results
+            // Sort so pages are stable, then return the requested page
             results.sort();
-            let total = results.len();
-            if total > 50 {
-                Ok(format!(
-                    "Found {} files matching '{}':\n\n{}...\n\n(showing first 50)",
-                    total, pattern,
-                    results[..50].join("\n")
-                ))
-            } else {
-                Ok(format!(
-                    "Found {} files matching '{}':\n\n{}",
-                    total, pattern,
-                    results.join("\n")
-                ))
-            }
+            Ok(format!(
+                "Found {} files matching '{}':\n\n{}",
+                results.len(), pattern,
+                page.apply(&results, "results")
+            ))
         }
     }
 }
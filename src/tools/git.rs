@@ -6,7 +6,10 @@ use serde_json::{json, Value};
 use tokio::process::Command;
 use crate::Result;
 use crate::error::Error;
-use super::Tool;
+use super::{DangerLevel, Tool};
+
+/// Maximum characters of diff/show output returned to the model
+const MAX_OUTPUT_CHARS: usize = 10000;
 
 /// Execute git commands
 pub struct GitTool {
@@ -18,7 +21,7 @@ impl GitTool {
         Self { workspace }
     }
 
-    async fn run_git(&self, args: &[&str]) -> Result<String> {
+    pub(crate) async fn run_git(&self, args: &[&str]) -> Result<String> {
         let output = Command::new("git")
             .args(args)
             .current_dir(&self.workspace)
@@ -43,12 +46,215 @@ impl GitTool {
             )))
         }
     }
+
+    /// Build `git diff` arguments from "[--staged] [path]"
+    ///
+    /// `--staged`/`--cached` (selecting the index) may come before or after the
+    /// path; the rest is passed to git whole, so paths containing spaces work.
+    fn diff_args(args_str: &str) -> Vec<&str> {
+        let is_flag = |word: &str| matches!(word, "--staged" | "--cached");
+        let mut path = args_str.trim();
+        let mut staged = false;
+        while let Some((_, rest)) = path.split_once(char::is_whitespace).filter(|(word, _)| is_flag(word)) {
+            staged = true;
+            path = rest.trim_start();
+        }
+        while let Some((rest, _)) = path.rsplit_once(char::is_whitespace).filter(|(_, word)| is_flag(word)) {
+            staged = true;
+            path = rest.trim_end();
+        }
+        if is_flag(path) {
+            staged = true;
+            path = "";
+        }
+
+        let mut cmd_args = vec!["diff"];
+        if staged {
+            cmd_args.push("--cached");
+        }
+        if !path.is_empty() {
+            cmd_args.extend(["--", path]);
+        }
+        cmd_args
+    }
+
+    /// Build `git blame` arguments from "<path> [start,end]" (or "start-end")
+    ///
+    /// The range is only taken from a trailing word of digits, ',' and '-', so
+    /// paths containing spaces are passed to git whole.
+    fn blame_args(args_str: &str) -> Result<Vec<String>> {
+        let args_str = args_str.trim();
+        let (path, range) = match args_str.rsplit_once(char::is_whitespace) {
+            Some((path, range)) if range.chars().all(|c| c.is_ascii_digit() || c == ',' || c == '-') => {
+                (path.trim_end(), Some(range))
+            }
+            _ => (args_str, None),
+        };
+        if path.is_empty() {
+            return Err(Error::Tool("File path required for blame".to_string()));
+        }
+
+        let mut cmd_args = vec!["blame".to_string(), "--line-porcelain".to_string()];
+        if let Some(range) = range {
+            let (start, end) = range.split_once([',', '-']).unwrap_or((range, range));
+            if start.parse::<u32>().is_err() || end.parse::<u32>().is_err() {
+                return Err(Error::Tool(format!(
+                    "Invalid line range '{}': use start,end like 10,20", range
+                )));
+            }
+            cmd_args.push(format!("-L{},{}", start, end));
+        }
+        cmd_args.extend(["--".to_string(), path.to_string()]);
+        Ok(cmd_args)
+    }
+}
+
+/// Group `git blame --line-porcelain` output into one line per run of lines
+/// from the same commit: range, short hash, author, date and commit subject
+fn summarize_blame(porcelain: &str) -> String {
+    struct Run {
+        commit: String,
+        author: String,
+        date: String,
+        summary: String,
+        start: u64,
+        end: u64,
+    }
+
+    let mut runs: Vec<Run> = Vec::new();
+    let mut current: Option<Run> = None;
+
+    for line in porcelain.lines() {
+        if line.starts_with('\t') {
+            // Content line: the previous header block is complete
+            if let Some(run) = current.take() {
+                match runs.last_mut() {
+                    Some(last) if last.commit == run.commit && last.end + 1 == run.start => last.end = run.end,
+                    _ => runs.push(run),
+                }
+            }
+        } else if let Some(author) = line.strip_prefix("author ") {
+            if let Some(run) = current.as_mut() {
+                run.author = author.to_string();
+            }
+        } else if let Some(time) = line.strip_prefix("author-time ") {
+            if let Some(run) = current.as_mut() {
+                run.date = time.parse()
+                    .ok()
+                    .and_then(|secs| chrono::DateTime::from_timestamp(secs, 0))
+                    .map(|t| t.format("%Y-%m-%d").to_string())
+                    .unwrap_or_default();
+            }
+        } else if let Some(summary) = line.strip_prefix("summary ") {
+            if let Some(run) = current.as_mut() {
+                run.summary = summary.to_string();
+            }
+        } else if current.is_none() {
+            // Header: "<sha> <original line> <final line> [<group size>]"
+            let mut fields = line.split_whitespace();
+            let (Some(sha), Some(_), Some(line_no)) = (fields.next(), fields.next(), fields.next()) else {
+                continue;
+            };
+            let Ok(line_no) = line_no.parse() else { continue };
+            current = Some(Run {
+                commit: sha.chars().take(7).collect(),
+                author: String::new(),
+                date: String::new(),
+                summary: String::new(),
+                start: line_no,
+                end: line_no,
+            });
+        }
+    }
+
+    runs.iter()
+        .map(|run| {
+            let lines = if run.start == run.end {
+                format!("L{}", run.start)
+            } else {
+                format!("L{}-{}", run.start, run.end)
+            };
+            format!("{} {} {} {} {}", lines, run.commit, run.author, run.date, run.summary)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Condense `git status --porcelain=v2 --branch` output into a small JSON summary
+fn summarize_status(porcelain: &str) -> Value {
+    let mut branch = "(unknown)";
+    let mut upstream: Option<&str> = None;
+    let (mut ahead, mut behind) = (0i64, 0i64);
+    let (mut staged, mut unstaged, mut untracked, mut conflicted) = (0, 0, 0, 0);
+
+    for line in porcelain.lines() {
+        if let Some(head) = line.strip_prefix("# branch.head ") {
+            branch = head;
+        } else if let Some(up) = line.strip_prefix("# branch.upstream ") {
+            upstream = Some(up);
+        } else if let Some(ab) = line.strip_prefix("# branch.ab ") {
+            for count in ab.split_whitespace() {
+                if let Some(n) = count.strip_prefix('+') {
+                    ahead = n.parse().unwrap_or(0);
+                } else if let Some(n) = count.strip_prefix('-') {
+                    behind = n.parse().unwrap_or(0);
+                }
+            }
+        } else if line.starts_with("? ") {
+            untracked += 1;
+        } else if line.starts_with("u ") {
+            conflicted += 1;
+        } else if line.starts_with("1 ") || line.starts_with("2 ") {
+            // Second field is XY: index status then worktree status, '.' means unchanged
+            let xy = line.split_whitespace().nth(1).unwrap_or("..").as_bytes();
+            if xy.first().is_some_and(|&c| c != b'.') {
+                staged += 1;
+            }
+            if xy.get(1).is_some_and(|&c| c != b'.') {
+                unstaged += 1;
+            }
+        }
+    }
+
+    json!({
+        "branch": branch,
+        "upstream": upstream,
+        "ahead": ahead,
+        "behind": behind,
+        "staged": staged,
+        "unstaged": unstaged,
+        "untracked": untracked,
+        "conflicted": conflicted,
+        "clean": staged + unstaged + untracked + conflicted == 0,
+    })
+}
+
+/// Trim surrounding whitespace and cap the output length
+fn trim_output(output: String) -> String {
+    let output = output.trim();
+    if output.len() <= MAX_OUTPUT_CHARS {
+        return output.to_string();
+    }
+    let mut end = MAX_OUTPUT_CHARS;
+    while !output.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}\n\n[Truncated - {} total chars]", &output[..end], output.len())
 }
 
 #[async_trait]
 impl Tool for GitTool {
     fn name(&self) -> &str { "git" }
-    fn description(&self) -> &str { "Run git commands (status, diff, commit, log, add)" }
+    fn description(&self) -> &str {
+        "Run git commands (status, summary, diff, show, commit, log, add, blame). 'summary' returns branch, ahead/behind and change counts as JSON; \
+         'blame' shows who last changed each range of lines and in which commit"
+    }
+
+    fn is_mutating(&self, params: &Value) -> bool {
+        matches!(params.get("operation").and_then(|v| v.as_str()), Some("commit" | "add"))
+    }
+
+    fn danger_level(&self) -> DangerLevel { DangerLevel::Caution }
 
     fn parameters(&self) -> Value {
         json!({
@@ -56,12 +262,12 @@ impl Tool for GitTool {
             "properties": {
                 "operation": {
                     "type": "string",
-                    "enum": ["status", "diff", "commit", "log", "add"],
+                    "enum": ["status", "summary", "diff", "show", "commit", "log", "add", "blame"],
                     "description": "Git operation to perform"
                 },
                 "args": {
                     "type": "string",
-                    "description": "Arguments for the operation (e.g., file paths, commit message)"
+                    "description": "Arguments for the operation: file paths for add; a path and/or --staged for diff; a commit for show (default HEAD); the message for commit; a path for log to see that file's history; a path and optional line range for blame ('src/main.rs 10,20')"
                 }
             },
             "required": ["operation"]
@@ -79,8 +285,38 @@ impl Tool for GitTool {
 
         match op {
             "status" => self.run_git(&["status"]).await,
-            "diff" => self.run_git(&["diff"]).await,
-            "log" => self.run_git(&["log", "-n", "10", "--oneline"]).await,
+            "summary" => {
+                let porcelain = self.run_git(&["status", "--porcelain=v2", "--branch"]).await?;
+                Ok(summarize_status(&porcelain).to_string())
+            },
+            "diff" => {
+                let output = self.run_git(&Self::diff_args(args_str)).await?;
+                if output.trim().is_empty() {
+                    return Ok("No changes.".to_string());
+                }
+                Ok(trim_output(output))
+            },
+            "show" => {
+                let commit = args_str.split_whitespace().next().unwrap_or("HEAD");
+                // A revision can't be an option (e.g. --output=<file>)
+                if commit.starts_with('-') {
+                    return Err(Error::Tool(format!("Invalid revision for show: {}", commit)));
+                }
+                self.run_git(&["show", "--stat", "--patch", commit]).await.map(trim_output)
+            },
+            "log" => match args_str.trim() {
+                "" => self.run_git(&["log", "-n", "10", "--oneline"]).await,
+                path => self.run_git(&[
+                    "log", "-n", "20", "--follow", "--date=short",
+                    "--format=%h %ad %an %s", "--", path,
+                ]).await.map(trim_output),
+            },
+            "blame" => {
+                let cmd_args = Self::blame_args(args_str)?;
+                let cmd_args: Vec<&str> = cmd_args.iter().map(String::as_str).collect();
+                let output = self.run_git(&cmd_args).await?;
+                Ok(trim_output(summarize_blame(&output)))
+            },
             "add" => {
                 let files: Vec<&str> = args_str.split_whitespace().collect();
                 if files.is_empty() {
@@ -100,3 +336,133 @@ impl Tool for GitTool {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn git(dir: &std::path::Path, args: &[&str]) {
+        let status = std::process::Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success(), "git {:?} failed", args);
+    }
+
+    fn repo() -> (TempDir, GitTool) {
+        let tmp = TempDir::new().unwrap();
+        git(tmp.path(), &["init", "-q"]);
+        git(tmp.path(), &["config", "user.email", "leo@example.com"]);
+        git(tmp.path(), &["config", "user.name", "Leo"]);
+        std::fs::write(tmp.path().join("a.txt"), "one\n").unwrap();
+        std::fs::write(tmp.path().join("b.txt"), "one\n").unwrap();
+        git(tmp.path(), &["add", "."]);
+        git(tmp.path(), &["commit", "-q", "-m", "Initial commit"]);
+        let tool = GitTool::new(tmp.path().to_path_buf());
+        (tmp, tool)
+    }
+
+    #[test]
+    fn test_diff_args() {
+        assert_eq!(GitTool::diff_args(""), vec!["diff"]);
+        assert_eq!(GitTool::diff_args("--staged src/main.rs"), vec!["diff", "--cached", "--", "src/main.rs"]);
+        assert_eq!(GitTool::diff_args(" --cached "), vec!["diff", "--cached"]);
+        assert_eq!(GitTool::diff_args("My Notes/todo list.md --staged"), vec!["diff", "--cached", "--", "My Notes/todo list.md"]);
+        assert_eq!(GitTool::diff_args("--staged my file.txt"), vec!["diff", "--cached", "--", "my file.txt"]);
+    }
+
+    #[test]
+    fn test_blame_args() {
+        assert_eq!(GitTool::blame_args("a.rs").unwrap(), vec!["blame", "--line-porcelain", "--", "a.rs"]);
+        assert_eq!(GitTool::blame_args("a.rs 10-20").unwrap(), vec!["blame", "--line-porcelain", "-L10,20", "--", "a.rs"]);
+        assert_eq!(
+            GitTool::blame_args("my notes.md 3,4").unwrap(),
+            vec!["blame", "--line-porcelain", "-L3,4", "--", "my notes.md"]
+        );
+        assert_eq!(GitTool::blame_args("my notes.md").unwrap().last().unwrap(), "my notes.md");
+        assert!(GitTool::blame_args("a.rs 10-").is_err());
+        assert!(GitTool::blame_args("").is_err());
+    }
+
+    #[test]
+    fn test_summarize_status() {
+        let porcelain = "# branch.oid abc123\n\
+            # branch.head feature\n\
+            # branch.upstream origin/feature\n\
+            # branch.ab +2 -1\n\
+            1 M. N... 100644 100644 100644 abc def src/lib.rs\n\
+            1 .M N... 100644 100644 100644 abc def src/main.rs\n\
+            1 MM N... 100644 100644 100644 abc def README.md\n\
+            ? notes.txt\n";
+        let summary = summarize_status(porcelain);
+        assert_eq!(summary["branch"], "feature");
+        assert_eq!(summary["upstream"], "origin/feature");
+        assert_eq!(summary["ahead"], 2);
+        assert_eq!(summary["behind"], 1);
+        assert_eq!(summary["staged"], 2);
+        assert_eq!(summary["unstaged"], 2);
+        assert_eq!(summary["untracked"], 1);
+        assert_eq!(summary["clean"], false);
+    }
+
+    #[tokio::test]
+    async fn test_summary_operation() {
+        let (tmp, tool) = repo();
+        std::fs::write(tmp.path().join("new.txt"), "x").unwrap();
+
+        let result = tool.execute(json!({"operation": "summary"})).await.unwrap();
+        let summary: Value = serde_json::from_str(&result).unwrap();
+        assert_eq!(summary["untracked"], 1);
+        assert_eq!(summary["staged"], 0);
+        assert!(summary["upstream"].is_null());
+    }
+
+    #[tokio::test]
+    async fn test_diff_scoped_and_staged() {
+        let (tmp, tool) = repo();
+        std::fs::write(tmp.path().join("a.txt"), "two\n").unwrap();
+        std::fs::write(tmp.path().join("b.txt"), "two\n").unwrap();
+        git(tmp.path(), &["add", "b.txt"]);
+
+        let scoped = tool.execute(json!({"operation": "diff", "args": "a.txt"})).await.unwrap();
+        assert!(scoped.contains("a.txt") && !scoped.contains("b.txt"));
+
+        std::fs::write(tmp.path().join("c d.txt"), "two\n").unwrap();
+        git(tmp.path(), &["add", "-N", "c d.txt"]);
+        let spaced = tool.execute(json!({"operation": "diff", "args": "c d.txt"})).await.unwrap();
+        assert!(spaced.contains("c d.txt") && !spaced.contains("a.txt"));
+
+        let staged = tool.execute(json!({"operation": "diff", "args": "--staged"})).await.unwrap();
+        assert!(staged.contains("b.txt") && !staged.contains("a.txt"));
+
+        let show = tool.execute(json!({"operation": "show"})).await.unwrap();
+        assert!(show.contains("Initial commit"));
+        assert_eq!(show, show.trim());
+
+        let err = tool.execute(json!({"operation": "show", "args": "--output=/tmp/leak"})).await.unwrap_err();
+        assert!(err.to_string().contains("Invalid revision"));
+    }
+
+    #[tokio::test]
+    async fn test_blame_and_file_history() {
+        let (tmp, tool) = repo();
+        std::fs::write(tmp.path().join("a.txt"), "one\ntwo\nthree\n").unwrap();
+        git(tmp.path(), &["commit", "-q", "-am", "Add more lines"]);
+
+        let blame = tool.execute(json!({"operation": "blame", "args": "a.txt"})).await.unwrap();
+        let lines: Vec<&str> = blame.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("L1 ") && lines[0].ends_with("Initial commit"));
+        assert!(lines[1].starts_with("L2-3 ") && lines[1].contains(" Leo ") && lines[1].ends_with("Add more lines"));
+
+        let ranged = tool.execute(json!({"operation": "blame", "args": "a.txt 3,3"})).await.unwrap();
+        assert!(ranged.starts_with("L3 ") && ranged.ends_with("Add more lines"));
+
+        let history = tool.execute(json!({"operation": "log", "args": "a.txt"})).await.unwrap();
+        assert_eq!(history.lines().count(), 2);
+        assert!(history.lines().next().unwrap().ends_with("Leo Add more lines"));
+    }
+}
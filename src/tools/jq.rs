@@ -0,0 +1,200 @@
+//! JSON query tool - jq-style filters over JSON via jaq
+
+use std::path::PathBuf;
+use std::time::Duration;
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use jaq_core::load::{Arena, File, Loader};
+use jaq_core::{Compiler, Ctx, RcIter};
+use jaq_json::Val;
+use crate::Result;
+use crate::error::Error;
+use super::{Tool, resolve_path};
+
+/// Maximum characters returned from a query
+const MAX_OUTPUT_CHARS: usize = 10_000;
+
+/// Most output values collected, so `repeat(.)` or `range(1e9)` stops early
+const MAX_RESULTS: usize = 1_000;
+
+/// Time allowed for a query before giving up on it
+const QUERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Evaluate jq-like expressions over JSON input or a JSON file
+pub struct JsonQueryTool {
+    workspace: PathBuf,
+}
+
+impl JsonQueryTool {
+    pub fn new(workspace: PathBuf) -> Self {
+        Self { workspace }
+    }
+}
+
+/// Summarize jaq load/compile errors (the file part is always the query itself)
+fn describe<F, E: std::fmt::Debug>(errs: Vec<(F, E)>) -> String {
+    errs.into_iter()
+        .map(|(_, e)| format!("{:?}", e))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Run a jq filter over `input` and collect its output values, stopping after
+/// `MAX_RESULTS` values or once they exceed `MAX_OUTPUT_CHARS`. The flag is set
+/// when the output was cut short.
+fn run_query(query: &str, input: Value) -> Result<(Vec<Value>, bool)> {
+    let program = File { code: query, path: () };
+
+    let loader = Loader::new(jaq_std::defs().chain(jaq_json::defs()));
+    let arena = Arena::default();
+
+    let modules = loader.load(&arena, program)
+        .map_err(|errs| Error::Tool(format!("Invalid jq query '{}': {}", query, describe(errs))))?;
+
+    let filter = Compiler::default()
+        .with_funs(jaq_std::funs().chain(jaq_json::funs()))
+        .compile(modules)
+        .map_err(|errs| Error::Tool(format!("Failed to compile jq query '{}': {}", query, describe(errs))))?;
+
+    let inputs = RcIter::new(core::iter::empty());
+    let out = filter.run((Ctx::new([], &inputs), Val::from(input)));
+
+    let mut results = Vec::new();
+    let mut chars = 0;
+    for r in out {
+        if results.len() >= MAX_RESULTS || chars > MAX_OUTPUT_CHARS {
+            return Ok((results, true));
+        }
+        let value = Value::from(r.map_err(|e| Error::Tool(format!("jq error: {}", e)))?);
+        chars += value.to_string().len();
+        results.push(value);
+    }
+    Ok((results, false))
+}
+
+/// `run_query` on a blocking thread, bounded by `QUERY_TIMEOUT`
+///
+/// jaq can't be interrupted, so a query that times out inside a single value
+/// (e.g. `last(range(1e12))`) keeps its thread until it finishes; the agent
+/// gets an error right away instead of hanging.
+async fn run_query_bounded(query: &str, input: Value) -> Result<(Vec<Value>, bool)> {
+    let query = query.to_string();
+    let task = tokio::task::spawn_blocking(move || run_query(&query, input));
+    match tokio::time::timeout(QUERY_TIMEOUT, task).await {
+        Ok(joined) => joined.map_err(|e| Error::Tool(format!("jq query failed: {}", e)))?,
+        Err(_) => Err(Error::Timeout(format!("jq query after {}s", QUERY_TIMEOUT.as_secs()))),
+    }
+}
+
+#[async_trait]
+impl Tool for JsonQueryTool {
+    fn name(&self) -> &str { "jq" }
+    fn description(&self) -> &str {
+        "Run a jq-style query (e.g. '.items[] | .name') over JSON text or a JSON file and return only the result"
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "query": {
+                    "type": "string",
+                    "description": "jq expression to evaluate (e.g. '.data[0].id', 'map(.price) | add')"
+                },
+                "input": {
+                    "type": "string",
+                    "description": "Raw JSON text to query (use this or 'path')"
+                },
+                "path": {
+                    "type": "string",
+                    "description": "Path to a JSON file to query (relative paths resolve against the workspace)"
+                }
+            },
+            "required": ["query"]
+        })
+    }
+
+    async fn execute(&self, params: Value) -> Result<String> {
+        let query = params.get("query")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| Error::Tool("Missing 'query' parameter".to_string()))?;
+
+        let text = match (params.get("input").and_then(|v| v.as_str()), params.get("path").and_then(|v| v.as_str())) {
+            (Some(input), _) => input.to_string(),
+            (None, Some(path)) => std::fs::read_to_string(resolve_path(&self.workspace, path))
+                .map_err(|e| Error::Tool(format!("Failed to read {}: {}", path, e)))?,
+            (None, None) => return Err(Error::Tool("Provide either 'input' or 'path'".to_string())),
+        };
+
+        let input: Value = serde_json::from_str(&text)
+            .map_err(|e| Error::Tool(format!("Input is not valid JSON: {}", e)))?;
+
+        let (results, cut_short) = run_query_bounded(query, input).await?;
+        if results.is_empty() {
+            return Ok("(no results)".to_string());
+        }
+
+        let mut rendered = results.iter()
+            .map(|v| serde_json::to_string_pretty(v).unwrap_or_else(|_| v.to_string()))
+            .collect::<Vec<_>>()
+            .join("\n");
+        if cut_short && rendered.len() <= MAX_OUTPUT_CHARS {
+            rendered.push_str(&format!("\n\n[Stopped after {} results]", results.len()));
+        }
+
+        if rendered.len() > MAX_OUTPUT_CHARS {
+            let mut end = MAX_OUTPUT_CHARS;
+            while !rendered.is_char_boundary(end) {
+                end -= 1;
+            }
+            Ok(format!("{}...\n\n[Truncated - {} total chars]", &rendered[..end], rendered.len()))
+        } else {
+            Ok(rendered)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_jq_inline_input() {
+        let tool = JsonQueryTool::new(PathBuf::from("."));
+        let result = tool.execute(json!({
+            "input": r#"{"items": [{"name": "a", "price": 2}, {"name": "b", "price": 3}]}"#,
+            "query": "[.items[].price] | add"
+        })).await.unwrap();
+
+        assert_eq!(result, "5");
+    }
+
+    #[tokio::test]
+    async fn test_jq_file_input() {
+        let tmp = TempDir::new().unwrap();
+        std::fs::write(tmp.path().join("data.json"), r#"{"users": [{"id": 1}, {"id": 2}]}"#).unwrap();
+
+        let tool = JsonQueryTool::new(tmp.path().to_path_buf());
+        let result = tool.execute(json!({"path": "data.json", "query": ".users[].id"})).await.unwrap();
+
+        assert_eq!(result, "1\n2");
+    }
+
+    #[tokio::test]
+    async fn test_jq_unbounded_output() {
+        let tool = JsonQueryTool::new(PathBuf::from("."));
+        let result = tool.execute(json!({"input": "1", "query": "repeat(.)"})).await.unwrap();
+        assert!(result.ends_with(&format!("[Stopped after {} results]", MAX_RESULTS)));
+
+        let result = tool.execute(json!({"input": "null", "query": "range(1e9) | [range(100)]"})).await.unwrap();
+        assert!(result.contains("[Truncated"));
+    }
+
+    #[tokio::test]
+    async fn test_jq_invalid_query() {
+        let tool = JsonQueryTool::new(PathBuf::from("."));
+        let result = tool.execute(json!({"input": "{}", "query": ".[[["})).await;
+        assert!(result.is_err());
+    }
+}
@@ -6,24 +6,65 @@ use serde_json::{json, Value};
 use crate::Result;
 use crate::error::Error;
 use super::Tool;
+use super::search::SearchTool;
 
 /// Manage long-term memory
 pub struct MemoryTool {
     memory_path: PathBuf,
+    daily_dir: PathBuf,
 }
 
 impl MemoryTool {
     pub fn new(workspace: PathBuf) -> Self {
         Self {
             memory_path: workspace.join("memory").join("MEMORY.md"),
+            daily_dir: workspace.join("memory").join("daily"),
         }
     }
+
+    /// Search all daily notes, returning matches tagged with their date
+    async fn recall(&self, query: &str) -> Result<String> {
+        if !self.daily_dir.exists() {
+            return Ok("No daily notes yet.".to_string());
+        }
+
+        let search = SearchTool::new(self.daily_dir.clone());
+        let result = search.execute(json!({
+            "query": query,
+            "case_insensitive": true,
+            "literal": true
+        })).await?;
+
+        // Search output is "YYYY-MM-DD.md:line: text" - turn it into "[YYYY-MM-DD] text"
+        let mut entries: Vec<(&str, usize, &str)> = result.lines()
+            .filter_map(|line| {
+                let (file, rest) = line.split_once(".md:")?;
+                let (line_no, text) = rest.split_once(": ")?;
+                Some((file, line_no.parse().ok()?, text))
+            })
+            .collect();
+
+        if entries.is_empty() {
+            return Ok(format!("Nothing in daily notes matches '{}'.", query));
+        }
+
+        // Most recent day first, keeping each day's entries in the order they were written
+        entries.sort_by(|a, b| b.0.cmp(a.0).then(a.1.cmp(&b.1)));
+        Ok(entries.iter()
+            .map(|(date, _, text)| format!("[{}] {}", date, text))
+            .collect::<Vec<_>>()
+            .join("\n"))
+    }
 }
 
 #[async_trait]
 impl Tool for MemoryTool {
     fn name(&self) -> &str { "memory" }
-    fn description(&self) -> &str { "Read or Add to long-term memory" }
+    fn description(&self) -> &str { "Read or Add to long-term memory, or recall past daily notes" }
+
+    fn is_mutating(&self, params: &Value) -> bool {
+        params.get("action").and_then(|v| v.as_str()) == Some("add")
+    }
 
     fn parameters(&self) -> Value {
         json!({
@@ -31,12 +72,16 @@ impl Tool for MemoryTool {
             "properties": {
                 "action": {
                     "type": "string",
-                    "enum": ["read", "add"],
-                    "description": "Action to perform"
+                    "enum": ["read", "add", "recall"],
+                    "description": "Action to perform. 'recall' searches all daily notes."
                 },
                 "content": {
                     "type": "string",
                     "description": "Content to add (required for 'add' action)"
+                },
+                "query": {
+                    "type": "string",
+                    "description": "Text to look for in daily notes (required for 'recall' action)"
                 }
             },
             "required": ["action"]
@@ -82,7 +127,40 @@ impl Tool for MemoryTool {
 
                 Ok("Successfully added to long-term memory.".to_string())
             },
+            "recall" => {
+                let query = params.get("query")
+                    .and_then(|v| v.as_str())
+                    .ok_or_else(|| Error::Tool("Missing 'query' parameter for recall action".to_string()))?;
+
+                self.recall(query).await
+            },
             _ => Err(Error::Tool(format!("Unknown action: {}", action)))
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_recall_across_daily_notes() {
+        let tmp = TempDir::new().unwrap();
+        let daily = tmp.path().join("memory").join("daily");
+        std::fs::create_dir_all(&daily).unwrap();
+        std::fs::write(daily.join("2026-01-05.md"), "- 10:00 user: let's use Postgres\n- 10:01 leo: ok").unwrap();
+        std::fs::write(daily.join("2026-01-09.md"), "- 09:00 user: switch to SQLite instead of postgres\n- 09:05 leo: dropping Postgres").unwrap();
+        std::fs::write(daily.join("2026-01-10.md"), "- 09:00 user: unrelated").unwrap();
+
+        let tool = MemoryTool::new(tmp.path().to_path_buf());
+        let result = tool.execute(json!({"action": "recall", "query": "postgres"})).await.unwrap();
+
+        let lines: Vec<&str> = result.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], "[2026-01-09] - 09:00 user: switch to SQLite instead of postgres");
+        assert_eq!(lines[1], "[2026-01-09] - 09:05 leo: dropping Postgres");
+        assert!(lines[2].starts_with("[2026-01-05]"));
+        assert!(lines[2].contains("let's use Postgres"));
+    }
+}
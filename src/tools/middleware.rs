@@ -0,0 +1,63 @@
+//! Tool middleware - hooks that wrap every tool call
+//!
+//! Middlewares run in registration order before a tool executes and in the
+//! same order after it finishes, so cross-cutting concerns (logging, timing,
+//! permissions, metrics) live in one place instead of in each tool.
+
+use std::time::Duration;
+use serde_json::Value;
+use tracing::{debug, warn};
+use crate::Result;
+
+/// Calls slower than this are logged as warnings by `TimingMiddleware`
+const SLOW_CALL: Duration = Duration::from_secs(10);
+
+/// Hooks around `ToolRunner::execute`
+pub trait ToolMiddleware: Send + Sync {
+    /// Called before the tool runs; an error blocks the call and is returned instead.
+    fn before(&self, _name: &str, _params: &Value) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called with the outcome of the call and how long it took.
+    fn after(&self, _name: &str, _result: &Result<String>, _elapsed: Duration) {}
+}
+
+/// Logs each call's arguments and outcome at debug level
+pub struct LoggingMiddleware;
+
+impl ToolMiddleware for LoggingMiddleware {
+    fn before(&self, name: &str, params: &Value) -> Result<()> {
+        debug!("Executing tool: {} with args: {}", name, params);
+        Ok(())
+    }
+
+    fn after(&self, name: &str, result: &Result<String>, _elapsed: Duration) {
+        match result {
+            Ok(output) => debug!("Tool {} succeeded: {} chars", name, output.len()),
+            Err(e) => debug!("Tool {} failed: {}", name, e),
+        }
+    }
+}
+
+/// Counts calls and their latency in the process-wide Prometheus metrics
+pub struct MetricsMiddleware;
+
+impl ToolMiddleware for MetricsMiddleware {
+    fn after(&self, name: &str, result: &Result<String>, elapsed: Duration) {
+        crate::metrics::metrics().record_tool_call(name, result.is_ok(), elapsed);
+    }
+}
+
+/// Logs how long each call took, warning about slow ones
+pub struct TimingMiddleware;
+
+impl ToolMiddleware for TimingMiddleware {
+    fn after(&self, name: &str, _result: &Result<String>, elapsed: Duration) {
+        if elapsed >= SLOW_CALL {
+            warn!("Tool {} took {:.1}s", name, elapsed.as_secs_f64());
+        } else {
+            debug!("Tool {} took {}ms", name, elapsed.as_millis());
+        }
+    }
+}
@@ -4,23 +4,140 @@
 //! executing commands, and searching the web.
 
 mod runner;
+mod middleware;
+mod approval;
 mod filesystem;
 mod shell;
 mod web;
+mod web_search;
+mod download;
+mod calendar;
 mod search;
 mod edit;
+mod template;
+mod permissions;
 mod git;
 mod memory;
 mod task;
+mod scratchpad;
+mod stop;
+mod skill;
+mod attach;
 mod browser_bridge;
+mod moments;
+mod continuation;
+mod result_cache;
 mod find;
+mod tail;
+mod count;
+mod csv;
+mod jq;
+mod summarize;
+#[cfg(feature = "headless")]
+mod screenshot;
 
-pub use runner::{ToolRunner, ToolDefinition};
+pub use runner::{default_tools, DangerLevel, ToolRunner, ToolDefinition};
+pub use middleware::{ToolMiddleware, LoggingMiddleware, MetricsMiddleware, TimingMiddleware};
+pub use approval::ApprovalHandler;
+pub use summarize::SummarizeTool;
+pub use scratchpad::{Scratchpad, ScratchpadTool};
+pub use stop::{stop_message, StopTool, STOP_TOOL_NAME};
+pub use skill::SkillTool;
+pub(crate) use git::GitTool;
+pub(crate) use download::DownloadTool;
+pub use attach::{Attachments, AttachTool};
+pub use web_search::{SearchProvider, SearchResult};
+pub(crate) use summarize::SUMMARY_PROMPT;
+pub use browser_bridge::pairing_token as browser_pairing_token;
+pub use browser_bridge::{bridge_status as browser_bridge_status, BridgeStatus};
 
+use std::path::{Path, PathBuf};
 use async_trait::async_trait;
 use serde_json::Value;
 use crate::Result;
 
+/// Folder shortcuts the system prompt promises ("documents" = ~/Documents)
+const PATH_SHORTCUTS: &[(&str, &str)] = &[
+    ("documents", "Documents"),
+    ("desktop", "Desktop"),
+    ("downloads", "Downloads"),
+];
+
+/// Resolve a path argument the way every file tool should
+///
+/// Expands `~`, maps shortcuts like `documents/notes.md` to `~/Documents/notes.md`
+/// (unless the workspace has its own folder of that name), keeps absolute paths,
+/// and joins everything else to the workspace.
+pub(crate) fn resolve_path(workspace: &Path, input: &str) -> PathBuf {
+    let input = input.trim();
+    let home = dirs::home_dir();
+
+    if let Some(home) = &home {
+        if input == "~" {
+            return home.clone();
+        }
+        if let Some(rest) = input.strip_prefix("~/") {
+            return home.join(rest);
+        }
+    }
+
+    let path = Path::new(input);
+    if path.is_absolute() {
+        return path.to_path_buf();
+    }
+
+    let (first, rest) = input.split_once('/').unwrap_or((input, ""));
+    if let Some(home) = &home {
+        if !workspace.join(first).exists() {
+            if let Some((_, folder)) = PATH_SHORTCUTS.iter().find(|(name, _)| first.eq_ignore_ascii_case(name)) {
+                let folder = home.join(folder);
+                return if rest.is_empty() { folder } else { folder.join(rest) };
+            }
+        }
+    }
+
+    workspace.join(path)
+}
+
+/// Largest page a listing tool returns, whatever `limit` asks for
+const MAX_PAGE: usize = 1000;
+
+/// A window over a listing's lines, from the `offset`/`limit` parameters
+pub(crate) struct Page {
+    offset: usize,
+    limit: usize,
+}
+
+impl Page {
+    pub(crate) fn from_params(params: &Value, default_limit: usize) -> Self {
+        let get = |key: &str| params.get(key).and_then(|v| v.as_u64()).map(|n| n as usize);
+        Self {
+            offset: get("offset").unwrap_or(0),
+            limit: get("limit").unwrap_or(default_limit).clamp(1, MAX_PAGE),
+        }
+    }
+
+    /// The lines in this page, followed by the total and the next offset when
+    /// the page doesn't cover everything
+    pub(crate) fn apply(&self, lines: &[String], noun: &str) -> String {
+        let total = lines.len();
+        if self.offset >= total && total > 0 {
+            return format!("No {} at offset {} ({} total)", noun, self.offset, total);
+        }
+
+        let end = (self.offset + self.limit).min(total);
+        let mut out = lines[self.offset.min(total)..end].join("\n");
+        if self.offset > 0 || end < total {
+            out.push_str(&format!("\n\n[Showing {}-{} of {} {}", self.offset + 1, end, total, noun));
+            if end < total {
+                out.push_str(&format!("; use offset={} for more", end));
+            }
+            out.push(']');
+        }
+        out
+    }
+}
+
 /// Tool trait - interface for all agent tools
 #[async_trait]
 pub trait Tool: Send + Sync {
@@ -33,15 +150,38 @@ pub trait Tool: Send + Sync {
     /// JSON Schema for parameters
     fn parameters(&self) -> Value;
     
+    /// Whether this call would change files, run commands or act elsewhere.
+    /// Mutating calls are only described, not run, in dry-run mode.
+    fn is_mutating(&self, _params: &Value) -> bool {
+        false
+    }
+    
+    /// Whether identical calls return identical results, so the runner may
+    /// reuse them when `tool_cache_ttl_secs` is set (read-only tools only)
+    fn cacheable(&self) -> bool {
+        false
+    }
+    
+    /// How careful the model should be with this tool (see `DangerLevel`)
+    fn danger_level(&self) -> DangerLevel {
+        DangerLevel::Safe
+    }
+    
     /// Execute the tool with given parameters
     async fn execute(&self, params: Value) -> Result<String>;
     
     /// Convert to tool definition for LLM
     fn to_definition(&self) -> ToolDefinition {
+        let danger_level = self.danger_level();
+        let description = match danger_level.note() {
+            Some(note) => format!("{} [{}]", self.description(), note),
+            None => self.description().to_string(),
+        };
         ToolDefinition {
             name: self.name().to_string(),
-            description: self.description().to_string(),
+            description,
             parameters: self.parameters(),
+            danger_level,
         }
     }
 }
@@ -62,3 +202,48 @@ impl Tool for DummyTool {
         Ok(self.result.clone())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_danger_level_in_definition() {
+        let dummy = DummyTool { name: "dummy".to_string(), result: String::new() };
+        let safe = dummy.to_definition();
+        assert_eq!(safe.danger_level, DangerLevel::Safe);
+        assert_eq!(safe.description, "Dummy tool for testing");
+
+        let exec = shell::ExecTool::new(PathBuf::from(".")).to_definition();
+        assert_eq!(exec.danger_level, DangerLevel::Destructive);
+        assert!(exec.description.ends_with(&format!("[{}]", DangerLevel::Destructive.note().unwrap())));
+    }
+
+    #[test]
+    fn test_resolve_path() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let ws = tmp.path();
+        let home = dirs::home_dir().unwrap();
+
+        assert_eq!(resolve_path(ws, "src/main.rs"), ws.join("src/main.rs"));
+        assert_eq!(resolve_path(ws, "/etc/hosts"), PathBuf::from("/etc/hosts"));
+        assert_eq!(resolve_path(ws, "~/notes.md"), home.join("notes.md"));
+        assert_eq!(resolve_path(ws, "Documents/a.txt"), home.join("Documents").join("a.txt"));
+        assert_eq!(resolve_path(ws, "desktop"), home.join("Desktop"));
+
+        // A workspace folder with the same name wins over the shortcut
+        std::fs::create_dir(ws.join("documents")).unwrap();
+        assert_eq!(resolve_path(ws, "documents/a.txt"), ws.join("documents/a.txt"));
+    }
+
+    #[test]
+    fn test_page() {
+        let lines: Vec<String> = (1..=5).map(|i| format!("line{}", i)).collect();
+        let page = |params| Page::from_params(&params, 2).apply(&lines, "entries");
+
+        assert_eq!(page(serde_json::json!({})), "line1\nline2\n\n[Showing 1-2 of 5 entries; use offset=2 for more]");
+        assert_eq!(page(serde_json::json!({ "offset": 4 })), "line5\n\n[Showing 5-5 of 5 entries]");
+        assert_eq!(page(serde_json::json!({ "limit": 10 })), lines.join("\n"));
+        assert_eq!(page(serde_json::json!({ "offset": 9 })), "No entries at offset 9 (5 total)");
+    }
+}
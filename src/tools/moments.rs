@@ -0,0 +1,273 @@
+//! Moments tool - browse the screenshots and pages saved by the browser bridge
+//!
+//! Each moment is a directory `<moments_dir>/<slug>/<timestamp>/` holding
+//! `screenshot.png` and, for page captures, `metadata.json` (title, url, text).
+
+use std::path::{Component, Path, PathBuf};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use crate::Result;
+use crate::error::Error;
+use super::{Page, Tool};
+
+/// Moments listed per page by default
+const DEFAULT_MOMENTS_LIMIT: usize = 20;
+
+/// Most characters of page text `get` returns
+const MAX_MOMENT_TEXT: usize = 4000;
+
+/// Format of the `<timestamp>` directory names
+pub(crate) const MOMENT_TIMESTAMP_FORMAT: &str = "%Y%m%d_%H%M%S";
+
+/// One saved moment
+struct Moment {
+    /// `<slug>/<timestamp>`, the id `get` takes
+    id: String,
+    dir: PathBuf,
+    timestamp: String,
+    metadata: Option<Value>,
+}
+
+impl Moment {
+    fn load(root: &Path, slug: &str, timestamp: &str) -> Self {
+        let dir = root.join(slug).join(timestamp);
+        let metadata = std::fs::read_to_string(dir.join("metadata.json"))
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok());
+        Self { id: format!("{}/{}", slug, timestamp), dir, timestamp: timestamp.to_string(), metadata }
+    }
+
+    fn field(&self, key: &str) -> Option<&str> {
+        self.metadata.as_ref()?.get(key)?.as_str().filter(|s| !s.is_empty())
+    }
+
+    /// "2026-03-01 14:05:09", or the raw directory name if it isn't a timestamp
+    fn saved_at(&self) -> String {
+        chrono::NaiveDateTime::parse_from_str(&self.timestamp, MOMENT_TIMESTAMP_FORMAT)
+            .map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_else(|_| self.timestamp.clone())
+    }
+
+    fn title(&self) -> &str {
+        self.field("title").unwrap_or("(screenshot only)")
+    }
+
+    fn summary(&self) -> String {
+        match self.field("url") {
+            Some(url) => format!("{}  {}  {} <{}>", self.id, self.saved_at(), self.title(), url),
+            None => format!("{}  {}  {}", self.id, self.saved_at(), self.title()),
+        }
+    }
+}
+
+/// Subdirectory names of `dir`
+fn subdirs(dir: &Path) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(dir) else { return Vec::new() };
+    entries
+        .flatten()
+        .filter(|e| e.path().is_dir())
+        .map(|e| e.file_name().to_string_lossy().to_string())
+        .collect()
+}
+
+/// List and recall saved browser moments
+pub struct MomentsTool {
+    root: PathBuf,
+}
+
+impl MomentsTool {
+    /// Browse the moments saved under `root` (`Config::moments_path`)
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    /// Every moment, newest first
+    fn all(&self) -> Vec<Moment> {
+        let mut moments: Vec<Moment> = subdirs(&self.root)
+            .iter()
+            .flat_map(|slug| {
+                subdirs(&self.root.join(slug))
+                    .into_iter()
+                    .map(move |timestamp| Moment::load(&self.root, slug, &timestamp))
+            })
+            .collect();
+        moments.sort_by(|a, b| b.timestamp.cmp(&a.timestamp).then_with(|| a.id.cmp(&b.id)));
+        moments
+    }
+
+    fn list(&self, params: &Value) -> String {
+        let query = params.get("query").and_then(|v| v.as_str()).map(str::to_lowercase);
+        let lines: Vec<String> = self.all()
+            .iter()
+            .map(Moment::summary)
+            .filter(|line| query.as_ref().is_none_or(|q| line.to_lowercase().contains(q)))
+            .collect();
+
+        if lines.is_empty() {
+            return match query {
+                Some(q) => format!("No moments match '{}'", q),
+                None => "No moments saved yet. Use the browser tool's 'moment' action to capture one".to_string(),
+            };
+        }
+        Page::from_params(params, DEFAULT_MOMENTS_LIMIT).apply(&lines, "moments")
+    }
+
+    /// A moment by `<slug>/<timestamp>` id, or the latest one for a bare slug
+    fn find(&self, id: &str) -> Result<Moment> {
+        let id = id.trim().trim_matches('/');
+        let path = Path::new(id);
+        if id.is_empty() || !path.components().all(|c| matches!(c, Component::Normal(_))) {
+            return Err(Error::Tool(format!("Invalid moment id: {}", id)));
+        }
+
+        let moment = match id.split_once('/') {
+            Some((slug, timestamp)) => Moment::load(&self.root, slug, timestamp),
+            None => {
+                let latest = subdirs(&self.root.join(id)).into_iter().max();
+                match latest {
+                    Some(timestamp) => Moment::load(&self.root, id, &timestamp),
+                    None => return Err(Error::Tool(format!("No moment '{}'. Use action='list' to see saved moments", id))),
+                }
+            }
+        };
+
+        if !moment.dir.is_dir() {
+            return Err(Error::Tool(format!("No moment '{}'. Use action='list' to see saved moments", id)));
+        }
+        Ok(moment)
+    }
+
+    fn get(&self, id: &str) -> Result<String> {
+        let moment = self.find(id)?;
+        let mut out = vec![
+            format!("Moment: {}", moment.id),
+            format!("Saved: {}", moment.saved_at()),
+            format!("Title: {}", moment.title()),
+        ];
+        if let Some(url) = moment.field("url") {
+            out.push(format!("URL: {}", url));
+        }
+        let screenshot = moment.dir.join("screenshot.png");
+        if screenshot.is_file() {
+            out.push(format!("Screenshot: {}", screenshot.display()));
+        }
+        if let Some(text) = moment.field("text") {
+            let text = text.trim();
+            let shown: String = text.chars().take(MAX_MOMENT_TEXT).collect();
+            out.push(String::new());
+            out.push(shown);
+            let total = text.chars().count();
+            if total > MAX_MOMENT_TEXT {
+                out.push(format!("\n[Page text truncated: {} of {} chars]", MAX_MOMENT_TEXT, total));
+            }
+        }
+        Ok(out.join("\n"))
+    }
+}
+
+#[async_trait]
+impl Tool for MomentsTool {
+    fn name(&self) -> &str { "moments" }
+    fn description(&self) -> &str {
+        "Browse pages and screenshots saved with the browser's 'moment'/'screenshot' actions. \
+         'list' shows saved moments (newest first), 'get' returns one's title, URL, screenshot path and page text"
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["list", "get"],
+                    "description": "Action to perform"
+                },
+                "id": {
+                    "type": "string",
+                    "description": "Moment to get, as shown by list (<slug>/<timestamp>); a bare slug gets its latest moment"
+                },
+                "query": {
+                    "type": "string",
+                    "description": "Only list moments whose id, title or URL contains this text"
+                },
+                "offset": {
+                    "type": "integer",
+                    "description": "Skip this many moments (for paging)"
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": "Most moments to list (default 20)"
+                }
+            },
+            "required": ["action"]
+        })
+    }
+
+    async fn execute(&self, params: Value) -> Result<String> {
+        let action = params.get("action")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| Error::Tool("Missing 'action' parameter".to_string()))?;
+
+        match action {
+            "list" => Ok(self.list(&params)),
+            "get" => {
+                let id = params.get("id")
+                    .and_then(|v| v.as_str())
+                    .ok_or_else(|| Error::Tool("Missing 'id' parameter".to_string()))?;
+                self.get(id)
+            }
+            other => Err(Error::Tool(format!("Unknown action: {}. Use list or get", other))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn save(root: &Path, slug: &str, timestamp: &str, metadata: Option<Value>) {
+        let dir = root.join(slug).join(timestamp);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("screenshot.png"), b"png").unwrap();
+        if let Some(metadata) = metadata {
+            std::fs::write(dir.join("metadata.json"), metadata.to_string()).unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_list_and_get_moments() {
+        let tmp = TempDir::new().unwrap();
+        let tool = MomentsTool::new(tmp.path().to_path_buf());
+        assert!(tool.execute(json!({ "action": "list" })).await.unwrap().starts_with("No moments saved"));
+
+        save(tmp.path(), "rust_docs", "20260301_140509", Some(json!({
+            "title": "Rust Docs", "url": "https://doc.rust-lang.org", "text": "The Rust book", "html": "<html>"
+        })));
+        save(tmp.path(), "rust_docs", "20260302_090000", Some(json!({ "title": "Rust Docs v2", "text": "Newer" })));
+        save(tmp.path(), "screenshot", "20260228_080000", None);
+
+        let list = tool.execute(json!({ "action": "list" })).await.unwrap();
+        let lines: Vec<&str> = list.lines().collect();
+        assert_eq!(lines, vec![
+            "rust_docs/20260302_090000  2026-03-02 09:00:00  Rust Docs v2",
+            "rust_docs/20260301_140509  2026-03-01 14:05:09  Rust Docs <https://doc.rust-lang.org>",
+            "screenshot/20260228_080000  2026-02-28 08:00:00  (screenshot only)",
+        ]);
+
+        let filtered = tool.execute(json!({ "action": "list", "query": "DOC.RUST" })).await.unwrap();
+        assert_eq!(filtered, lines[1]);
+
+        let got = tool.execute(json!({ "action": "get", "id": "rust_docs/20260301_140509" })).await.unwrap();
+        assert!(got.contains("Title: Rust Docs\nURL: https://doc.rust-lang.org"));
+        assert!(got.contains("screenshot.png"));
+        assert!(got.ends_with("The Rust book"));
+        assert!(!got.contains("<html>"));
+
+        let latest = tool.execute(json!({ "action": "get", "id": "rust_docs" })).await.unwrap();
+        assert!(latest.starts_with("Moment: rust_docs/20260302_090000"));
+
+        assert!(tool.execute(json!({ "action": "get", "id": "../secrets" })).await.is_err());
+        assert!(tool.execute(json!({ "action": "get", "id": "rust_docs/19990101_000000" })).await.is_err());
+    }
+}
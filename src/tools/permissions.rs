@@ -0,0 +1,174 @@
+//! Permissions tool - set Unix file modes without shelling out to chmod
+
+use std::path::{Path, PathBuf};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use crate::Result;
+use crate::error::Error;
+use super::{DangerLevel, Tool, resolve_path};
+
+/// Change a file's permission bits
+pub struct PermissionsTool {
+    workspace: PathBuf,
+    restrict_to_workspace: bool,
+}
+
+impl PermissionsTool {
+    pub fn new(workspace: PathBuf) -> Self {
+        Self { workspace, restrict_to_workspace: false }
+    }
+
+    /// Refuse paths outside the workspace
+    pub fn restricted(mut self, restrict: bool) -> Self {
+        self.restrict_to_workspace = restrict;
+        self
+    }
+
+    fn check_scope(&self, path: &Path) -> Result<()> {
+        if !self.restrict_to_workspace {
+            return Ok(());
+        }
+        let workspace = self.workspace.canonicalize()?;
+        if path.canonicalize()?.starts_with(&workspace) {
+            Ok(())
+        } else {
+            Err(Error::Tool(format!(
+                "{} is outside the workspace ({})", path.display(), workspace.display()
+            )))
+        }
+    }
+}
+
+/// Apply `mode` ("755", "0644", "+x" or "-x") to `current` permission bits
+fn parse_mode(mode: &str, current: u32) -> Result<u32> {
+    match mode.trim() {
+        "+x" => Ok(current | ((current & 0o444) >> 2)),
+        "-x" => Ok(current & !0o111),
+        octal => {
+            let digits = octal.strip_prefix("0o").unwrap_or(octal);
+            match u32::from_str_radix(digits, 8) {
+                Ok(bits) if !digits.is_empty() && bits <= 0o7777 => Ok(bits),
+                _ => Err(Error::Tool(format!(
+                    "Invalid mode '{}': use octal like 755 or 0644, or +x / -x", mode
+                ))),
+            }
+        }
+    }
+}
+
+/// Render the permission bits like `ls -l`, e.g. "rwxr-xr-x"
+fn symbolic(mode: u32) -> String {
+    (0..9).rev()
+        .map(|bit| {
+            if mode & (1 << bit) == 0 {
+                '-'
+            } else {
+                ['x', 'w', 'r'][bit % 3]
+            }
+        })
+        .collect()
+}
+
+#[cfg(unix)]
+fn apply_mode(path: &Path, mode: &str) -> Result<u32> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mut permissions = std::fs::metadata(path)?.permissions();
+    let bits = parse_mode(mode, permissions.mode() & 0o7777)?;
+    permissions.set_mode(bits);
+    std::fs::set_permissions(path, permissions)?;
+    Ok(std::fs::metadata(path)?.permissions().mode() & 0o7777)
+}
+
+#[cfg(not(unix))]
+fn apply_mode(path: &Path, mode: &str) -> Result<u32> {
+    parse_mode(mode, 0)?;
+    tracing::warn!("chmod is not supported on this platform; left {} unchanged", path.display());
+    Err(Error::Tool("Unix permissions are not supported on this platform".to_string()))
+}
+
+#[async_trait]
+impl Tool for PermissionsTool {
+    fn name(&self) -> &str { "chmod" }
+    fn description(&self) -> &str {
+        "Set Unix permissions on a file, e.g. make a script executable. Returns the resulting mode"
+    }
+
+    fn is_mutating(&self, _params: &Value) -> bool { true }
+
+    fn danger_level(&self) -> DangerLevel { DangerLevel::Caution }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "File or directory to change"
+                },
+                "mode": {
+                    "type": "string",
+                    "description": "Octal mode like '755' or '0644', or '+x' / '-x' to toggle execute"
+                }
+            },
+            "required": ["path", "mode"]
+        })
+    }
+
+    async fn execute(&self, params: Value) -> Result<String> {
+        let path = params.get("path")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| Error::Tool("Missing 'path' parameter".to_string()))?;
+
+        let mode = params.get("mode")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| Error::Tool("Missing 'mode' parameter".to_string()))?;
+
+        let target = resolve_path(&self.workspace, path);
+        if !target.exists() {
+            return Err(Error::Tool(format!("No such file: {}", target.display())));
+        }
+        self.check_scope(&target)?;
+
+        let bits = apply_mode(&target, mode)?;
+        Ok(format!("{} is now {:o} ({})", target.display(), bits, symbolic(bits)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_mode() {
+        assert_eq!(parse_mode("755", 0).unwrap(), 0o755);
+        assert_eq!(parse_mode("0644", 0).unwrap(), 0o644);
+        assert_eq!(parse_mode("+x", 0o644).unwrap(), 0o755);
+        assert_eq!(parse_mode("+x", 0o600).unwrap(), 0o700);
+        assert_eq!(parse_mode("-x", 0o755).unwrap(), 0o644);
+        assert!(parse_mode("rwx", 0).is_err());
+        assert!(parse_mode("999", 0).is_err());
+        assert!(parse_mode("17777", 0).is_err());
+        assert_eq!(symbolic(0o750), "rwxr-x---");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_chmod_in_workspace() {
+        let tmp = TempDir::new().unwrap();
+        std::fs::write(tmp.path().join("run.sh"), "#!/bin/sh\n").unwrap();
+        let tool = PermissionsTool::new(tmp.path().to_path_buf()).restricted(true);
+
+        let result = tool.execute(json!({ "path": "run.sh", "mode": "750" })).await.unwrap();
+        assert!(result.ends_with("is now 750 (rwxr-x---)"));
+
+        let outside = TempDir::new().unwrap();
+        std::fs::write(outside.path().join("other.sh"), "").unwrap();
+        let err = tool.execute(json!({
+            "path": outside.path().join("other.sh").to_str().unwrap(),
+            "mode": "+x"
+        })).await.unwrap_err();
+        assert!(err.to_string().contains("outside the workspace"));
+    }
+}
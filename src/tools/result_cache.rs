@@ -0,0 +1,90 @@
+//! Result cache - reuse the output of repeated read-only tool calls
+//!
+//! Opt-in via `tool_cache_ttl_secs`. Only tools whose `Tool::cacheable` is true
+//! are cached, keyed by tool name and arguments; any mutating call clears it.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use serde_json::Value;
+
+/// Argument that skips the cache for one call
+pub(crate) const NO_CACHE_PARAM: &str = "no_cache";
+
+/// Outputs of cacheable tool calls, each kept for `ttl`
+pub(crate) struct ResultCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<String, (Instant, String)>>,
+}
+
+impl ResultCache {
+    pub(crate) fn new(ttl: Duration) -> Self {
+        Self { ttl, entries: Mutex::new(HashMap::new()) }
+    }
+
+    /// `serde_json` objects keep their keys sorted, so equal arguments give equal keys
+    fn key(name: &str, params: &Value) -> String {
+        format!("{}:{}", name, params)
+    }
+
+    /// The stored output of this call, if it hasn't expired
+    pub(crate) fn get(&self, name: &str, params: &Value) -> Option<String> {
+        let key = Self::key(name, params);
+        let mut entries = self.entries.lock().unwrap();
+        match entries.get(&key) {
+            Some((stored, output)) if stored.elapsed() < self.ttl => Some(output.clone()),
+            Some(_) => {
+                entries.remove(&key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    pub(crate) fn put(&self, name: &str, params: &Value, output: &str) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|_, (stored, _)| stored.elapsed() < self.ttl);
+        entries.insert(Self::key(name, params), (Instant::now(), output.to_string()));
+    }
+
+    /// Forget everything (after a call that may have changed what was read)
+    pub(crate) fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+}
+
+/// Remove `no_cache` from the arguments, returning whether it was set
+pub(crate) fn take_no_cache(params: &mut Value) -> bool {
+    params.as_object_mut()
+        .and_then(|map| map.remove(NO_CACHE_PARAM))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_cache_keys_and_expiry() {
+        let cache = ResultCache::new(Duration::from_secs(60));
+        cache.put("search", &json!({ "query": "fn main", "path": "src" }), "src/main.rs:1: fn main()");
+        assert_eq!(
+            cache.get("search", &json!({ "path": "src", "query": "fn main" })).as_deref(),
+            Some("src/main.rs:1: fn main()")
+        );
+        assert!(cache.get("find_files", &json!({ "query": "fn main", "path": "src" })).is_none());
+        cache.clear();
+        assert!(cache.get("search", &json!({ "query": "fn main", "path": "src" })).is_none());
+
+        let expired = ResultCache::new(Duration::ZERO);
+        expired.put("read_file", &json!({ "path": "a" }), "a");
+        assert!(expired.get("read_file", &json!({ "path": "a" })).is_none());
+
+        let mut params = json!({ "path": "a", "no_cache": true });
+        assert!(take_no_cache(&mut params));
+        assert_eq!(params, json!({ "path": "a" }));
+        assert!(!take_no_cache(&mut params));
+    }
+}
@@ -2,20 +2,61 @@
 
 use std::collections::HashMap;
 use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use crate::Result;
 use crate::error::Error;
-use super::Tool;
+use super::{Tool, ToolMiddleware, LoggingMiddleware, MetricsMiddleware, TimingMiddleware, ApprovalHandler};
 use super::filesystem::{ReadFileTool, WriteFileTool, ListDirTool};
 use super::edit::EditTool;
+use super::template::TemplateTool;
+use super::permissions::PermissionsTool;
 use super::search::SearchTool;
 use super::find::FindFilesTool;
+use super::tail::TailTool;
+use super::count::CountTool;
+use super::csv::CsvTool;
+use super::jq::JsonQueryTool;
 use super::git::GitTool;
 use super::memory::MemoryTool;
 use super::task::TaskTool;
 use super::shell::ExecTool;
-use super::web::{WebSearchTool, WebFetchTool};
+use super::web::{WebSearchTool, WebFetchTool, WebCache};
+use super::download::DownloadTool;
+use super::calendar::CalendarTool;
+use super::moments::MomentsTool;
+use super::continuation::{Continuations, MoreOutputTool, MORE_OUTPUT_TOOL, SESSION_PARAM};
+use super::result_cache::{take_no_cache, ResultCache, NO_CACHE_PARAM};
+use crate::config::Config;
+
+/// How much harm a tool can do if misused, shown to the model with its description
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DangerLevel {
+    /// Only reads, or touches Leo's own notes
+    #[default]
+    Safe,
+    /// Changes files or acts on other services
+    Caution,
+    /// Can delete or overwrite anything (e.g. arbitrary shell commands)
+    Destructive,
+}
+
+impl DangerLevel {
+    /// Note appended to the tool's description (`None` for safe tools)
+    pub fn note(self) -> Option<&'static str> {
+        match self {
+            Self::Safe => None,
+            Self::Caution => Some("Caution: changes files or outside state; double-check the arguments"),
+            Self::Destructive => Some(
+                "Danger: can delete or overwrite data irreversibly; prefer a safer dedicated tool \
+                 (e.g. edit or write_file over shell redirects, never rm what the user didn't ask to remove)"
+            ),
+        }
+    }
+}
 
 /// Tool definition for LLM
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -23,72 +64,192 @@ pub struct ToolDefinition {
     pub name: String,
     pub description: String,
     pub parameters: Value,
+    /// Also noted at the end of `description`, which is all most providers send
+    #[serde(default)]
+    pub danger_level: DangerLevel,
 }
 
 /// Tool runner manages registered tools and executes them
 pub struct ToolRunner {
     tools: HashMap<String, Box<dyn Tool>>,
+    /// Hooks run around every call, in order
+    middleware: Vec<Box<dyn ToolMiddleware>>,
+    /// Describe mutating calls instead of running them
+    dry_run: bool,
+    /// Asked before each mutating call, when set
+    approval: Option<Arc<dyn ApprovalHandler>>,
+    /// Rest of outputs too long to return at once, fetched with `more_output`
+    continuations: Continuations,
+    /// Session of the current run, which truncated outputs are tied to
+    session: String,
+    /// Results of cacheable tools, when enabled
+    cache: Option<ResultCache>,
 }
 
 impl ToolRunner {
-    /// Create an empty tool runner
+    /// Create a tool runner with only `more_output` and the built-in logging, timing and metrics middleware
     pub fn new() -> Self {
-        Self {
+        let continuations = Continuations::new();
+        let mut runner = Self {
             tools: HashMap::new(),
-        }
+            middleware: vec![Box::new(LoggingMiddleware), Box::new(TimingMiddleware), Box::new(MetricsMiddleware)],
+            dry_run: false,
+            approval: None,
+            continuations: continuations.clone(),
+            session: String::new(),
+            cache: None,
+        };
+        runner.register(MoreOutputTool::new(continuations));
+        runner
     }
     
     /// Create a tool runner with default tools
     pub fn new_with_defaults(workspace: &Path) -> Self {
-        let mut runner = Self::new();
-        
-        // File tools
-        runner.register(ReadFileTool);
-        runner.register(WriteFileTool);
-        runner.register(ListDirTool);
-        runner.register(EditTool);
-        runner.register(SearchTool::new(workspace.to_path_buf()));
-        runner.register(FindFilesTool::new(workspace.to_path_buf()));
-        
-        // Shell & Git tools
-        runner.register(ExecTool::new(workspace.to_path_buf()));
-        runner.register(GitTool::new(workspace.to_path_buf()));
-        
-        // Memory & Task tools
-        runner.register(MemoryTool::new(workspace.to_path_buf()));
-        runner.register(TaskTool::new(workspace.to_path_buf()));
-        
-        // Create Browser Bridge (Extension) instance first to share it
-        let browser = super::browser_bridge::BrowserBridgeTool::new();
-
-        // Web tools (now with browser support)
-        runner.register(WebSearchTool::new(Some(browser.clone())));
-        runner.register(WebFetchTool::new(Some(browser.clone())));
-        
-        // Browser Bridge (registered as its own tool too)
-        runner.register(browser);
-        
+        Self::from_config(&Config {
+            workspace: workspace.to_path_buf(),
+            ..Config::default()
+        })
+    }
+    
+    /// Create a tool runner with default tools, applying tool settings from `config`
+    pub fn from_config(config: &Config) -> Self {
+        let mut runner = Self::from_tools(default_tools(config));
+        runner.dry_run = config.dry_run;
+        if config.tool_cache_ttl_secs > 0 {
+            runner.set_result_cache(Some(Duration::from_secs(config.tool_cache_ttl_secs)));
+        }
         runner
     }
     
+    /// Create a tool runner holding `tools` (plus `more_output`)
+    pub fn from_tools(tools: Vec<Box<dyn Tool>>) -> Self {
+        Self::new().with_tools(tools)
+    }
+    
+    /// Add `tools`, builder style
+    pub fn with_tools(mut self, tools: Vec<Box<dyn Tool>>) -> Self {
+        self.register_all(tools);
+        self
+    }
+    
     /// Register a tool
     pub fn register<T: Tool + 'static>(&mut self, tool: T) {
         self.tools.insert(tool.name().to_string(), Box::new(tool));
     }
     
+    /// Register every tool in `tools`; a later tool replaces an earlier one with the same name
+    pub fn register_all(&mut self, tools: Vec<Box<dyn Tool>>) {
+        for tool in tools {
+            self.tools.insert(tool.name().to_string(), tool);
+        }
+    }
+    
     /// Get tool definitions for LLM
     pub fn definitions(&self) -> Vec<ToolDefinition> {
         self.tools.values()
-            .map(|t| t.to_definition())
+            .map(|t| {
+                let mut definition = t.to_definition();
+                if self.cache.is_some() && t.cacheable() {
+                    if let Some(properties) = definition.parameters["properties"].as_object_mut() {
+                        properties.insert(NO_CACHE_PARAM.to_string(), serde_json::json!({
+                            "type": "boolean",
+                            "description": "Skip the cached result of an identical earlier call (e.g. after changing files with exec)"
+                        }));
+                    }
+                }
+                definition
+            })
             .collect()
     }
     
     /// Execute a tool by name
-    pub async fn execute(&self, name: &str, params: Value) -> Result<String> {
+    pub async fn execute(&self, name: &str, mut params: Value) -> Result<String> {
         let tool = self.tools.get(name)
             .ok_or_else(|| Error::Tool(format!("Unknown tool: {}", name)))?;
         
-        tool.execute(params).await
+        if self.dry_run && tool.is_mutating(&params) {
+            return Ok(format!(
+                "[dry run] Not executed. Would call {} with {}",
+                name,
+                preview_params(&params)
+            ));
+        }
+        
+        if let Some(approval) = &self.approval {
+            if tool.is_mutating(&params) && !approval.approve(name, &preview_params(&params)).await {
+                return Err(Error::Tool(format!(
+                    "The user declined this {} call. Don't retry it; ask how they'd like to proceed",
+                    name
+                )));
+            }
+        }
+        
+        // Only hand back remainders stored for this session, whatever the model passed
+        if name == MORE_OUTPUT_TOOL {
+            if let Some(params) = params.as_object_mut() {
+                params.insert(SESSION_PARAM.to_string(), Value::String(self.session.clone()));
+            }
+        }
+        
+        let cache = self.cache.as_ref().filter(|_| tool.cacheable());
+        let no_cache = cache.is_some() && take_no_cache(&mut params);
+        let mutating = tool.is_mutating(&params);
+        
+        for middleware in &self.middleware {
+            middleware.before(name, &params)?;
+        }
+        
+        let cached = cache.filter(|_| !no_cache).and_then(|cache| cache.get(name, &params));
+        let hit = cached.is_some();
+        let started = std::time::Instant::now();
+        let result = match cached {
+            Some(output) => {
+                tracing::debug!("Reusing cached result of {}", name);
+                Ok(output)
+            }
+            None => tool.execute(params.clone()).await,
+        };
+        let elapsed = started.elapsed();
+        
+        for middleware in &self.middleware {
+            middleware.after(name, &result, elapsed);
+        }
+        
+        if let (Some(cache), Ok(output), false) = (cache, &result, hit) {
+            cache.put(name, &params, output);
+        }
+        // Whatever was read before may have changed
+        if mutating {
+            if let Some(cache) = &self.cache {
+                cache.clear();
+            }
+        }
+        result.map(|output| self.continuations.paginate(&self.session, output))
+    }
+    
+    /// Add a middleware, run after those already registered
+    pub fn add_middleware<M: ToolMiddleware + 'static>(&mut self, middleware: M) {
+        self.middleware.push(Box::new(middleware));
+    }
+    
+    /// Describe mutating tool calls instead of running them
+    pub fn set_dry_run(&mut self, dry_run: bool) {
+        self.dry_run = dry_run;
+    }
+    
+    /// Reuse results of cacheable tools for `ttl` (`None` disables caching)
+    pub fn set_result_cache(&mut self, ttl: Option<Duration>) {
+        self.cache = ttl.map(ResultCache::new);
+    }
+    
+    /// Tie truncated outputs to `session`, so `more_output` can't read another chat's
+    pub fn set_session(&mut self, session: &str) {
+        self.session = session.to_string();
+    }
+    
+    /// Ask `approval` before every mutating call (`None` runs them without asking)
+    pub fn set_approval(&mut self, approval: Option<Arc<dyn ApprovalHandler>>) {
+        self.approval = approval;
     }
     
     /// Check if a tool exists
@@ -102,6 +263,85 @@ impl ToolRunner {
     }
 }
 
+/// The built-in tools for `config`, for callers that want to add or remove some
+/// before building a runner with `ToolRunner::from_tools`
+pub fn default_tools(config: &Config) -> Vec<Box<dyn Tool>> {
+    let workspace = config.workspace.as_path();
+    let mut tools: Vec<Box<dyn Tool>> = vec![
+        // File tools
+        Box::new(ReadFileTool::new(workspace.to_path_buf())),
+        Box::new(WriteFileTool::new(workspace.to_path_buf())),
+        Box::new(ListDirTool::new(workspace.to_path_buf())),
+        Box::new(EditTool::new(workspace.to_path_buf())),
+        Box::new(SearchTool::new(workspace.to_path_buf())),
+        Box::new(FindFilesTool::new(workspace.to_path_buf())),
+        Box::new(TailTool::new(workspace.to_path_buf())),
+        Box::new(CountTool::new(workspace.to_path_buf())),
+        Box::new(TemplateTool::new(workspace.to_path_buf())),
+        Box::new(PermissionsTool::new(workspace.to_path_buf()).restricted(config.restrict_chmod_to_workspace)),
+        
+        // Data tools
+        Box::new(CsvTool::new(workspace.to_path_buf())),
+        Box::new(JsonQueryTool::new(workspace.to_path_buf())),
+        
+        // Shell & Git tools
+        Box::new(ExecTool::new(workspace.to_path_buf())),
+        Box::new(GitTool::new(workspace.to_path_buf())),
+        
+        // Memory & Task tools
+        Box::new(MemoryTool::new(workspace.to_path_buf())),
+        Box::new(TaskTool::new(workspace.to_path_buf())),
+    ];
+    
+    // Create Browser Bridge (Extension) instance first to share it
+    let browser = super::browser_bridge::BrowserBridgeTool::new(config.moments_path(), &config.search_engine);
+    
+    // Web tools (now with browser support)
+    tools.push(Box::new(WebSearchTool::new(Some(browser.clone())).with_search_config(&config.web_search)));
+    tools.push(Box::new(WebFetchTool::new(Some(browser.clone())).with_cache(WebCache::from_config(config))));
+    tools.push(Box::new(DownloadTool::new(workspace.to_path_buf())));
+    
+    // Browser Bridge (registered as its own tool too)
+    tools.push(Box::new(browser));
+    tools.push(Box::new(MomentsTool::new(config.moments_path())));
+    
+    // Google Calendar, sharing the Gemini CLI OAuth sign-in
+    if config.google_calendar {
+        match crate::auth::GeminiAuthProvider::from_cli() {
+            Ok(auth) => tools.push(Box::new(CalendarTool::new(auth.with_calendar(true)))),
+            Err(e) => tracing::warn!("Calendar tool disabled: {}", e),
+        }
+    }
+    
+    // Headless screenshots, when built in and Chrome is installed
+    #[cfg(feature = "headless")]
+    if super::screenshot::ScreenshotTool::is_available() {
+        tools.push(Box::new(super::screenshot::ScreenshotTool::new(config.moments_path())));
+    }
+    
+    tools
+}
+
+/// Parameters as compact JSON, with long strings (e.g. file contents) shortened
+fn preview_params(params: &Value) -> String {
+    const MAX_CHARS: usize = 200;
+    
+    let shortened = match params {
+        Value::Object(map) => Value::Object(map.iter().map(|(key, value)| {
+            let value = match value.as_str() {
+                Some(s) if s.chars().count() > MAX_CHARS => {
+                    let head: String = s.chars().take(MAX_CHARS).collect();
+                    Value::String(format!("{}… ({} chars)", head, s.chars().count()))
+                }
+                _ => value.clone(),
+            };
+            (key.clone(), value)
+        }).collect()),
+        other => other.clone(),
+    };
+    shortened.to_string()
+}
+
 impl Default for ToolRunner {
     fn default() -> Self {
         Self::new()
@@ -127,6 +367,190 @@ mod tests {
         assert_eq!(result, "success");
     }
     
+    #[tokio::test]
+    async fn test_from_tools() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let config = Config { workspace: tmp.path().to_path_buf(), ..Config::default() };
+        let mut tools = default_tools(&config);
+        tools.retain(|tool| tool.name() != "exec");
+        tools.push(Box::new(DummyTool { name: "greet".to_string(), result: "hi".to_string() }));
+        
+        let runner = ToolRunner::from_tools(tools);
+        assert!(runner.has("read_file") && runner.has("more_output"));
+        assert!(!runner.has("exec"));
+        assert_eq!(runner.execute("greet", serde_json::json!({})).await.unwrap(), "hi");
+    }
+    
+    #[tokio::test]
+    async fn test_result_cache() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        
+        /// Counts its calls; cacheable unless it's the writer
+        struct Counting { name: &'static str, calls: Arc<AtomicUsize> }
+        
+        #[async_trait::async_trait]
+        impl Tool for Counting {
+            fn name(&self) -> &str { self.name }
+            fn description(&self) -> &str { "Counts calls" }
+            fn parameters(&self) -> Value { serde_json::json!({ "type": "object", "properties": {} }) }
+            fn cacheable(&self) -> bool { self.name == "reader" }
+            fn is_mutating(&self, _params: &Value) -> bool { self.name == "writer" }
+            async fn execute(&self, _params: Value) -> Result<String> {
+                Ok(format!("call {}", self.calls.fetch_add(1, Ordering::SeqCst) + 1))
+            }
+        }
+        
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mut runner = ToolRunner::new();
+        runner.register(Counting { name: "reader", calls: calls.clone() });
+        runner.register(Counting { name: "writer", calls: Arc::new(AtomicUsize::new(0)) });
+        let read = |params: Value| runner.execute("reader", params);
+        
+        // Off by default
+        assert_eq!(read(serde_json::json!({ "path": "a" })).await.unwrap(), "call 1");
+        assert_eq!(read(serde_json::json!({ "path": "a" })).await.unwrap(), "call 2");
+        
+        runner.set_result_cache(Some(Duration::from_secs(60)));
+        let read = |params: Value| runner.execute("reader", params);
+        assert_eq!(read(serde_json::json!({ "path": "a" })).await.unwrap(), "call 3");
+        assert_eq!(read(serde_json::json!({ "path": "a" })).await.unwrap(), "call 3");
+        assert_eq!(read(serde_json::json!({ "path": "b" })).await.unwrap(), "call 4");
+        assert_eq!(read(serde_json::json!({ "path": "a", "no_cache": true })).await.unwrap(), "call 5");
+        
+        // A mutating call invalidates everything
+        runner.execute("writer", serde_json::json!({})).await.unwrap();
+        assert_eq!(read(serde_json::json!({ "path": "b" })).await.unwrap(), "call 6");
+        
+        let reader = runner.definitions().into_iter().find(|d| d.name == "reader").unwrap();
+        assert!(reader.parameters["properties"]["no_cache"].is_object());
+    }
+    
+    #[tokio::test]
+    async fn test_long_output_continues() {
+        let mut runner = ToolRunner::new();
+        let output = "y".repeat(25_000);
+        runner.register(DummyTool {
+            name: "big".to_string(),
+            result: output.clone(),
+        });
+        runner.set_session("telegram:1");
+        
+        let first = runner.execute("big", serde_json::json!({})).await.unwrap();
+        let (head, note) = first.split_once("\n\n[Output truncated").unwrap();
+        assert_eq!(head.len(), 20_000);
+        assert!(note.contains("5000 more chars"));
+        let token = note.split("token=\"").nth(1).unwrap().split('"').next().unwrap();
+        
+        // Another session can't read it, even by naming the first one
+        runner.set_session("telegram:2");
+        let spoofed = serde_json::json!({ "token": token, "session": "telegram:1" });
+        assert!(runner.execute("more_output", spoofed).await.is_err());
+        
+        runner.set_session("telegram:1");
+        let rest = runner.execute("more_output", serde_json::json!({ "token": token })).await.unwrap();
+        assert_eq!(format!("{}{}", head, rest), output);
+    }
+    
+    #[tokio::test]
+    async fn test_dry_run_skips_mutating_tools() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let mut runner = ToolRunner::from_config(&Config {
+            workspace: tmp.path().to_path_buf(),
+            dry_run: true,
+            ..Config::default()
+        });
+        std::fs::write(tmp.path().join("notes.txt"), "keep").unwrap();
+        
+        let result = runner.execute("write_file", serde_json::json!({
+            "path": "notes.txt", "content": "x".repeat(500)
+        })).await.unwrap();
+        assert!(result.starts_with("[dry run] Not executed. Would call write_file"));
+        assert!(result.contains("(500 chars)"));
+        assert_eq!(std::fs::read_to_string(tmp.path().join("notes.txt")).unwrap(), "keep");
+        
+        // Read-only tools still run
+        let read = runner.execute("read_file", serde_json::json!({ "path": "notes.txt" })).await.unwrap();
+        assert_eq!(read, "keep");
+        
+        runner.set_dry_run(false);
+        runner.execute("write_file", serde_json::json!({
+            "path": "notes.txt", "content": "changed"
+        })).await.unwrap();
+        assert_eq!(std::fs::read_to_string(tmp.path().join("notes.txt")).unwrap(), "changed");
+    }
+    
+    #[tokio::test]
+    async fn test_approval_gates_mutating_tools() {
+        use std::sync::Mutex;
+        
+        /// Approves only the calls whose preview mentions "ok", recording each question
+        struct Approver(Mutex<Vec<String>>);
+        
+        #[async_trait::async_trait]
+        impl ApprovalHandler for Approver {
+            async fn approve(&self, tool: &str, preview: &str) -> bool {
+                self.0.lock().unwrap().push(format!("{} {}", tool, preview));
+                preview.contains("ok")
+            }
+        }
+        
+        let tmp = tempfile::TempDir::new().unwrap();
+        let mut runner = ToolRunner::new_with_defaults(tmp.path());
+        let approver = Arc::new(Approver(Mutex::new(Vec::new())));
+        runner.set_approval(Some(approver.clone()));
+        
+        let err = runner.execute("write_file", serde_json::json!({
+            "path": "a.txt", "content": "no"
+        })).await.unwrap_err();
+        assert!(err.to_string().contains("declined"));
+        assert!(!tmp.path().join("a.txt").exists());
+        
+        runner.execute("write_file", serde_json::json!({ "path": "a.txt", "content": "ok" })).await.unwrap();
+        runner.execute("read_file", serde_json::json!({ "path": "a.txt" })).await.unwrap();
+        
+        // Read-only calls are never asked about
+        assert_eq!(approver.0.lock().unwrap().len(), 2);
+        assert!(approver.0.lock().unwrap()[1].starts_with("write_file {"));
+    }
+    
+    #[tokio::test]
+    async fn test_middleware_wraps_calls() {
+        use std::sync::{Arc, Mutex};
+        
+        /// Records calls and blocks the "forbidden" tool
+        struct Recorder(Arc<Mutex<Vec<String>>>);
+        
+        impl ToolMiddleware for Recorder {
+            fn before(&self, name: &str, _params: &Value) -> Result<()> {
+                self.0.lock().unwrap().push(format!("before {}", name));
+                if name == "forbidden" {
+                    return Err(Error::Tool("blocked".to_string()));
+                }
+                Ok(())
+            }
+            
+            fn after(&self, name: &str, result: &Result<String>, _elapsed: std::time::Duration) {
+                let outcome = result.as_deref().unwrap_or("error");
+                self.0.lock().unwrap().push(format!("after {} = {}", name, outcome));
+            }
+        }
+        
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let mut runner = ToolRunner::new();
+        runner.add_middleware(Recorder(calls.clone()));
+        for name in ["allowed", "forbidden"] {
+            runner.register(DummyTool { name: name.to_string(), result: "ok".to_string() });
+        }
+        
+        assert_eq!(runner.execute("allowed", serde_json::json!({})).await.unwrap(), "ok");
+        let err = runner.execute("forbidden", serde_json::json!({})).await.unwrap_err();
+        assert!(err.to_string().contains("blocked"));
+        
+        assert_eq!(*calls.lock().unwrap(), vec![
+            "before allowed", "after allowed = ok", "before forbidden",
+        ]);
+    }
+    
     #[tokio::test]
     async fn test_tool_runner_unknown_tool() {
         let runner = ToolRunner::new();
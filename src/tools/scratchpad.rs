@@ -0,0 +1,138 @@
+//! Scratchpad tool - a working plan that lasts for one agent run
+
+use std::sync::{Arc, Mutex};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use crate::Result;
+use crate::error::Error;
+use super::Tool;
+
+/// Most characters the scratchpad holds
+const MAX_SCRATCHPAD_CHARS: usize = 4000;
+
+/// Notes shared between the scratchpad tool and the agent's context
+///
+/// Cleared at the start of every run and never written to disk, unlike `task.md`.
+#[derive(Clone, Default)]
+pub struct Scratchpad(Arc<Mutex<String>>);
+
+impl Scratchpad {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Current contents
+    pub fn read(&self) -> String {
+        self.0.lock().unwrap().clone()
+    }
+
+    /// Empty the scratchpad
+    pub fn clear(&self) {
+        self.0.lock().unwrap().clear();
+    }
+
+    /// Replace the contents, or append a line, keeping within the size limit
+    fn set(&self, text: &str, append: bool) -> Result<usize> {
+        let mut pad = self.0.lock().unwrap();
+        let next = match (append, pad.is_empty()) {
+            (true, false) => format!("{}\n{}", pad, text),
+            _ => text.to_string(),
+        };
+        let chars = next.chars().count();
+        if chars > MAX_SCRATCHPAD_CHARS {
+            return Err(Error::Tool(format!(
+                "Scratchpad would hold {} chars, over the {} limit. Rewrite it more briefly",
+                chars, MAX_SCRATCHPAD_CHARS
+            )));
+        }
+        *pad = next;
+        Ok(chars)
+    }
+}
+
+/// Read and write the run's scratchpad
+pub struct ScratchpadTool {
+    pad: Scratchpad,
+}
+
+impl ScratchpadTool {
+    pub fn new(pad: Scratchpad) -> Self {
+        Self { pad }
+    }
+}
+
+#[async_trait]
+impl Tool for ScratchpadTool {
+    fn name(&self) -> &str { "scratchpad" }
+    fn description(&self) -> &str {
+        "Keep a working plan for the current request (steps, findings, what's left). \
+         It is shown to you on every step and discarded when you answer; use task/memory for anything lasting"
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["read", "write", "append", "clear"],
+                    "description": "'write' replaces the contents, 'append' adds a line"
+                },
+                "content": {
+                    "type": "string",
+                    "description": "Text for write/append"
+                }
+            },
+            "required": ["action"]
+        })
+    }
+
+    async fn execute(&self, params: Value) -> Result<String> {
+        let action = params.get("action")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| Error::Tool("Missing 'action' parameter".to_string()))?;
+
+        match action {
+            "read" => {
+                let pad = self.pad.read();
+                Ok(if pad.is_empty() { "Scratchpad is empty".to_string() } else { pad })
+            }
+            "write" | "append" => {
+                let content = params.get("content")
+                    .and_then(|v| v.as_str())
+                    .ok_or_else(|| Error::Tool("Missing 'content' parameter".to_string()))?;
+                let chars = self.pad.set(content, action == "append")?;
+                Ok(format!("Scratchpad updated ({} chars)", chars))
+            }
+            "clear" => {
+                self.pad.clear();
+                Ok("Scratchpad cleared".to_string())
+            }
+            other => Err(Error::Tool(format!(
+                "Unknown action: {}. Use read, write, append or clear", other
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_scratchpad_actions() {
+        let pad = Scratchpad::new();
+        let tool = ScratchpadTool::new(pad.clone());
+
+        tool.execute(json!({ "action": "write", "content": "1. Read config" })).await.unwrap();
+        tool.execute(json!({ "action": "append", "content": "2. Fix parser" })).await.unwrap();
+        assert_eq!(pad.read(), "1. Read config\n2. Fix parser");
+
+        let too_long = "x".repeat(MAX_SCRATCHPAD_CHARS);
+        assert!(tool.execute(json!({ "action": "append", "content": too_long })).await.is_err());
+        assert_eq!(pad.read(), "1. Read config\n2. Fix parser");
+
+        tool.execute(json!({ "action": "clear" })).await.unwrap();
+        assert_eq!(tool.execute(json!({ "action": "read" })).await.unwrap(), "Scratchpad is empty");
+    }
+}
@@ -0,0 +1,120 @@
+//! Screenshot tool - capture a URL with headless Chrome
+//!
+//! Works without the Leo Link extension. Only built with the `headless`
+//! feature and only registered when a Chrome/Chromium binary is found.
+
+use std::path::PathBuf;
+use std::time::Duration;
+use async_trait::async_trait;
+use chromiumoxide::browser::{Browser, BrowserConfig};
+use chromiumoxide::page::ScreenshotParams;
+use futures_util::StreamExt;
+use serde_json::{json, Value};
+use crate::Result;
+use crate::error::{Error, with_timeout};
+use super::Tool;
+use super::browser_bridge::moment_dir;
+
+/// Time allowed to launch the browser, load the page and capture it
+const SCREENSHOT_TIMEOUT: Duration = Duration::from_secs(45);
+
+/// Take a PNG screenshot of a URL in headless Chrome
+pub struct ScreenshotTool {
+    moments_dir: PathBuf,
+}
+
+impl ScreenshotTool {
+    /// Save captures under `moments_dir` (`Config::moments_path`), like the browser bridge
+    pub fn new(moments_dir: PathBuf) -> Self {
+        Self { moments_dir }
+    }
+
+    /// True when a Chrome/Chromium executable can be found
+    pub fn is_available() -> bool {
+        chromiumoxide::detection::default_executable(Default::default()).is_ok()
+    }
+
+    async fn capture(url: &str, full_page: bool, path: &std::path::Path) -> Result<()> {
+        let config = BrowserConfig::builder()
+            .window_size(1280, 800)
+            .build()
+            .map_err(|e| Error::Tool(format!("Failed to configure Chrome: {}", e)))?;
+
+        let (mut browser, mut handler) = Browser::launch(config).await
+            .map_err(|e| Error::Tool(format!("Failed to launch Chrome: {}", e)))?;
+        let events = tokio::spawn(async move {
+            while let Some(event) = handler.next().await {
+                if event.is_err() {
+                    break;
+                }
+            }
+        });
+
+        // The timeout covers only the page work, so Chrome is shut down on every path
+        let work = async {
+            let page = browser.new_page(url).await
+                .map_err(|e| Error::Tool(format!("Failed to open {}: {}", url, e)))?;
+            page.wait_for_navigation().await
+                .map_err(|e| Error::Tool(format!("Failed to load {}: {}", url, e)))?;
+            let params = ScreenshotParams::builder().full_page(full_page).build();
+            page.save_screenshot(params, path).await
+                .map_err(|e| Error::Tool(format!("Failed to capture {}: {}", url, e)))?;
+            Ok(())
+        };
+        let result = with_timeout(SCREENSHOT_TIMEOUT, &format!("capturing {}", url), work).await;
+
+        if browser.close().await.is_err() {
+            let _ = browser.kill().await;
+        }
+        let _ = browser.wait().await;
+        events.abort();
+        result
+    }
+}
+
+#[async_trait]
+impl Tool for ScreenshotTool {
+    fn name(&self) -> &str { "screenshot" }
+    fn description(&self) -> &str {
+        "Take a PNG screenshot of a URL using headless Chrome (no extension needed). Returns the saved path"
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "url": {
+                    "type": "string",
+                    "description": "http(s) URL to capture"
+                },
+                "full_page": {
+                    "type": "boolean",
+                    "description": "Capture the whole scrollable page instead of the viewport (default: false)"
+                }
+            },
+            "required": ["url"]
+        })
+    }
+
+    async fn execute(&self, params: Value) -> Result<String> {
+        let url = params.get("url")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| Error::Tool("Missing 'url' parameter".to_string()))?;
+
+        let full_page = params.get("full_page")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
+        if !url.starts_with("http://") && !url.starts_with("https://") {
+            return Err(Error::Tool(format!("Only http(s) URLs can be captured: {}", url)));
+        }
+
+        let dir = moment_dir(&self.moments_dir, "screenshot");
+        std::fs::create_dir_all(&dir)?;
+        let path = dir.join("screenshot.png");
+
+        Self::capture(url, full_page, &path).await?;
+
+        Ok(format!("Saved screenshot of {} to {}", url, path.display()))
+    }
+}
@@ -6,7 +6,10 @@ use serde_json::{json, Value};
 use regex::Regex;
 use crate::Result;
 use crate::error::Error;
-use super::Tool;
+use super::{Tool, Page, resolve_path};
+
+/// Matches shown per page by default
+const DEFAULT_SEARCH_LIMIT: usize = 100;
 
 /// Search for text in files
 pub struct SearchTool {
@@ -58,6 +61,7 @@ impl SearchTool {
 impl Tool for SearchTool {
     fn name(&self) -> &str { "search" }
     fn description(&self) -> &str { "Search for text in files (supports regex or literal text, case-sensitive or insensitive)" }
+    fn cacheable(&self) -> bool { true }
 
     fn parameters(&self) -> Value {
         json!({
@@ -78,6 +82,14 @@ impl Tool for SearchTool {
                 "literal": {
                     "type": "boolean",
                     "description": "Treat query as literal text, not regex (default: false)"
+                },
+                "offset": {
+                    "type": "integer",
+                    "description": "Skip this many matches (for paging)"
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": "Most matches to show (default 100)"
                 }
             },
             "required": ["query"]
@@ -90,8 +102,7 @@ impl Tool for SearchTool {
             .ok_or_else(|| Error::Tool("Missing 'query' parameter".to_string()))?;
 
         let sub_path = params.get("path")
-            .and_then(|v| v.as_str())
-            .map(PathBuf::from);
+            .and_then(|v| v.as_str());
         
         let case_insensitive = params.get("case_insensitive")
             .and_then(|v| v.as_bool())
@@ -102,7 +113,7 @@ impl Tool for SearchTool {
             .unwrap_or(false);
 
         let search_path = if let Some(p) = sub_path {
-            self.workspace.join(p)
+            resolve_path(&self.workspace, p)
         } else {
             self.workspace.clone()
         };
@@ -134,18 +145,8 @@ impl Tool for SearchTool {
         if results.is_empty() {
             Ok("No matches found.".to_string())
         } else {
-            // Cap results to avoid blowing up context
-            if results.len() > 100 {
-                let displayed = results.len().min(100);
-                Ok(format!(
-                    "Found {} matches (showing first {}):\n\n{}", 
-                    results.len(), 
-                    displayed,
-                    results[..displayed].join("\n")
-                ))
-            } else {
-                Ok(results.join("\n"))
-            }
+            // Page results to avoid blowing up context
+            Ok(Page::from_params(&params, DEFAULT_SEARCH_LIMIT).apply(&results, "matches"))
         }
     }
 }
@@ -6,7 +6,7 @@ use serde_json::{json, Value};
 use tokio::process::Command;
 use crate::Result;
 use crate::error::Error;
-use super::Tool;
+use super::{DangerLevel, Tool};
 
 /// Execute shell commands
 pub struct ExecTool {
@@ -22,8 +22,14 @@ impl ExecTool {
 #[async_trait]
 impl Tool for ExecTool {
     fn name(&self) -> &str { "exec" }
-    fn description(&self) -> &str { "Execute a shell command in the workspace" }
+    fn description(&self) -> &str {
+        "Execute a shell command in the workspace. Returns JSON with exit_code, stdout and stderr"
+    }
     
+    fn is_mutating(&self, _params: &Value) -> bool { true }
+
+    fn danger_level(&self) -> DangerLevel { DangerLevel::Destructive }
+
     fn parameters(&self) -> Value {
         json!({
             "type": "object",
@@ -35,6 +41,14 @@ impl Tool for ExecTool {
                 "working_dir": {
                     "type": "string",
                     "description": "Working directory (optional, defaults to workspace)"
+                },
+                "env": {
+                    "type": "object",
+                    "description": "Extra environment variables, e.g. {\"NODE_ENV\": \"test\"} (optional)"
+                },
+                "clear_env": {
+                    "type": "boolean",
+                    "description": "Start from an empty environment instead of inheriting Leo's (default: false)"
                 }
             },
             "required": ["command"]
@@ -51,31 +65,55 @@ impl Tool for ExecTool {
             .map(PathBuf::from)
             .unwrap_or_else(|| self.workspace.clone());
         
-        let output = Command::new("sh")
-            .arg("-c")
-            .arg(command)
-            .current_dir(&working_dir)
-            .output()
-            .await
-            .map_err(|e| Error::Tool(format!("Failed to execute command: {}", e)))?;
+        let clear_env = params.get("clear_env")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg(command).current_dir(&working_dir);
         
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let stderr = String::from_utf8_lossy(&output.stderr);
+        if clear_env {
+            cmd.env_clear();
+        }
         
-        if output.status.success() {
-            if stderr.is_empty() {
-                Ok(stdout.to_string())
-            } else {
-                Ok(format!("{}\n\n[stderr]\n{}", stdout, stderr))
+        if let Some(env) = params.get("env") {
+            let env = env.as_object()
+                .ok_or_else(|| Error::Tool("'env' must be an object of NAME: value pairs".to_string()))?;
+            for (key, value) in env {
+                let value = match value {
+                    Value::String(s) => s.clone(),
+                    Value::Number(_) | Value::Bool(_) => value.to_string(),
+                    _ => return Err(Error::Tool(format!("Environment variable '{}' must be a string", key))),
+                };
+                cmd.env(key, value);
             }
-        } else {
-            Err(Error::Tool(format!(
-                "Command failed with exit code {}\nstdout: {}\nstderr: {}",
-                output.status.code().unwrap_or(-1),
-                stdout,
-                stderr
-            )))
         }
+        
+        run_command(&mut cmd).await
+    }
+}
+
+/// Run `cmd` and report its exit code, stdout and stderr as JSON, failing on a
+/// non-zero exit
+pub(crate) async fn run_command(cmd: &mut Command) -> Result<String> {
+    let output = cmd
+        .output()
+        .await
+        .map_err(|e| Error::Tool(format!("Failed to execute command: {}", e)))?;
+    
+    // Killed by a signal has no exit code
+    let exit_code = output.status.code().unwrap_or(-1);
+    let result = json!({
+        "exit_code": exit_code,
+        "stdout": String::from_utf8_lossy(&output.stdout),
+        "stderr": String::from_utf8_lossy(&output.stderr),
+    });
+    let result = serde_json::to_string_pretty(&result)?;
+    
+    if output.status.success() {
+        Ok(result)
+    } else {
+        Err(Error::Tool(format!("Command failed with exit code {}\n{}", exit_code, result)))
     }
 }
 
@@ -107,4 +145,42 @@ mod tests {
         
         assert!(result.is_err());
     }
+    
+    #[tokio::test]
+    async fn test_exec_reports_exit_code() {
+        let tmp = TempDir::new().unwrap();
+        let exec = ExecTool::new(tmp.path().to_path_buf());
+        
+        let err = exec.execute(json!({
+            "command": "echo out; echo oops >&2; exit 3"
+        })).await.unwrap_err().to_string();
+        
+        assert!(err.contains("Command failed with exit code 3"));
+        let (_, body) = err.split_once('\n').unwrap();
+        let result: Value = serde_json::from_str(body).unwrap();
+        assert_eq!(result["exit_code"], 3);
+        assert_eq!(result["stdout"], "out\n");
+        assert_eq!(result["stderr"], "oops\n");
+    }
+    
+    #[tokio::test]
+    async fn test_exec_env() {
+        let tmp = TempDir::new().unwrap();
+        let exec = ExecTool::new(tmp.path().to_path_buf());
+        
+        let result = exec.execute(json!({
+            "command": "echo \"$LEO_TEST_VAR-$HOME\"",
+            "env": {"LEO_TEST_VAR": "hello"}
+        })).await.unwrap();
+        let result: Value = serde_json::from_str(&result).unwrap();
+        assert!(result["stdout"].as_str().unwrap().starts_with("hello-/"));
+        
+        let result = exec.execute(json!({
+            "command": "echo \"$LEO_TEST_VAR-$HOME\"",
+            "env": {"LEO_TEST_VAR": "clean"},
+            "clear_env": true
+        })).await.unwrap();
+        let result: Value = serde_json::from_str(&result).unwrap();
+        assert_eq!(result["stdout"], "clean-\n");
+    }
 }
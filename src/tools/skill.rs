@@ -0,0 +1,159 @@
+//! Skill tool - load a skill's instructions or run the scripts it bundles
+//!
+//! `load` returns a skill's content with its `{{parameters}}` filled in from
+//! `arguments`. A skill lists its scripts in the SKILL.md frontmatter
+//! (`scripts: [setup.sh, run.sh]`). Only listed scripts that exist in the
+//! skill's directory can be run; they run with `sh` from that directory, and like
+//! `exec` the runner's dry-run and approval checks apply to them.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use tokio::process::Command;
+use crate::Result;
+use crate::error::Error;
+use crate::skills::Skill;
+use super::shell::run_command;
+use super::{DangerLevel, Tool};
+
+/// Load or run the scripts of a skill from the workspace
+pub struct SkillTool {
+    skills: HashMap<String, Skill>,
+}
+
+impl SkillTool {
+    pub fn new<'a>(skills: impl IntoIterator<Item = &'a Skill>) -> Self {
+        let skills = skills.into_iter().map(|s| (s.name.clone(), s.clone())).collect();
+        Self { skills }
+    }
+
+    fn skill(&self, name: &str) -> Result<&Skill> {
+        self.skills.get(name).ok_or_else(|| Error::Tool(format!("Unknown skill: {}", name)))
+    }
+
+    /// The skill's directory and the absolute path of `script` in it
+    fn script_path(&self, skill: &str, script: &str) -> Result<(PathBuf, PathBuf)> {
+        let skill = self.skill(skill)?;
+        let (dir, scripts) = (&skill.path, &skill.scripts);
+        if !scripts.iter().any(|s| s == script) {
+            return Err(Error::Tool(format!(
+                "Skill '{}' doesn't declare '{}' (scripts: {})", skill.name, script, scripts.join(", ")
+            )));
+        }
+        // Declared names are plain file names inside the skill directory
+        let valid = !script.is_empty()
+            && !script.starts_with(['-', '.'])
+            && !script.contains(['/', '\\']);
+        let path = std::path::absolute(dir.join(script))?;
+        if !valid || !path.is_file() {
+            return Err(Error::Tool(format!("Script '{}' not found in {}", script, dir.display())));
+        }
+        Ok((dir.clone(), path))
+    }
+}
+
+#[async_trait]
+impl Tool for SkillTool {
+    fn name(&self) -> &str { "skill" }
+    fn description(&self) -> &str {
+        "Use a skill: 'load' returns its instructions with 'arguments' filled into its parameters; \
+         'run' runs a script the skill lists under 'scripts', from the skill's directory"
+    }
+
+    fn is_mutating(&self, params: &Value) -> bool {
+        params.get("action").and_then(|v| v.as_str()) == Some("run")
+    }
+
+    // Most calls just load instructions; 'run' still goes through approval as a mutating call
+    fn danger_level(&self) -> DangerLevel { DangerLevel::Caution }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["load", "run"],
+                    "description": "Action to perform"
+                },
+                "skill": {
+                    "type": "string",
+                    "description": "Skill name"
+                },
+                "arguments": {
+                    "type": "object",
+                    "description": "Values for the skill's parameters, e.g. {\"recipient\": \"Sam\"} (for load)"
+                },
+                "script": {
+                    "type": "string",
+                    "description": "Script file name, e.g. setup.sh (for run)"
+                }
+            },
+            "required": ["action", "skill"]
+        })
+    }
+
+    async fn execute(&self, params: Value) -> Result<String> {
+        let get = |key: &str| params.get(key)
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| Error::Tool(format!("Missing '{}' parameter", key)));
+
+        match get("action")? {
+            "load" => {
+                let empty = serde_json::Map::new();
+                let args = match params.get("arguments") {
+                    None | Some(Value::Null) => &empty,
+                    Some(Value::Object(map)) => map,
+                    Some(_) => return Err(Error::Tool("'arguments' must be an object".to_string())),
+                };
+                self.skill(get("skill")?)?.render(args).map_err(Error::Tool)
+            }
+            "run" => {
+                let (dir, path) = self.script_path(get("skill")?, get("script")?)?;
+                run_command(Command::new("sh").arg(path).current_dir(dir)).await
+            }
+            other => Err(Error::Tool(format!("Unknown action: {}", other))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_load_and_run() {
+        let tmp = TempDir::new().unwrap();
+        let dir = tmp.path().join("greeter");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("run.sh"), "echo \"hi from $(basename \"$PWD\")\"").unwrap();
+        std::fs::write(dir.join("other.sh"), "echo nope").unwrap();
+        std::fs::write(dir.join("-c"), "echo nope").unwrap();
+        let skill = Skill {
+            name: "greeter".to_string(),
+            description: String::new(),
+            requires: vec![],
+            scripts: vec!["run.sh".to_string(), "setup.sh".to_string(), "-c".to_string()],
+            parameters: vec!["name".to_string()],
+            content: "Greet {{name}}".to_string(),
+            path: dir.clone(),
+        };
+        let tool = SkillTool::new([&skill]);
+
+        let load = json!({ "action": "load", "skill": "greeter", "arguments": { "name": "Ada" } });
+        assert!(!tool.is_mutating(&load));
+        assert_eq!(tool.execute(load).await.unwrap(), "Greet Ada");
+
+        let run = |script: &str| tool.execute(json!({ "action": "run", "skill": "greeter", "script": script }));
+        let result: Value = serde_json::from_str(&run("run.sh").await.unwrap()).unwrap();
+        assert_eq!(result["stdout"], "hi from greeter\n");
+
+        assert!(run("other.sh").await.unwrap_err().to_string().contains("doesn't declare"));
+        assert!(run("setup.sh").await.unwrap_err().to_string().contains("not found"));
+        assert!(run("-c").await.unwrap_err().to_string().contains("not found"));
+        let err = tool.execute(json!({ "action": "run", "skill": "missing", "script": "run.sh" })).await;
+        assert!(err.unwrap_err().to_string().contains("Unknown skill"));
+    }
+}
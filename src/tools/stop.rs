@@ -0,0 +1,64 @@
+//! Stop tool - lets the model end a run with its final answer
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use crate::Result;
+use crate::error::Error;
+use super::Tool;
+
+/// Name the agent loop watches for to end a run early
+pub const STOP_TOOL_NAME: &str = "done";
+
+/// The final answer passed to a `done` call, if it has one
+pub fn stop_message(params: &Value) -> Option<&str> {
+    params.get("message")
+        .and_then(|v| v.as_str())
+        .map(str::trim)
+        .filter(|m| !m.is_empty())
+}
+
+/// Finish the current request with a final message
+///
+/// `AgentLoop` intercepts calls to this tool and returns the message right away,
+/// skipping any remaining iterations; `execute` only runs outside the loop.
+pub struct StopTool;
+
+#[async_trait]
+impl Tool for StopTool {
+    fn name(&self) -> &str { STOP_TOOL_NAME }
+    fn description(&self) -> &str {
+        "Finish the request and reply to the user with 'message'. \
+         Call this once the task is complete instead of making more tool calls"
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "message": {
+                    "type": "string",
+                    "description": "Your final answer to the user"
+                }
+            },
+            "required": ["message"]
+        })
+    }
+
+    async fn execute(&self, params: Value) -> Result<String> {
+        stop_message(&params)
+            .map(String::from)
+            .ok_or_else(|| Error::Tool("Missing 'message' parameter".to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_stop_tool_returns_message() {
+        assert_eq!(StopTool.execute(json!({ "message": " All set. " })).await.unwrap(), "All set.");
+        assert!(StopTool.execute(json!({ "message": "" })).await.is_err());
+        assert_eq!(stop_message(&json!({})), None);
+    }
+}
@@ -0,0 +1,87 @@
+//! Summarize tool - condense long text with the active LLM
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use crate::Result;
+use crate::error::Error;
+use crate::agent::{LlmClient, Message};
+use super::Tool;
+
+/// Instructions given to the LLM for every summary
+pub(crate) const SUMMARY_PROMPT: &str = "You condense conversation excerpts into compact notes. \
+Keep decisions, facts, names, file paths, numbers and open questions. \
+Drop greetings, repetition and reasoning that led nowhere. \
+Reply with terse bullet points only.";
+
+/// Condense older conversation into a short note using the active LLM client
+pub struct SummarizeTool<C: LlmClient> {
+    client: C,
+}
+
+impl<C: LlmClient> SummarizeTool<C> {
+    pub fn new(client: C) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl<C: LlmClient + 'static> Tool for SummarizeTool<C> {
+    fn name(&self) -> &str { "summarize" }
+    fn description(&self) -> &str {
+        "Condense text (e.g. the older part of this conversation) into a compact note. Save the result with the memory tool to keep it"
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "text": {
+                    "type": "string",
+                    "description": "Text to summarize"
+                },
+                "focus": {
+                    "type": "string",
+                    "description": "What the summary should preserve (optional)"
+                }
+            },
+            "required": ["text"]
+        })
+    }
+
+    async fn execute(&self, params: Value) -> Result<String> {
+        let text = params.get("text")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| Error::Tool("Missing 'text' parameter".to_string()))?;
+
+        if text.trim().is_empty() {
+            return Err(Error::Tool("Nothing to summarize".to_string()));
+        }
+
+        let mut request = format!("Summarize this:\n\n{}", text);
+        if let Some(focus) = params.get("focus").and_then(|v| v.as_str()) {
+            request.push_str(&format!("\n\nFocus on: {}", focus));
+        }
+
+        let messages = [Message::system(SUMMARY_PROMPT), Message::user(request)];
+        let response = self.client.chat(&messages, &[]).await?;
+
+        response.content
+            .filter(|c| !c.trim().is_empty())
+            .ok_or_else(|| Error::Tool("Summary came back empty".to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::llm::FakeLlmClient;
+
+    #[tokio::test]
+    async fn test_summarize_uses_client() {
+        let tool = SummarizeTool::new(FakeLlmClient::new(vec!["- Chose Postgres"]));
+        let result = tool.execute(json!({"text": "long discussion about databases"})).await.unwrap();
+        assert_eq!(result, "- Chose Postgres");
+
+        assert!(tool.execute(json!({"text": "  "})).await.is_err());
+    }
+}
@@ -0,0 +1,213 @@
+//! Tail tool - read the end of a file and optionally follow new output
+
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use crate::Result;
+use crate::error::Error;
+use super::{Tool, resolve_path};
+
+/// Maximum bytes returned, so a fast-growing log can't flood the context
+const MAX_OUTPUT_BYTES: usize = 16 * 1024;
+
+/// Bytes read per step when scanning backwards from the end of the file
+const READ_BLOCK: u64 = 8 * 1024;
+
+/// Upper bound for the follow window
+const MAX_FOLLOW_MS: u64 = 10_000;
+
+/// Show the last lines of a file, optionally waiting for new output
+pub struct TailTool {
+    workspace: PathBuf,
+}
+
+impl TailTool {
+    pub fn new(workspace: PathBuf) -> Self {
+        Self { workspace }
+    }
+
+    /// Return the last `n` lines of `content`
+    fn last_lines(content: &str, n: usize) -> String {
+        let lines: Vec<&str> = content.lines().collect();
+        let start = lines.len().saturating_sub(n);
+        lines[start..].join("\n")
+    }
+
+    /// Read the last `n` lines by scanning backwards in blocks, so only the end
+    /// of a large log is read. Also returns the file length, for following.
+    fn read_last_lines(path: &Path, n: usize) -> std::io::Result<(String, u64)> {
+        let mut file = std::fs::File::open(path)?;
+        let len = file.metadata()?.len();
+        let mut start = len;
+        let mut buf: Vec<u8> = Vec::new();
+        // One more newline than lines wanted, as the file usually ends with one;
+        // past the output cap nothing more would be shown anyway
+        while start > 0 && buf.iter().filter(|&&b| b == b'\n').count() <= n && buf.len() <= MAX_OUTPUT_BYTES {
+            let size = READ_BLOCK.min(start);
+            start -= size;
+            let mut block = vec![0; size as usize];
+            file.seek(SeekFrom::Start(start))?;
+            file.read_exact(&mut block)?;
+            block.extend_from_slice(&buf);
+            buf = block;
+        }
+        Ok((Self::last_lines(&String::from_utf8_lossy(&buf), n), len))
+    }
+
+    /// Read everything appended to the file after `offset`
+    fn read_from(path: &Path, offset: u64) -> std::io::Result<String> {
+        let mut file = std::fs::File::open(path)?;
+        let len = file.metadata()?.len();
+        if len < offset {
+            // File was truncated or rotated - read it from the start
+            file.seek(SeekFrom::Start(0))?;
+        } else {
+            file.seek(SeekFrom::Start(offset))?;
+        }
+        let mut buf = Vec::new();
+        file.read_to_end(&mut buf)?;
+        Ok(String::from_utf8_lossy(&buf).to_string())
+    }
+}
+
+/// Keep only the last `max` bytes of `text`, respecting UTF-8 boundaries
+fn cap_tail(text: &str, max: usize) -> String {
+    if text.len() <= max {
+        return text.to_string();
+    }
+    let mut start = text.len() - max;
+    while !text.is_char_boundary(start) {
+        start += 1;
+    }
+    format!("[Truncated - showing last {} of {} bytes]\n{}", text.len() - start, text.len(), &text[start..])
+}
+
+#[async_trait]
+impl Tool for TailTool {
+    fn name(&self) -> &str { "tail" }
+    fn description(&self) -> &str {
+        "Show the last lines of a file (e.g. a log), optionally following it briefly for new output"
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "Path to the file (relative paths resolve against the workspace)"
+                },
+                "lines": {
+                    "type": "integer",
+                    "description": "Number of lines to return (default: 20)"
+                },
+                "follow_ms": {
+                    "type": "integer",
+                    "description": "Keep watching for new output for this many milliseconds (optional, max 10000)"
+                }
+            },
+            "required": ["path"]
+        })
+    }
+
+    async fn execute(&self, params: Value) -> Result<String> {
+        let path = params.get("path")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| Error::Tool("Missing 'path' parameter".to_string()))?;
+
+        let lines = params.get("lines")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(20) as usize;
+
+        let follow_ms = params.get("follow_ms")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0)
+            .min(MAX_FOLLOW_MS);
+
+        let file_path = resolve_path(&self.workspace, path);
+        if !file_path.is_file() {
+            return Err(Error::Tool(format!("File not found: {}", path)));
+        }
+
+        let (mut output, offset) = Self::read_last_lines(&file_path, lines)
+            .map_err(|e| Error::Tool(format!("Failed to read {}: {}", path, e)))?;
+
+        if follow_ms > 0 {
+            tokio::time::sleep(std::time::Duration::from_millis(follow_ms)).await;
+
+            let appended = Self::read_from(&file_path, offset)
+                .map_err(|e| Error::Tool(format!("Failed to follow {}: {}", path, e)))?;
+            if appended.is_empty() {
+                output.push_str(&format!("\n\n[No new output after {} ms]", follow_ms));
+            } else {
+                output.push_str(&format!("\n\n[New output after {} ms]\n{}", follow_ms, appended.trim_end()));
+            }
+        }
+
+        Ok(cap_tail(&output, MAX_OUTPUT_BYTES))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_tail_last_lines() {
+        let tmp = TempDir::new().unwrap();
+        let content: Vec<String> = (1..=50).map(|i| format!("line {}", i)).collect();
+        std::fs::write(tmp.path().join("app.log"), content.join("\n")).unwrap();
+
+        let tail = TailTool::new(tmp.path().to_path_buf());
+        let result = tail.execute(json!({"path": "app.log", "lines": 3})).await.unwrap();
+
+        assert_eq!(result, "line 48\nline 49\nline 50");
+    }
+
+    #[test]
+    fn test_read_last_lines_across_blocks() {
+        let tmp = TempDir::new().unwrap();
+        let log = tmp.path().join("big.log");
+        let content: Vec<String> = (1..=5000).map(|i| format!("entry {:05}", i)).collect();
+        std::fs::write(&log, content.join("\n") + "\n").unwrap();
+
+        let (tail, len) = TailTool::read_last_lines(&log, 1000).unwrap();
+        assert_eq!(len, std::fs::metadata(&log).unwrap().len());
+        assert_eq!(tail, content[4000..].join("\n"));
+
+        let (tail, _) = TailTool::read_last_lines(&log, 1).unwrap();
+        assert_eq!(tail, "entry 05000");
+    }
+
+    #[tokio::test]
+    async fn test_tail_follow_captures_new_output() {
+        let tmp = TempDir::new().unwrap();
+        let log = tmp.path().join("app.log");
+        std::fs::write(&log, "start\n").unwrap();
+
+        let writer_path = log.clone();
+        let writer = tokio::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+            use std::io::Write;
+            let mut f = std::fs::OpenOptions::new().append(true).open(writer_path).unwrap();
+            writeln!(f, "appended").unwrap();
+        });
+
+        let tail = TailTool::new(tmp.path().to_path_buf());
+        let result = tail.execute(json!({"path": "app.log", "follow_ms": 500})).await.unwrap();
+        writer.await.unwrap();
+
+        assert!(result.contains("start"));
+        assert!(result.contains("appended"));
+    }
+
+    #[test]
+    fn test_cap_tail() {
+        let text = "a".repeat(100);
+        let capped = cap_tail(&text, 10);
+        assert!(capped.starts_with("[Truncated"));
+        assert!(capped.ends_with(&"a".repeat(10)));
+    }
+}
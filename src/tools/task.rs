@@ -25,6 +25,10 @@ impl Tool for TaskTool {
     fn name(&self) -> &str { "task" }
     fn description(&self) -> &str { "Read or Update the task list" }
 
+    fn is_mutating(&self, params: &Value) -> bool {
+        params.get("action").and_then(|v| v.as_str()) == Some("update")
+    }
+
     fn parameters(&self) -> Value {
         json!({
             "type": "object",
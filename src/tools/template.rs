@@ -0,0 +1,124 @@
+//! Template tool - render `{{var}}` templates into files
+
+use std::path::PathBuf;
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use crate::Result;
+use crate::error::Error;
+use crate::templates::render;
+use super::{DangerLevel, Tool, resolve_path};
+
+/// Render a template with variables, optionally writing the result to a file
+pub struct TemplateTool {
+    workspace: PathBuf,
+}
+
+impl TemplateTool {
+    pub fn new(workspace: PathBuf) -> Self {
+        Self { workspace }
+    }
+}
+
+#[async_trait]
+impl Tool for TemplateTool {
+    fn name(&self) -> &str { "template" }
+    fn description(&self) -> &str {
+        "Render a template with {{variable}} placeholders and optionally write it to a file. Returns the rendered text"
+    }
+
+    fn is_mutating(&self, params: &Value) -> bool {
+        params.get("path").is_some_and(|v| v.is_string())
+    }
+
+    fn danger_level(&self) -> DangerLevel { DangerLevel::Caution }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "template": {
+                    "type": "string",
+                    "description": "Template text with {{name}} placeholders"
+                },
+                "template_path": {
+                    "type": "string",
+                    "description": "Read the template from this file instead of 'template'"
+                },
+                "variables": {
+                    "type": "object",
+                    "description": "Values for the placeholders, e.g. {\"name\": \"Leo\"}"
+                },
+                "path": {
+                    "type": "string",
+                    "description": "Write the rendered text to this file (optional)"
+                }
+            }
+        })
+    }
+
+    async fn execute(&self, params: Value) -> Result<String> {
+        let template = match (
+            params.get("template").and_then(|v| v.as_str()),
+            params.get("template_path").and_then(|v| v.as_str()),
+        ) {
+            (Some(text), _) => text.to_string(),
+            (None, Some(path)) => {
+                let path = resolve_path(&self.workspace, path);
+                tokio::fs::read_to_string(&path).await
+                    .map_err(|e| Error::Tool(format!("Failed to read template {}: {}", path.display(), e)))?
+            }
+            (None, None) => {
+                return Err(Error::Tool("Missing 'template' or 'template_path' parameter".to_string()));
+            }
+        };
+
+        let empty = serde_json::Map::new();
+        let variables = match params.get("variables") {
+            None | Some(Value::Null) => &empty,
+            Some(Value::Object(map)) => map,
+            Some(_) => return Err(Error::Tool("'variables' must be an object".to_string())),
+        };
+
+        let rendered = render(&template, variables).map_err(|missing| {
+            Error::Tool(format!("Missing template variables: {}", missing.join(", ")))
+        })?;
+
+        let Some(path) = params.get("path").and_then(|v| v.as_str()) else {
+            return Ok(rendered);
+        };
+
+        let target = resolve_path(&self.workspace, path);
+        if let Some(parent) = target.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::write(&target, &rendered).await?;
+        Ok(format!("Wrote {} bytes to {}\n\n{}", rendered.len(), target.display(), rendered))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_render_to_file() {
+        let tmp = TempDir::new().unwrap();
+        std::fs::write(tmp.path().join("readme.tpl"), "# {{ project }}\n\nBy {{author}}\n").unwrap();
+        let tool = TemplateTool::new(tmp.path().to_path_buf());
+
+        let result = tool.execute(json!({
+            "template_path": "readme.tpl",
+            "variables": { "project": "Leo", "author": "Hitesh" },
+            "path": "out/README.md"
+        })).await.unwrap();
+        assert!(result.ends_with("# Leo\n\nBy Hitesh\n"));
+        assert_eq!(
+            std::fs::read_to_string(tmp.path().join("out/README.md")).unwrap(),
+            "# Leo\n\nBy Hitesh\n"
+        );
+
+        let err = tool.execute(json!({ "template": "Hi {{name}}" })).await.unwrap_err();
+        assert!(err.to_string().contains("Missing template variables: name"));
+    }
+}
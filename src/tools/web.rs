@@ -1,21 +1,39 @@
 //! Web tools - search and fetch
 
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
 use crate::Result;
+use crate::config::{Config, SearchConfig};
 use crate::error::Error;
 use super::Tool;
 
 use super::browser_bridge::BrowserBridgeTool;
+use super::web_search::{format_results, provider_from_config, SearchProvider};
 
 /// Web search tool - can use browser bridge as a fallback/primary
 pub struct WebSearchTool {
     pub(crate) browser: Option<BrowserBridgeTool>,
+    /// Search API used before the browser, when a key is configured
+    provider: Option<Box<dyn SearchProvider>>,
+    max_results: usize,
 }
 
 impl WebSearchTool {
     pub fn new(browser: Option<BrowserBridgeTool>) -> Self {
-        Self { browser }
+        Self { browser, provider: None, max_results: SearchConfig::default().max_results }
+    }
+    
+    /// Search through the API configured in `config`, if it has a key
+    pub fn with_search_config(mut self, config: &SearchConfig) -> Self {
+        self.provider = provider_from_config(config);
+        self.max_results = config.max_results.max(1);
+        self
     }
 }
 
@@ -50,6 +68,19 @@ impl Tool for WebSearchTool {
             .and_then(|v| v.as_u64())
             .unwrap_or(10000) as usize;
 
+        // Why the search API wasn't used, told to the agent along with the browser result
+        let mut fallback_note = None;
+        if let Some(provider) = &self.provider {
+            match provider.search(query, self.max_results).await {
+                Ok(results) => return Ok(truncate(format_results(provider.name(), query, &results), max_len)),
+                Err(e) if self.browser.is_some() => {
+                    tracing::warn!("{} failed ({}), searching in the browser", provider.name(), e);
+                    fallback_note = Some(format!("{} search failed ({}); searched in the browser instead.", provider.name(), e));
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        
         if let Some(browser) = &self.browser {
             // Use the browser bridge to perform the search
             let result = browser.execute(json!({
@@ -57,31 +88,150 @@ impl Tool for WebSearchTool {
                 "query": query
             })).await?;
 
-            return Ok(if result.len() > max_len {
-                format!("{}...\n\n[Truncated - {} total chars]", &result[..max_len], result.len())
-            } else {
-                result
-            });
+            let result = match fallback_note {
+                Some(note) => format!("{}\n{}", note, result),
+                None => result,
+            };
+            return Ok(truncate(result, max_len));
         }
 
-        // Placeholder - in production, use a search API (Brave, Google, etc.)
         Ok(format!(
-            "Web search is not yet configured (and no browser connected).\n\
+            "Web search is not configured (no search API key and no browser connected).\n\
              Query: {}\n\n\
-             Please install the Leo Link extension to enable browser-based searching.",
+             Set web_search.brave_api_key or web_search.serpapi_api_key in the config, \
+             or install the Leo Link extension to search in the browser.",
             query
         ))
     }
 }
 
+/// Default time a fetched page is reused
+const DEFAULT_CACHE_TTL: Duration = Duration::from_secs(600);
+
+/// A cached page as stored on disk
+#[derive(Serialize, Deserialize)]
+struct CachedPage {
+    url: String,
+    fetched_at: u64,
+    text: String,
+}
+
+/// Cache of fetched page text keyed by URL, in memory and optionally on disk
+pub(crate) struct WebCache {
+    ttl: Duration,
+    dir: Option<PathBuf>,
+    entries: Mutex<HashMap<String, (SystemTime, String)>>,
+}
+
+impl WebCache {
+    pub(crate) fn new(ttl: Duration, dir: Option<PathBuf>) -> Self {
+        Self { ttl, dir, entries: Mutex::new(HashMap::new()) }
+    }
+    
+    pub(crate) fn from_config(config: &Config) -> Self {
+        let dir = config.web_cache_on_disk
+            .then(|| crate::config::config_dir().join("cache").join("web"));
+        Self::new(Duration::from_secs(config.web_cache_ttl_secs), dir)
+    }
+    
+    fn is_fresh(&self, fetched_at: SystemTime) -> bool {
+        fetched_at.elapsed().map(|age| age < self.ttl).unwrap_or(false)
+    }
+    
+    fn disk_path(&self, url: &str) -> Option<PathBuf> {
+        let digest = Sha256::digest(url.as_bytes());
+        let name: String = digest.iter().map(|b| format!("{:02x}", b)).collect();
+        self.dir.as_ref().map(|dir| dir.join(format!("{}.json", name)))
+    }
+    
+    fn get(&self, url: &str) -> Option<String> {
+        if self.ttl.is_zero() {
+            return None;
+        }
+        
+        if let Some((fetched_at, text)) = self.entries.lock().unwrap().get(url) {
+            if self.is_fresh(*fetched_at) {
+                return Some(text.clone());
+            }
+        }
+        
+        let content = std::fs::read_to_string(self.disk_path(url)?).ok()?;
+        let page: CachedPage = serde_json::from_str(&content).ok()?;
+        let fetched_at = UNIX_EPOCH + Duration::from_secs(page.fetched_at);
+        if page.url != url || !self.is_fresh(fetched_at) {
+            return None;
+        }
+        self.entries.lock().unwrap().insert(url.to_string(), (fetched_at, page.text.clone()));
+        Some(page.text)
+    }
+    
+    fn put(&self, url: &str, text: &str) {
+        if self.ttl.is_zero() {
+            return;
+        }
+        
+        let now = SystemTime::now();
+        self.entries.lock().unwrap().insert(url.to_string(), (now, text.to_string()));
+        
+        if let Some(path) = self.disk_path(url) {
+            let page = CachedPage {
+                url: url.to_string(),
+                fetched_at: now.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+                text: text.to_string(),
+            };
+            let written = path.parent()
+                .map(std::fs::create_dir_all)
+                .transpose()
+                .and_then(|_| std::fs::write(&path, serde_json::to_string(&page)?));
+            if let Err(e) = written {
+                tracing::debug!("Failed to write web cache entry {:?}: {}", path, e);
+            }
+        }
+    }
+}
+
 /// Fetch web page content
 pub struct WebFetchTool {
     pub(crate) browser: Option<BrowserBridgeTool>,
+    cache: WebCache,
 }
 
 impl WebFetchTool {
     pub fn new(browser: Option<BrowserBridgeTool>) -> Self {
-        Self { browser }
+        Self { browser, cache: WebCache::new(DEFAULT_CACHE_TTL, None) }
+    }
+    
+    pub(crate) fn with_cache(mut self, cache: WebCache) -> Self {
+        self.cache = cache;
+        self
+    }
+    
+    /// Fetch the full page text via the browser or plain HTTP
+    ///
+    /// Custom headers or redirect limits only apply to plain HTTP, so they skip the browser.
+    async fn fetch_page(&self, url: &str, options: &FetchOptions) -> Result<String> {
+        if let Some(browser) = self.browser.as_ref().filter(|_| options.is_default()) {
+            // First open the URL
+            browser.execute(json!({
+                "action": "open",
+                "url": url
+            })).await?;
+            
+            // Wait a tiny bit then read
+            tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+            
+            return browser.execute(json!({
+                "action": "read"
+            })).await;
+        }
+
+        // Fallback to direct HTTP fetch
+        let page = fetch_page_text(url, options).await?;
+        Ok(if page.final_url != url {
+            format!("Final URL: {}\n\n{}", page.final_url, page.text)
+        } else {
+            page.text
+        })
     }
 }
 
@@ -89,6 +239,7 @@ impl WebFetchTool {
 impl Tool for WebFetchTool {
     fn name(&self) -> &str { "web_fetch" }
     fn description(&self) -> &str { "Fetch content from a URL via browser or HTTP" }
+    fn cacheable(&self) -> bool { true }
     
     fn parameters(&self) -> Value {
         json!({
@@ -97,6 +248,18 @@ impl Tool for WebFetchTool {
                 "url": {
                     "type": "string",
                     "description": "URL to fetch"
+                },
+                "no_cache": {
+                    "type": "boolean",
+                    "description": "Fetch again even if the page was fetched recently (default: false)"
+                },
+                "headers": {
+                    "type": "object",
+                    "description": "Extra HTTP headers, e.g. {\"User-Agent\": \"Mozilla/5.0 ...\", \"Accept-Language\": \"en\"} for sites that block bots"
+                },
+                "max_redirects": {
+                    "type": "integer",
+                    "description": "Maximum redirects to follow (default 10, 0 = don't follow)"
                 }
             },
             "required": ["url"]
@@ -111,61 +274,147 @@ impl Tool for WebFetchTool {
         let max_len = params.get("max_length")
             .and_then(|v| v.as_u64())
             .unwrap_or(10000) as usize;
-
-        if let Some(browser) = &self.browser {
-            // First open the URL
-            browser.execute(json!({
-                "action": "open",
-                "url": url
-            })).await?;
-            
-            // Wait a tiny bit then read
-            tokio::time::sleep(std::time::Duration::from_secs(2)).await;
-            
-            let result = browser.execute(json!({
-                "action": "read"
-            })).await?;
-
-            return Ok(if result.len() > max_len {
-                format!("{}...\n\n[Truncated - {} total chars]", &result[..max_len], result.len())
-            } else {
-                result
-            });
-        }
-
-        // Fallback to direct HTTP fetch
-        let client = reqwest::Client::builder()
-            .timeout(std::time::Duration::from_secs(30))
-            .build()
-            .map_err(|e| Error::Tool(format!("Failed to create HTTP client: {}", e)))?;
-        
-        let response = client.get(url)
-            .header("User-Agent", "leo/1.0")
-            .send()
-            .await
-            .map_err(|e| Error::Tool(format!("Failed to fetch {}: {}", url, e)))?;
-        
-        let status = response.status();
-        if !status.is_success() {
-            return Err(Error::Tool(format!("HTTP error: {}", status)));
-        }
         
-        let text = response.text().await
-            .map_err(|e| Error::Tool(format!("Failed to read response: {}", e)))?;
+        let no_cache = params.get("no_cache")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
         
-        // Basic HTML to text conversion
-        let clean = html_to_text(&text);
-        
-        if clean.len() > max_len {
-            Ok(format!("{}...\n\n[Truncated - {} total chars]", &clean[..max_len], clean.len()))
-        } else {
-            Ok(clean)
+        let mut options = FetchOptions::default();
+        if let Some(headers) = params.get("headers").and_then(|v| v.as_object()) {
+            for (name, value) in headers {
+                let value = match value {
+                    Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                };
+                options.headers.push((name.clone(), value));
+            }
         }
+        options.max_redirects = params.get("max_redirects")
+            .and_then(|v| v.as_u64())
+            .map(|n| n as usize);
+
+        // Pages fetched with custom options may differ, so they bypass the cache
+        let cacheable = options.is_default();
+        let text = match self.cache.get(url).filter(|_| cacheable && !no_cache) {
+            Some(cached) => {
+                tracing::debug!("web_fetch cache hit for {}", url);
+                cached
+            }
+            None => {
+                let text = self.fetch_page(url, &options).await?;
+                if cacheable {
+                    self.cache.put(url, &text);
+                }
+                text
+            }
+        };
+        
+        Ok(truncate(text, max_len))
+    }
+}
+
+/// Fetch a URL over HTTP and return its content as plain text, truncated to `max_len`
+pub(super) async fn fetch_text(url: &str, max_len: usize) -> Result<String> {
+    fetch_page_text(url, &FetchOptions::default()).await
+        .map(|page| truncate(page.text, max_len))
+}
+
+/// Request options for a plain HTTP fetch
+#[derive(Debug, Default)]
+struct FetchOptions {
+    /// Extra headers; a `User-Agent` here replaces the default one
+    headers: Vec<(String, String)>,
+    /// Redirects to follow (`None` = reqwest's default of 10)
+    max_redirects: Option<usize>,
+}
+
+impl FetchOptions {
+    fn is_default(&self) -> bool {
+        self.headers.is_empty() && self.max_redirects.is_none()
     }
 }
 
+/// Text of a fetched page and the URL it was finally served from
+struct FetchedPage {
+    final_url: String,
+    text: String,
+}
+
+/// Cut `text` to `max_len` bytes (on a char boundary), noting the original length
+fn truncate(text: String, max_len: usize) -> String {
+    if text.len() <= max_len {
+        return text;
+    }
+    let mut end = max_len;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}...\n\n[Truncated - {} total chars]", &text[..end], text.len())
+}
+
+/// Error for a failed request to `url`, keeping timeouts distinct
+pub(crate) fn fetch_error(url: &str, err: reqwest::Error) -> Error {
+    if err.is_timeout() {
+        Error::Timeout(format!("fetching {}", url))
+    } else {
+        Error::Tool(format!("Failed to fetch {}: {}", url, err))
+    }
+}
+
+/// Fetch a URL over HTTP and return its full content as plain text
+async fn fetch_page_text(url: &str, options: &FetchOptions) -> Result<FetchedPage> {
+    use reqwest::header::{HeaderMap, HeaderName, HeaderValue, LOCATION, USER_AGENT};
+    use reqwest::redirect::Policy;
+    
+    let mut headers = HeaderMap::new();
+    headers.insert(USER_AGENT, HeaderValue::from_static("leo/1.0"));
+    for (name, value) in &options.headers {
+        let name = HeaderName::from_bytes(name.as_bytes())
+            .map_err(|_| Error::Tool(format!("Invalid header name: {}", name)))?;
+        let value = HeaderValue::from_str(value)
+            .map_err(|_| Error::Tool(format!("Invalid value for header {}", name)))?;
+        headers.insert(name, value);
+    }
+    
+    let redirect = match options.max_redirects {
+        Some(0) => Policy::none(),
+        Some(n) => Policy::limited(n),
+        None => Policy::default(),
+    };
+    
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(30))
+        .redirect(redirect)
+        .build()
+        .map_err(|e| Error::Tool(format!("Failed to create HTTP client: {}", e)))?;
+    
+    let response = client.get(url)
+        .headers(headers)
+        .send()
+        .await
+        .map_err(|e| fetch_error(url, e))?;
+    
+    let status = response.status();
+    if status.is_redirection() {
+        let location = response.headers().get(LOCATION)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("unknown");
+        return Err(Error::Tool(format!("HTTP {} redirect to {} (not followed)", status, location)));
+    }
+    if !status.is_success() {
+        return Err(Error::Tool(format!("HTTP error: {}", status)));
+    }
+    
+    let final_url = response.url().to_string();
+    let text = response.text().await
+        .map_err(|e| Error::Tool(format!("Failed to read response: {}", e)))?;
+    
+    // Basic HTML to text conversion
+    Ok(FetchedPage { final_url, text: html_to_text(&text) })
+}
+
 /// Very basic HTML to text conversion
-fn html_to_text(html: &str) -> String {
+pub(super) fn html_to_text(html: &str) -> String {
     // Remove script/style tags and their content
     let mut text = html.to_string();
     
@@ -202,10 +451,65 @@ fn html_to_text(html: &str) -> String {
         }
     }
     
+    // Decode entities only now, so an escaped "&lt;" can't start a tag
+    let result = decode_entities(&result);
+    
     // Collapse whitespace aggressively
     result.split_whitespace().collect::<Vec<_>>().join(" ")
 }
 
+/// Named entities common enough on real pages to be worth decoding
+const NAMED_ENTITIES: &[(&str, &str)] = &[
+    ("amp", "&"), ("lt", "<"), ("gt", ">"), ("quot", "\""), ("apos", "'"),
+    ("nbsp", " "), ("ndash", "–"), ("mdash", "—"), ("hellip", "…"),
+    ("lsquo", "‘"), ("rsquo", "’"), ("ldquo", "“"), ("rdquo", "”"),
+    ("copy", "©"), ("reg", "®"), ("trade", "™"), ("deg", "°"), ("middot", "·"),
+    ("bull", "•"), ("times", "×"), ("euro", "€"), ("pound", "£"),
+];
+
+/// Decode `&name;`, `&#NN;` and `&#xHH;` entities, leaving unknown ones as-is
+fn decode_entities(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        
+        // Entities are short; don't scan far for the ';'
+        let decoded = rest[1..].find(';')
+            .filter(|&end| end > 0 && end <= 10)
+            .and_then(|end| {
+                let name = &rest[1..end + 1];
+                let value = if let Some(num) = name.strip_prefix('#') {
+                    let code = match num.strip_prefix(['x', 'X']) {
+                        Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                        None => num.parse().ok(),
+                    };
+                    code.and_then(char::from_u32).map(String::from)
+                } else {
+                    NAMED_ENTITIES.iter()
+                        .find(|(n, _)| *n == name)
+                        .map(|(_, v)| v.to_string())
+                };
+                value.map(|v| (v, end + 2))
+            });
+        
+        match decoded {
+            Some((value, len)) => {
+                out.push_str(&value);
+                rest = &rest[len..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -217,6 +521,12 @@ mod tests {
         assert!(text.contains("Hello World"));
     }
     
+    #[test]
+    fn test_html_to_text_decodes_entities() {
+        let html = "<p>Tom &amp; Jerry&#x27;s&nbsp;show &lt;b&gt; &#8212; &copy; &bogus; & more</p>";
+        assert_eq!(html_to_text(html), "Tom & Jerry's show <b> — © &bogus; & more");
+    }
+    
     #[test]
     fn test_html_to_text_removes_scripts() {
         let html = "<body><script>alert('hi');</script><p>Content</p></body>";
@@ -224,4 +534,89 @@ mod tests {
         assert!(text.contains("Content"));
         assert!(!text.contains("alert"));
     }
+    
+    #[test]
+    fn test_truncate_respects_char_boundaries() {
+        assert_eq!(truncate("short".to_string(), 10), "short");
+        let cut = truncate("héllo".to_string(), 2);
+        assert!(cut.starts_with("h..."));
+        assert!(cut.contains("[Truncated - 6 total chars]"));
+    }
+    
+    #[test]
+    fn test_web_cache_memory_and_disk() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = WebCache::new(Duration::from_secs(60), Some(dir.path().to_path_buf()));
+        assert!(cache.get("https://example.com").is_none());
+        
+        cache.put("https://example.com", "Example page");
+        assert_eq!(cache.get("https://example.com").as_deref(), Some("Example page"));
+        
+        // A fresh cache over the same directory is served from disk
+        let reloaded = WebCache::new(Duration::from_secs(60), Some(dir.path().to_path_buf()));
+        assert_eq!(reloaded.get("https://example.com").as_deref(), Some("Example page"));
+        assert!(reloaded.get("https://example.org").is_none());
+        
+        // A zero TTL disables caching entirely
+        let disabled = WebCache::new(Duration::ZERO, Some(dir.path().to_path_buf()));
+        assert!(disabled.get("https://example.com").is_none());
+    }
+    
+    #[tokio::test]
+    async fn test_web_fetch_uses_cache() {
+        let tool = WebFetchTool::new(None);
+        tool.cache.put("http://127.0.0.1:9/never", "cached text");
+        
+        let result = tool.execute(json!({"url": "http://127.0.0.1:9/never"})).await.unwrap();
+        assert_eq!(result, "cached text");
+        
+        // Forcing a refresh goes to the network (and fails against the closed port)
+        let refreshed = tool.execute(json!({"url": "http://127.0.0.1:9/never", "no_cache": true})).await;
+        assert!(refreshed.is_err());
+    }
+    
+    #[tokio::test]
+    async fn test_web_fetch_headers_and_redirects() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        
+        // Redirects /start to /final; /final echoes whether our headers arrived
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut buf = [0u8; 2048];
+                let n = socket.read(&mut buf).await.unwrap();
+                let request = String::from_utf8_lossy(&buf[..n]).to_lowercase();
+                let response = if request.starts_with("get /start") {
+                    "HTTP/1.1 302 Found\r\nLocation: /final\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string()
+                } else {
+                    let body = format!(
+                        "<p>agent={} lang={}</p>",
+                        request.contains("user-agent: mozilla/5.0"),
+                        request.contains("accept-language: fr")
+                    );
+                    format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: text/html\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        body.len(),
+                        body
+                    )
+                };
+                socket.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        
+        let tool = WebFetchTool::new(None);
+        let start = format!("http://{}/start", addr);
+        
+        let result = tool.execute(json!({
+            "url": start,
+            "headers": {"User-Agent": "Mozilla/5.0", "Accept-Language": "fr"}
+        })).await.unwrap();
+        assert!(result.starts_with(&format!("Final URL: http://{}/final", addr)));
+        assert!(result.contains("agent=true lang=true"));
+        
+        let blocked = tool.execute(json!({"url": start, "max_redirects": 0})).await.unwrap_err();
+        assert!(blocked.to_string().contains("redirect to /final"));
+    }
 }
@@ -0,0 +1,232 @@
+//! Search APIs behind the `web_search` tool
+//!
+//! With an API key in `web_search` config, results come straight from Brave
+//! Search or SerpAPI; otherwise `web_search` falls back to the browser bridge.
+
+use std::time::Duration;
+use async_trait::async_trait;
+use serde_json::Value;
+use crate::Result;
+use crate::config::SearchConfig;
+use crate::error::Error;
+use super::web::{fetch_error, html_to_text};
+
+const BRAVE_URL: &str = "https://api.search.brave.com/res/v1/web/search";
+const SERPAPI_URL: &str = "https://serpapi.com/search.json";
+
+/// How long a search API gets to answer
+const SEARCH_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// One search hit
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchResult {
+    pub title: String,
+    pub url: String,
+    pub snippet: String,
+}
+
+/// A web search API
+#[async_trait]
+pub trait SearchProvider: Send + Sync {
+    /// Name shown in results, e.g. "Brave Search"
+    fn name(&self) -> &str;
+
+    /// Up to `count` results for `query`
+    async fn search(&self, query: &str, count: usize) -> Result<Vec<SearchResult>>;
+}
+
+/// The provider configured in `web_search`, if any (Brave wins when both keys are set)
+pub fn provider_from_config(config: &SearchConfig) -> Option<Box<dyn SearchProvider>> {
+    if !config.brave_api_key.is_empty() {
+        Some(Box::new(BraveSearch::new(&config.brave_api_key)))
+    } else if !config.serpapi_api_key.is_empty() {
+        Some(Box::new(SerpApiSearch::new(&config.serpapi_api_key)))
+    } else {
+        None
+    }
+}
+
+/// Numbered results with their URLs, ready for the model to pick one to fetch
+pub fn format_results(provider: &str, query: &str, results: &[SearchResult]) -> String {
+    if results.is_empty() {
+        return format!("No results for '{}' ({})", query, provider);
+    }
+    let mut out = format!("Results for '{}' ({}):\n", query, provider);
+    for (i, result) in results.iter().enumerate() {
+        out.push_str(&format!("\n{}. {}\n   {}\n", i + 1, result.title, result.url));
+        if !result.snippet.is_empty() {
+            out.push_str(&format!("   {}\n", result.snippet));
+        }
+    }
+    out.push_str("\nUse web_fetch on a URL to read the page.");
+    out
+}
+
+/// GET `url` and parse the JSON body
+///
+/// Errors leave out the request URL, whose query string may hold the API key.
+async fn get_json(request: reqwest::RequestBuilder, url: &str) -> Result<Value> {
+    let response = request
+        .timeout(SEARCH_TIMEOUT)
+        .send()
+        .await
+        .map_err(|e| fetch_error(url, e.without_url()))?;
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        let body: String = body.chars().take(200).collect();
+        return Err(Error::Tool(format!("Search API error ({}): {}", status, body)));
+    }
+    response.json().await
+        .map_err(|e| Error::Tool(format!("Invalid search API response: {}", e.without_url())))
+}
+
+/// Build results from a JSON array, reading each hit's fields by name
+fn collect_results(items: Option<&Value>, url_key: &str, snippet_key: &str, count: usize) -> Vec<SearchResult> {
+    let field = |item: &Value, key: &str| item.get(key).and_then(|v| v.as_str()).unwrap_or("").to_string();
+    items
+        .and_then(|v| v.as_array())
+        .map(|items| {
+            items.iter()
+                .filter(|item| !field(item, url_key).is_empty())
+                .take(count)
+                .map(|item| SearchResult {
+                    // Brave marks matches with <strong>; snippets may carry entities too
+                    title: html_to_text(&field(item, "title")),
+                    url: field(item, url_key),
+                    snippet: html_to_text(&field(item, snippet_key)),
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Brave Search API (https://brave.com/search/api/)
+pub struct BraveSearch {
+    api_key: String,
+    client: reqwest::Client,
+}
+
+impl BraveSearch {
+    pub fn new(api_key: &str) -> Self {
+        Self { api_key: api_key.to_string(), client: reqwest::Client::new() }
+    }
+
+    fn parse(body: &Value, count: usize) -> Vec<SearchResult> {
+        collect_results(body.pointer("/web/results"), "url", "description", count)
+    }
+}
+
+#[async_trait]
+impl SearchProvider for BraveSearch {
+    fn name(&self) -> &str { "Brave Search" }
+
+    async fn search(&self, query: &str, count: usize) -> Result<Vec<SearchResult>> {
+        let request = self.client.get(BRAVE_URL)
+            .header("Accept", "application/json")
+            .header("X-Subscription-Token", &self.api_key)
+            .query(&[("q", query), ("count", &count.to_string())]);
+        let body = get_json(request, BRAVE_URL).await?;
+        Ok(Self::parse(&body, count))
+    }
+}
+
+/// SerpAPI Google results (https://serpapi.com/)
+pub struct SerpApiSearch {
+    api_key: String,
+    client: reqwest::Client,
+}
+
+impl SerpApiSearch {
+    pub fn new(api_key: &str) -> Self {
+        Self { api_key: api_key.to_string(), client: reqwest::Client::new() }
+    }
+
+    fn parse(body: &Value, count: usize) -> Vec<SearchResult> {
+        collect_results(body.get("organic_results"), "link", "snippet", count)
+    }
+}
+
+#[async_trait]
+impl SearchProvider for SerpApiSearch {
+    fn name(&self) -> &str { "SerpAPI" }
+
+    async fn search(&self, query: &str, count: usize) -> Result<Vec<SearchResult>> {
+        let request = self.client.get(SERPAPI_URL)
+            .query(&[
+                ("engine", "google"),
+                ("q", query),
+                ("num", &count.to_string()),
+                ("api_key", &self.api_key),
+            ]);
+        let body = get_json(request, SERPAPI_URL).await?;
+        if let Some(error) = body.get("error").and_then(|v| v.as_str()) {
+            return Err(Error::Tool(format!("SerpAPI error: {}", error)));
+        }
+        Ok(Self::parse(&body, count))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_errors_leave_out_the_api_key() {
+        // Nothing listens on port 9, so the request fails before any response
+        let url = "http://127.0.0.1:9/search.json";
+        let request = reqwest::Client::new().get(url).query(&[("api_key", "s3cret-key")]);
+        let err = get_json(request, url).await.unwrap_err();
+        assert!(!err.to_string().contains("s3cret-key"), "{}", err);
+    }
+
+    #[test]
+    fn test_parse_brave_results() {
+        let body = json!({ "web": { "results": [
+            { "title": "The <strong>Rust</strong> Book", "url": "https://doc.rust-lang.org/book/",
+              "description": "Learn <strong>Rust</strong> &amp; more" },
+            { "title": "No URL" },
+            { "title": "Rust", "url": "https://www.rust-lang.org/", "description": "" }
+        ]}});
+        let results = BraveSearch::parse(&body, 5);
+        assert_eq!(results, vec![
+            SearchResult {
+                title: "The Rust Book".to_string(),
+                url: "https://doc.rust-lang.org/book/".to_string(),
+                snippet: "Learn Rust & more".to_string(),
+            },
+            SearchResult { title: "Rust".to_string(), url: "https://www.rust-lang.org/".to_string(), snippet: String::new() },
+        ]);
+        assert_eq!(BraveSearch::parse(&body, 1).len(), 1);
+        assert!(BraveSearch::parse(&json!({}), 5).is_empty());
+    }
+
+    #[test]
+    fn test_parse_serpapi_results() {
+        let body = json!({ "organic_results": [
+            { "title": "Tokio", "link": "https://tokio.rs/", "snippet": "An asynchronous runtime" }
+        ]});
+        let results = SerpApiSearch::parse(&body, 5);
+        assert_eq!(results[0].url, "https://tokio.rs/");
+        assert_eq!(results[0].snippet, "An asynchronous runtime");
+    }
+
+    #[test]
+    fn test_provider_from_config_and_format() {
+        assert!(provider_from_config(&SearchConfig::default()).is_none());
+        let config = SearchConfig { serpapi_api_key: "k".to_string(), ..SearchConfig::default() };
+        assert_eq!(provider_from_config(&config).unwrap().name(), "SerpAPI");
+        let both = SearchConfig { brave_api_key: "b".to_string(), ..config };
+        assert_eq!(provider_from_config(&both).unwrap().name(), "Brave Search");
+
+        let results = vec![SearchResult {
+            title: "Tokio".to_string(),
+            url: "https://tokio.rs/".to_string(),
+            snippet: "Async runtime".to_string(),
+        }];
+        let text = format_results("SerpAPI", "tokio", &results);
+        assert!(text.contains("1. Tokio\n   https://tokio.rs/\n   Async runtime"));
+        assert!(format_results("SerpAPI", "zzz", &[]).starts_with("No results"));
+    }
+}
@@ -70,7 +70,7 @@ pub fn print_leo_header_with_emotion(model: &str, provider: &str, emotion: LionE
     println!("  {} {}  {}", "Leo".yellow().bold(), version.black().bold(), "•".black().bold());
 
     // Animated sequence: Start -> Blink -> Target Emotion
-    let frames = vec![LionEmotion::Normal, LionEmotion::Blink, emotion];
+    let frames = [LionEmotion::Normal, LionEmotion::Blink, emotion];
     
     // Clear and print frames
     for (i, frame) in frames.iter().enumerate() {